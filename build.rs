@@ -0,0 +1,41 @@
+//! Build script
+//!
+//! With the `ffi` feature, generates the C header for the FFI layer.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+}
+
+/// Generate `intel_gpu_stats.h` from `src/ffi.rs` into `OUT_DIR`
+///
+/// The checked-in `include/intel_gpu_stats.h` is only updated when
+/// `INTEL_GPU_STATS_UPDATE_HEADER` is set, so normal builds never write to
+/// the source tree (which may be read-only, e.g. when vendored).
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    use std::path::Path;
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is set");
+    let crate_dir = Path::new(&crate_dir);
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set");
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=INTEL_GPU_STATS_UPDATE_HEADER");
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))
+        .expect("cbindgen.toml is valid");
+    match cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/ffi.rs"))
+        .generate()
+    {
+        Ok(bindings) => {
+            bindings.write_to_file(Path::new(&out_dir).join("intel_gpu_stats.h"));
+            if std::env::var_os("INTEL_GPU_STATS_UPDATE_HEADER").is_some() {
+                bindings.write_to_file(crate_dir.join("include/intel_gpu_stats.h"));
+            }
+        }
+        Err(e) => println!("cargo:warning=Failed to generate C header: {}", e),
+    }
+}
@@ -0,0 +1,26 @@
+//! Example: newline-delimited JSON output
+//!
+//! Writes one compact JSON object per sample to stdout, suitable for
+//! `tail -f` and log shippers such as Loki or Vector.
+//!
+//! Run with: cargo run --example json_lines --features json
+
+use intel_gpu_stats::record::JsonLinesWriter;
+use intel_gpu_stats::{IntelGpu, Result};
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+fn main() -> Result<()> {
+    let mut gpu = IntelGpu::detect()?;
+    let mut writer = JsonLinesWriter::new(io::stdout().lock());
+
+    // Initial read to establish baseline
+    let _ = gpu.read_stats()?;
+
+    loop {
+        thread::sleep(Duration::from_secs(1));
+        let stats = gpu.read_stats()?;
+        writer.write(&stats)?;
+    }
+}
@@ -8,10 +8,25 @@
 //! Note: This is a simple example without the serde dependency.
 //! For production use, consider adding serde with the "derive" feature.
 
-use intel_gpu_stats::{GpuStats, IntelGpu, Result};
+use intel_gpu_stats::{EngineUtilization, GpuStats, IntelGpu, Result};
 use std::thread;
 use std::time::Duration;
 
+/// Format one engine, writing unmeasured wait/sema as null
+fn engine_to_json(name: &str, engine: &EngineUtilization) -> String {
+    let percent = |value: Option<f64>| match value {
+        Some(value) => format!("{:.2}", value),
+        None => "null".to_string(),
+    };
+    format!(
+        "    \"{}\": {{ \"busy\": {:.2}, \"wait\": {}, \"sema\": {} }}",
+        name,
+        engine.busy_percent,
+        percent(engine.wait_percent),
+        percent(engine.sema_percent)
+    )
+}
+
 /// Format GPU stats as JSON string
 fn stats_to_json(stats: &GpuStats) -> String {
     let mut json = String::from("{\n");
@@ -24,37 +39,21 @@ fn stats_to_json(stats: &GpuStats) -> String {
 
     // Engines
     json.push_str("  \"engines\": {\n");
-    json.push_str(&format!(
-        "    \"render\": {{ \"busy\": {:.2}, \"wait\": {:.2}, \"sema\": {:.2} }},\n",
-        stats.engines.render.busy_percent,
-        stats.engines.render.wait_percent,
-        stats.engines.render.sema_percent
-    ));
-    json.push_str(&format!(
-        "    \"video\": {{ \"busy\": {:.2}, \"wait\": {:.2}, \"sema\": {:.2} }},\n",
-        stats.engines.video.busy_percent,
-        stats.engines.video.wait_percent,
-        stats.engines.video.sema_percent
-    ));
-    json.push_str(&format!(
-        "    \"video_enhance\": {{ \"busy\": {:.2}, \"wait\": {:.2}, \"sema\": {:.2} }},\n",
-        stats.engines.video_enhance.busy_percent,
-        stats.engines.video_enhance.wait_percent,
-        stats.engines.video_enhance.sema_percent
-    ));
-    json.push_str(&format!(
-        "    \"blitter\": {{ \"busy\": {:.2}, \"wait\": {:.2}, \"sema\": {:.2} }}",
-        stats.engines.blitter.busy_percent,
-        stats.engines.blitter.wait_percent,
-        stats.engines.blitter.sema_percent
+    json.push_str(&engine_to_json("render", &stats.engines.render));
+    json.push_str(",\n");
+    json.push_str(&engine_to_json("video", &stats.engines.video));
+    json.push_str(",\n");
+    json.push_str(&engine_to_json(
+        "video_enhance",
+        &stats.engines.video_enhance,
     ));
+    json.push_str(",\n");
+    json.push_str(&engine_to_json("blitter", &stats.engines.blitter));
 
     if let Some(ref compute) = stats.engines.compute {
         json.push_str(",\n");
-        json.push_str(&format!(
-            "    \"compute\": {{ \"busy\": {:.2}, \"wait\": {:.2}, \"sema\": {:.2} }}\n",
-            compute.busy_percent, compute.wait_percent, compute.sema_percent
-        ));
+        json.push_str(&engine_to_json("compute", compute));
+        json.push('\n');
     } else {
         json.push('\n');
     }
@@ -89,6 +88,9 @@ fn stats_to_json(stats: &GpuStats) -> String {
         if let Some(package) = power.package_watts {
             json.push_str(&format!(",\n    \"package_watts\": {:.2}", package));
         }
+        if let Some(platform) = power.platform_watts {
+            json.push_str(&format!(",\n    \"platform_watts\": {:.2}", platform));
+        }
         json.push_str("\n  }");
     }
 
@@ -32,8 +32,10 @@ fn main() -> Result<()> {
 
                 println!("  PCI Path:    {}", gpu.pci_path);
 
-                if let Some(driver) = gpu.driver {
-                    println!("  Driver:      {}", driver);
+                match (gpu.driver, &gpu.driver_name) {
+                    (Some(driver), _) => println!("  Driver:      {}", driver),
+                    (None, Some(name)) => println!("  Driver:      {} (unsupported)", name),
+                    (None, None) => {}
                 }
 
                 if let Some(ref node) = gpu.card_node {
@@ -44,6 +46,17 @@ fn main() -> Result<()> {
                     println!("  Render Node: {}", node);
                 }
 
+                if let Some(ref speed) = gpu.pcie_link_speed {
+                    match gpu.pcie_link_width {
+                        Some(width) => println!("  PCIe Link:   {} x{}", speed, width),
+                        None => println!("  PCIe Link:   {}", speed),
+                    }
+                }
+
+                if let Some(count) = gpu.media_engine_count() {
+                    println!("  Media Engines: {}", count);
+                }
+
                 println!();
             }
 
@@ -52,12 +65,16 @@ fn main() -> Result<()> {
             println!();
 
             for gpu in &gpus {
-                match IntelGpu::open(&gpu.id) {
+                match IntelGpu::open_info(gpu.clone()) {
                     Ok(opened) => {
                         println!("{}: OK", gpu.id);
-                        if opened.has_compute_engine() {
+                        let caps = opened.capabilities();
+                        if caps.compute {
                             println!("  - Has Compute engine (Intel Arc)");
                         }
+                        if !caps.rc6 {
+                            println!("  - RC6 residency unavailable");
+                        }
                     }
                     Err(e) => {
                         println!("{}: Error - {}", gpu.id, e);
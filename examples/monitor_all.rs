@@ -0,0 +1,53 @@
+//! Example: Monitor every Intel GPU at once
+//!
+//! Opens all Intel GPUs (e.g. the iGPU and an Arc card) and prints one line
+//! per GPU every second. Works the same on Linux and Windows.
+//!
+//! Run with: cargo run --example monitor_all
+//!
+//! Note: Requires appropriate permissions (root, render group, or CAP_PERFMON)
+
+use intel_gpu_stats::{IntelGpu, Result};
+use std::thread;
+use std::time::Duration;
+
+fn main() -> Result<()> {
+    let mut gpus = IntelGpu::open_all()?;
+
+    for gpu in &gpus {
+        let info = gpu.gpu_info();
+        println!(
+            "{}: {} ({})",
+            info.id,
+            info.device_name.as_deref().unwrap_or("Unknown"),
+            gpu.driver()
+        );
+    }
+    println!();
+
+    // Initial read to establish each GPU's baseline
+    for gpu in &mut gpus {
+        gpu.read_stats()?;
+    }
+
+    loop {
+        thread::sleep(Duration::from_secs(1));
+
+        let mut total_power = 0.0;
+        for gpu in &mut gpus {
+            let stats = gpu.read_stats()?;
+            let power = stats.power.as_ref().map_or(0.0, |p| p.gpu_watts);
+            total_power += power;
+            println!(
+                "{:<10} render {:5.1}%  media {:5.1}%  {:4} MHz  {:5.1} W",
+                gpu.gpu_info().id,
+                stats.engines.render.busy_percent,
+                stats.engines.quicksync_utilization(),
+                stats.frequency.actual_mhz,
+                power
+            );
+        }
+        println!("{:<10} {:>44.1} W", "total", total_power);
+        println!();
+    }
+}
@@ -0,0 +1,81 @@
+/* Generated by cbindgen from src/ffi.rs - do not edit. */
+
+#ifndef INTEL_GPU_STATS_H
+#define INTEL_GPU_STATS_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The call succeeded
+#define IGS_OK 0
+
+// A required pointer argument was null
+#define IGS_ERR_NULL -1
+
+// Reading statistics from the GPU failed
+#define IGS_ERR_READ -2
+
+// Opaque handle to an opened GPU
+typedef struct IgsGpu IgsGpu;
+
+// Flattened GPU statistics, mirroring [`GpuSummary`]
+//
+// Missing data is reported as zero (or `false` for `throttled`).
+typedef struct CStats {
+  // Highest busy percentage across all engines (0.0 - 100.0)
+  double busy_percent;
+  // Quick Sync encode (video enhance) busy percentage
+  double encode_percent;
+  // Quick Sync decode (video) busy percentage
+  double decode_percent;
+  // Actual GPU frequency in MHz
+  uint32_t actual_mhz;
+  // GPU power draw in watts
+  double gpu_watts;
+  // GPU temperature in Celsius
+  double temperature_celsius;
+  // Whether any throttle reason is active
+  bool throttled;
+} CStats;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Detect and open the first available Intel GPU
+//
+// Returns null if no GPU could be opened. The handle must be released with
+// [`igs_free`].
+struct IgsGpu *igs_detect(void);
+
+// Read current statistics into `out`
+//
+// Utilization is measured since the previous read (or since the GPU was
+// opened). Returns [`IGS_OK`] on success, [`IGS_ERR_NULL`] if an argument is
+// null, or [`IGS_ERR_READ`] if reading failed; `out` is only written on
+// success.
+//
+// # Safety
+//
+// `gpu` must be null or a handle returned by [`igs_detect`] that has not
+// been freed, and `out` must be null or valid for writing a [`CStats`]. The
+// handle must not be used from several threads at the same time.
+int32_t igs_read_stats(struct IgsGpu *gpu, struct CStats *out);
+
+// Close a GPU handle
+//
+// Passing null is a no-op.
+//
+// # Safety
+//
+// `gpu` must be null or a handle returned by [`igs_detect`] that has not
+// already been freed. It must not be used afterwards.
+void igs_free(struct IgsGpu *gpu);
+
+#ifdef __cplusplus
+} // extern "C"
+#endif // __cplusplus
+
+#endif /* INTEL_GPU_STATS_H */
@@ -0,0 +1,498 @@
+//! Compact binary encoding of [`GpuStats`] for IPC
+//!
+//! The format is a fixed little-endian layout with no external dependencies,
+//! intended for shipping samples between processes at high frequency where
+//! JSON is too heavy.
+//!
+//! # Layout (version 16)
+//!
+//! | Field                     | Encoding                                   |
+//! |---------------------------|--------------------------------------------|
+//! | magic                     | `b"IGPS"`                                  |
+//! | version                   | `u16`                                      |
+//! | `sample_duration_ns`      | `u64`                                      |
+//! | render, video, video_enhance, blitter | each `f64` busy, optional `f64` wait, optional `f64` sema, `f64` peak busy, `u64` busy ns, optional `u64` context switches |
+//! | compute                   | optional engine                            |
+//! | video_instances           | `u16` count, then engines                  |
+//! | video_enhance_instances   | `u16` count, then engines                  |
+//! | compute_instances         | `u16` count, then engines                  |
+//! | frequency                 | `u32` actual MHz, `u32` requested MHz, optional `u32` min MHz, optional `u32` media MHz, optional `f64` media frequency factor |
+//! | power                     | optional: `f64` GPU W, optional `f64` package W, optional `f64` platform W, `u16` domain count, then per domain a string name and `f64` W |
+//! | rc6                       | optional `f64` residency                   |
+//! | temperature               | optional: `f64` °C, optional `u32` fan RPM, optional `f64` critical °C, optional `u8` fan mode (`pwm1_enable`), `u16` sensor count, then per sensor a `u32` index, string label and `f64` °C |
+//! | throttle                  | optional: `u8` bitmask, `u8` inferred flag |
+//! | memory                    | optional: 4 × `u64` (dedicated total, dedicated used, shared total, shared used) bytes |
+//! | perf_state                | optional `u8` (0 idle, 1 normal, 2 boost)  |
+//! | system_memory_bandwidth_gbps | optional `f64`                          |
+//!
+//! Optional values are prefixed with a `u8` presence flag (0 or 1), strings
+//! with their `u16` length in bytes followed by UTF-8 data. The
+//! throttle bitmask holds `is_throttled` in bit 0, followed by the reasons in
+//! [`ThrottleReason::ALL`] order.
+//!
+//! `GpuStats::timestamp` is not encoded; decoded stats are stamped with the
+//! time of decoding.
+
+use std::time::Instant;
+
+use crate::error::{Error, Result};
+use crate::types::*;
+
+/// Magic bytes at the start of every encoded sample
+pub const MAGIC: [u8; 4] = *b"IGPS";
+
+/// Current version of the binary format
+pub const FORMAT_VERSION: u16 = 16;
+
+impl GpuStats {
+    /// Encode these stats in the compact binary format
+    ///
+    /// See the [`binary`](crate::binary) module for the layout.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes(&MAGIC);
+        writer.u16(FORMAT_VERSION);
+        writer.u64(self.sample_duration_ns);
+
+        let engines = &self.engines;
+        writer.engine(&engines.render);
+        writer.engine(&engines.video);
+        writer.engine(&engines.video_enhance);
+        writer.engine(&engines.blitter);
+        writer.option(engines.compute.as_ref(), Writer::engine);
+        writer.engine_list(&engines.video_instances);
+        writer.engine_list(&engines.video_enhance_instances);
+        writer.engine_list(&engines.compute_instances);
+
+        writer.u32(self.frequency.actual_mhz);
+        writer.u32(self.frequency.requested_mhz);
+        writer.option(self.frequency.min_mhz, Writer::u32);
+        writer.option(self.frequency.media_mhz, Writer::u32);
+        writer.option(self.frequency.media_freq_factor, Writer::f64);
+
+        writer.option(self.power.as_ref(), |writer, power| {
+            writer.f64(power.gpu_watts);
+            writer.option(power.package_watts, Writer::f64);
+            writer.option(power.platform_watts, Writer::f64);
+            writer.list(&power.domains, |writer, (name, watts)| {
+                writer.string(name);
+                writer.f64(*watts);
+            });
+        });
+        writer.option(self.rc6.as_ref(), |writer, rc6| {
+            writer.f64(rc6.residency_percent)
+        });
+        writer.option(self.temperature.as_ref(), |writer, temp| {
+            writer.f64(temp.gpu_celsius);
+            writer.option(temp.fan_rpm, Writer::u32);
+            writer.option(temp.critical_celsius, Writer::f64);
+            writer.option(temp.fan_control_mode, |writer, mode| {
+                writer.u8(mode.pwm_enable() as u8)
+            });
+            writer.list(&temp.sensors, |writer, sensor| {
+                writer.u32(sensor.index);
+                writer.string(&sensor.label);
+                writer.f64(sensor.celsius);
+            });
+        });
+        writer.option(self.throttle.as_ref(), |writer, throttle| {
+            writer.u8(throttle_mask(throttle));
+            writer.u8(throttle.inferred as u8);
+        });
+        writer.option(self.memory.as_ref(), |writer, memory| {
+            writer.u64(memory.dedicated_total_bytes);
+            writer.u64(memory.dedicated_used_bytes);
+            writer.u64(memory.shared_total_bytes);
+            writer.u64(memory.shared_used_bytes);
+        });
+        writer.option(self.perf_state, |writer, state| writer.u8(state as u8));
+        writer.option(self.system_memory_bandwidth_gbps, Writer::f64);
+
+        writer.buf
+    }
+
+    /// Decode stats from the compact binary format
+    ///
+    /// Returns [`Error::Decode`] if the magic or version does not match, or
+    /// the data is truncated or has trailing bytes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<GpuStats> {
+        let mut reader = Reader::new(bytes);
+
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(decode_error("bad magic"));
+        }
+        let version = reader.u16()?;
+        if version != FORMAT_VERSION {
+            return Err(decode_error(format!(
+                "unsupported format version {}",
+                version
+            )));
+        }
+
+        let mut stats = GpuStats::new(Instant::now(), reader.u64()?);
+
+        stats.engines.render = reader.engine()?;
+        stats.engines.video = reader.engine()?;
+        stats.engines.video_enhance = reader.engine()?;
+        stats.engines.blitter = reader.engine()?;
+        stats.engines.compute = reader.option(Reader::engine)?;
+        stats.engines.video_instances = reader.engine_list()?;
+        stats.engines.video_enhance_instances = reader.engine_list()?;
+        stats.engines.compute_instances = reader.engine_list()?;
+
+        stats.frequency = FrequencyStats::new(reader.u32()?, reader.u32()?)
+            .with_min_mhz(reader.option(Reader::u32)?)
+            .with_media_mhz(reader.option(Reader::u32)?)
+            .with_media_freq_factor(reader.option(Reader::f64)?);
+
+        stats.power = reader.option(|reader| {
+            let mut power = PowerStats::new(reader.f64()?, reader.option(Reader::f64)?);
+            power.platform_watts = reader.option(Reader::f64)?;
+            power.domains = reader.list(|reader| Ok((reader.string()?, reader.f64()?)))?;
+            Ok(power)
+        })?;
+        stats.rc6 = reader.option(|reader| Ok(Rc6Stats::new(reader.f64()?)))?;
+        stats.temperature = reader.option(|reader| {
+            let mut temp = TemperatureStats::new(reader.f64()?);
+            temp.fan_rpm = reader.option(Reader::u32)?;
+            temp.critical_celsius = reader.option(Reader::f64)?;
+            temp.fan_control_mode =
+                reader.option(|reader| Ok(FanMode::from_pwm_enable(reader.u8()?.into())))?;
+            temp.sensors = reader.list(|reader| {
+                Ok(TemperatureSensor {
+                    index: reader.u32()?,
+                    label: reader.string()?,
+                    celsius: reader.f64()?,
+                })
+            })?;
+            Ok(temp)
+        })?;
+        stats.throttle = reader.option(|reader| {
+            let mut throttle = throttle_from_mask(reader.u8()?);
+            throttle.inferred = reader.u8()? != 0;
+            Ok(throttle)
+        })?;
+        stats.memory = reader.option(|reader| {
+            Ok(MemoryStats {
+                dedicated_total_bytes: reader.u64()?,
+                dedicated_used_bytes: reader.u64()?,
+                shared_total_bytes: reader.u64()?,
+                shared_used_bytes: reader.u64()?,
+            })
+        })?;
+        stats.perf_state = reader.option(|reader| {
+            PerfState::from_u8(reader.u8()?).ok_or_else(|| decode_error("bad perf state"))
+        })?;
+        stats.system_memory_bandwidth_gbps = reader.option(Reader::f64)?;
+
+        if !reader.is_empty() {
+            return Err(decode_error("trailing bytes after sample"));
+        }
+
+        Ok(stats)
+    }
+}
+
+fn decode_error(message: impl Into<String>) -> Error {
+    Error::Decode {
+        message: message.into(),
+    }
+}
+
+fn throttle_mask(info: &ThrottleInfo) -> u8 {
+    ThrottleReason::ALL
+        .iter()
+        .enumerate()
+        .filter(|(_, reason)| info.is_active(**reason))
+        .fold(info.is_throttled as u8, |mask, (index, _)| {
+            mask | (1 << (index + 1))
+        })
+}
+
+fn throttle_from_mask(mask: u8) -> ThrottleInfo {
+    let bit = |index: usize| mask & (1 << index) != 0;
+    ThrottleInfo {
+        is_throttled: bit(0),
+        status: bit(1),
+        power_limit: bit(2),
+        thermal: bit(3),
+        prochot: bit(4),
+        ratl: bit(5),
+        vr_thermal: bit(6),
+        vr_tdc: bit(7),
+        inferred: false,
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                write(self, value);
+            }
+            None => self.u8(0),
+        }
+    }
+
+    fn list<T>(&mut self, items: &[T], mut write: impl FnMut(&mut Self, &T)) {
+        // Lists (engine instances, power domains, sensors) are tiny; clamp rather than fail
+        let count = items.len().min(u16::MAX as usize);
+        self.u16(count as u16);
+        for item in &items[..count] {
+            write(self, item);
+        }
+    }
+
+    fn string(&mut self, value: &str) {
+        // Truncate on a character boundary if the string is absurdly long
+        let mut len = value.len().min(u16::MAX as usize);
+        while !value.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.u16(len as u16);
+        self.bytes(&value.as_bytes()[..len]);
+    }
+
+    fn engine(&mut self, engine: &EngineUtilization) {
+        self.f64(engine.busy_percent);
+        self.option(engine.wait_percent, Writer::f64);
+        self.option(engine.sema_percent, Writer::f64);
+        self.f64(engine.peak_busy_percent);
+        self.u64(engine.busy_ns);
+        self.option(engine.context_switches, Writer::u64);
+    }
+
+    fn engine_list(&mut self, engines: &[EngineUtilization]) {
+        self.list(engines, |writer, engine| writer.engine(engine));
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(decode_error("unexpected end of data"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn option<T>(&mut self, read: impl FnOnce(&mut Self) -> Result<T>) -> Result<Option<T>> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => read(self).map(Some),
+            flag => Err(decode_error(format!("invalid presence flag {}", flag))),
+        }
+    }
+
+    fn list<T>(&mut self, mut read: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        let count = self.u16()?;
+        (0..count).map(|_| read(self)).collect()
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.u16()? as usize;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| decode_error("invalid UTF-8 in string"))
+    }
+
+    fn engine(&mut self) -> Result<EngineUtilization> {
+        let mut engine = EngineUtilization::busy_only(self.f64()?);
+        engine.wait_percent = self.option(Reader::f64)?;
+        engine.sema_percent = self.option(Reader::f64)?;
+        engine.peak_busy_percent = self.f64()?;
+        engine.busy_ns = self.u64()?;
+        engine.context_switches = self.option(Reader::u64)?;
+        Ok(engine)
+    }
+
+    fn engine_list(&mut self) -> Result<Vec<EngineUtilization>> {
+        self.list(Reader::engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> GpuStats {
+        let mut stats = GpuStats::new(Instant::now(), 100_000_000);
+        stats.engines.render = EngineUtilization::new(12.5, 1.0, 0.5).with_busy_ns(12_500_000);
+        stats.engines.video = EngineUtilization::new(80.0, 0.0, 0.0);
+        stats.engines.video.context_switches = Some(42);
+        stats.engines.compute = Some(EngineUtilization::new(3.0, 0.0, 0.0));
+        stats.engines.compute_instances = vec![
+            EngineUtilization::new(3.0, 0.0, 0.0),
+            EngineUtilization::new(60.0, 0.0, 0.0),
+        ];
+        stats.engines.video_instances = vec![
+            EngineUtilization::new(80.0, 0.0, 0.0),
+            EngineUtilization::new(20.0, 0.0, 0.0),
+        ];
+        stats.frequency = FrequencyStats::new(1200, 1400)
+            .with_min_mhz(Some(300))
+            .with_media_mhz(Some(1100))
+            .with_media_freq_factor(Some(0.5));
+        let mut power = PowerStats::new(14.5, None);
+        power.platform_watts = Some(12.0);
+        power.domains = vec![("uncore".into(), 2.5), ("psys".into(), 12.0)];
+        stats.power = Some(power);
+        let mut temp = TemperatureStats::with_fan(61.0, 1800);
+        temp.fan_control_mode = Some(FanMode::Manual);
+        temp.sensors = vec![TemperatureSensor {
+            index: 2,
+            label: "vram".into(),
+            celsius: 70.0,
+        }];
+        stats.temperature = Some(temp);
+        stats.throttle = Some(ThrottleInfo {
+            is_throttled: true,
+            thermal: true,
+            vr_tdc: true,
+            inferred: true,
+            ..Default::default()
+        });
+        stats.memory = Some(MemoryStats {
+            dedicated_total_bytes: 16 << 30,
+            dedicated_used_bytes: 3 << 30,
+            shared_total_bytes: 32 << 30,
+            shared_used_bytes: 1 << 20,
+        });
+        stats.perf_state = Some(PerfState::Boost);
+        stats.system_memory_bandwidth_gbps = Some(12.5);
+        stats
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let stats = sample();
+        let bytes = stats.to_bytes();
+        assert_eq!(&bytes[..4], b"IGPS");
+
+        let decoded = GpuStats::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.sample_duration_ns, 100_000_000);
+        assert_eq!(decoded.engines.render.wait_percent, Some(1.0));
+        assert_eq!(decoded.engines.render.busy_ns, 12_500_000);
+        assert_eq!(decoded.engines.render.context_switches, None);
+        assert_eq!(decoded.engines.video.context_switches, Some(42));
+        assert_eq!(decoded.engines.video_instances.len(), 2);
+        assert_eq!(decoded.engines.compute.unwrap().busy_percent, 3.0);
+        assert_eq!(decoded.engines.compute_instances[1].busy_percent, 60.0);
+        assert_eq!(decoded.frequency.requested_mhz, 1400);
+        assert_eq!(decoded.frequency.min_mhz, Some(300));
+        assert_eq!(decoded.frequency.media_mhz, Some(1100));
+        assert_eq!(decoded.frequency.media_freq_factor, Some(0.5));
+        let power = decoded.power.unwrap();
+        assert_eq!(power.package_watts, None);
+        assert_eq!(power.platform_watts, Some(12.0));
+        assert_eq!(power.domains[1], ("psys".to_string(), 12.0));
+        assert!(decoded.rc6.is_none());
+        let temp = decoded.temperature.unwrap();
+        assert_eq!(temp.fan_rpm, Some(1800));
+        assert_eq!(temp.fan_control_mode, Some(FanMode::Manual));
+        assert_eq!(temp.sensors[0].label, "vram");
+
+        let throttle = decoded.throttle.unwrap();
+        assert!(throttle.is_throttled && throttle.thermal && throttle.vr_tdc);
+        assert!(!throttle.power_limit);
+        assert!(throttle.inferred);
+        let memory = decoded.memory.unwrap();
+        assert_eq!(memory.dedicated_used_bytes, 3 << 30);
+        assert_eq!(memory.shared_used_bytes, 1 << 20);
+        assert_eq!(decoded.perf_state, Some(PerfState::Boost));
+        assert_eq!(decoded.system_memory_bandwidth_gbps, Some(12.5));
+
+        // Re-encoding yields identical bytes
+        assert_eq!(GpuStats::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_rejects_invalid_input() {
+        let bytes = sample().to_bytes();
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(matches!(
+            GpuStats::from_bytes(&bad_magic),
+            Err(Error::Decode { .. })
+        ));
+
+        let mut bad_version = bytes.clone();
+        bad_version[4] = 0xff;
+        assert!(GpuStats::from_bytes(&bad_version).is_err());
+
+        assert!(GpuStats::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes;
+        trailing.push(0);
+        assert!(GpuStats::from_bytes(&trailing).is_err());
+    }
+}
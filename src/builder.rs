@@ -0,0 +1,514 @@
+//! Builder for opening a GPU with custom options
+//!
+//! `IntelGpu::detect()` and `IntelGpu::open()` use the defaults; use
+//! [`IntelGpuBuilder`] (via `IntelGpu::builder()`) to change them.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::types::{ComputeReporting, EngineClass, GpuInfo, GpuPreference};
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::error::{Error, Result};
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::IntelGpu;
+
+/// Default minimum time between two `read_stats` calls
+///
+/// Utilization is computed from counter deltas over the elapsed time, so
+/// reads closer together than this would divide by a near-zero duration.
+pub const DEFAULT_MIN_SAMPLE_INTERVAL: Duration = Duration::from_millis(1);
+
+/// How long opening with [`IntelGpuBuilder::warmup`] waits after priming
+pub const WARMUP_SETTLE: Duration = Duration::from_millis(100);
+
+/// How long opening with [`IntelGpuBuilder::probe_frequency`] waits to check
+/// that the frequency counters advance
+///
+/// i915 samples the frequency every 5ms while the GPU is awake, so this
+/// covers a few samples. An idle GPU does not advance them at all.
+pub const FREQUENCY_PROBE_WINDOW: Duration = Duration::from_millis(20);
+
+/// Options applied to an opened GPU
+#[derive(Debug, Clone)]
+pub(crate) struct OpenOptions {
+    /// Minimum time between reads before new stats are computed
+    pub(crate) min_sample_interval: Duration,
+    /// Engine instance to report as the primary one, per class
+    pub(crate) engine_instances: HashMap<EngineClass, u16>,
+    /// perf_event_attr flag bits used for engine events (Linux only)
+    pub(crate) engine_event_flags: u64,
+    /// Open all perf events stopped until `enable_counters` (Linux only)
+    pub(crate) start_disabled: bool,
+    /// Report video and video enhance as a single media engine
+    pub(crate) merge_media: bool,
+    /// How compute engine utilization is reported
+    pub(crate) compute_reporting: ComputeReporting,
+    /// Open the sensors even if the PMU can't be used (Linux only)
+    pub(crate) best_effort: bool,
+    /// Prime the counters and let them settle before returning from open
+    pub(crate) warmup: bool,
+    /// Check the frequency counters advance before returning from open
+    /// (Linux only)
+    pub(crate) probe_frequency: bool,
+    /// Read system memory bandwidth from the CPU's memory controller
+    /// (Linux only)
+    pub(crate) system_memory_bandwidth: bool,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self {
+            min_sample_interval: DEFAULT_MIN_SAMPLE_INTERVAL,
+            engine_instances: HashMap::new(),
+            engine_event_flags: 0,
+            start_disabled: false,
+            merge_media: false,
+            compute_reporting: ComputeReporting::Auto,
+            best_effort: false,
+            warmup: false,
+            probe_frequency: false,
+            system_memory_bandwidth: false,
+        }
+    }
+}
+
+/// Which GPU the builder opens
+#[derive(Debug, Clone)]
+enum Selection {
+    First,
+    Card(String),
+    Info(GpuInfo),
+    Preferred(GpuPreference),
+}
+
+/// Builder for opening an `IntelGpu` with custom options
+///
+/// ```rust,no_run
+/// use intel_gpu_stats::IntelGpu;
+/// use std::time::Duration;
+///
+/// let mut gpu = IntelGpu::builder()
+///     .card("card1")
+///     .min_sample_interval(Duration::from_millis(10))
+///     .open()?;
+/// # Ok::<(), intel_gpu_stats::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct IntelGpuBuilder {
+    selection: Selection,
+    options: OpenOptions,
+}
+
+impl IntelGpuBuilder {
+    /// Create a builder that opens the first available GPU with default options
+    pub fn new() -> Self {
+        Self {
+            selection: Selection::First,
+            options: OpenOptions::default(),
+        }
+    }
+
+    /// Open the GPU with the given card ID (e.g. "card0") instead of the first one
+    pub fn card(mut self, card_id: impl Into<String>) -> Self {
+        self.selection = Selection::Card(card_id.into());
+        self
+    }
+
+    /// Open a GPU previously returned by `IntelGpu::list_gpus`
+    pub fn gpu_info(mut self, gpu_info: GpuInfo) -> Self {
+        self.selection = Selection::Info(gpu_info);
+        self
+    }
+
+    /// Open the GPU that best matches a preference instead of the first one
+    ///
+    /// See [`GpuPreference`]. Useful on hybrid systems with both integrated
+    /// graphics and a discrete GPU.
+    pub fn prefer(mut self, preference: GpuPreference) -> Self {
+        self.selection = Selection::Preferred(preference);
+        self
+    }
+
+    /// Set the minimum time between reads
+    ///
+    /// If `read_stats` is called again before this much time has passed, it
+    /// returns the previous stats unchanged instead of computing utilization
+    /// over a tiny window. The very first read always computes new stats.
+    /// Defaults to [`DEFAULT_MIN_SAMPLE_INTERVAL`] (1ms); `Duration::ZERO`
+    /// disables the guard.
+    pub fn min_sample_interval(mut self, interval: Duration) -> Self {
+        self.options.min_sample_interval = interval;
+        self
+    }
+
+    /// Use a specific engine instance as the primary one for a class
+    ///
+    /// The primary instance is the one reported in e.g.
+    /// `GpuStats::engines.render`. By default it is the lowest instance the
+    /// PMU exposes, which is not necessarily 0 when engines are fused off.
+    /// Only used on Linux.
+    pub fn engine_instance(mut self, class: EngineClass, instance: u16) -> Self {
+        self.options.engine_instances.insert(class, instance);
+        self
+    }
+
+    /// Set the `perf_event_attr` flag bits used for engine events
+    ///
+    /// The bits (`linux::perf::flags`) are passed to `perf_event_open`
+    /// unchanged for the busy, wait and sema events (or the xe tick events);
+    /// frequency and RC6 events are not affected. Defaults to no flags. For
+    /// the i915 and xe PMUs:
+    ///
+    /// - `DISABLED` is supported: events are opened stopped, then reset and
+    ///   enabled so counting starts from a clean baseline.
+    /// - `PINNED` and `EXCLUSIVE` are accepted; they only matter if the
+    ///   counters would otherwise be multiplexed.
+    /// - `INHERIT` has no effect on these system-wide events.
+    /// - `EXCLUDE_USER`, `EXCLUDE_KERNEL`, `EXCLUDE_HV` and `EXCLUDE_IDLE`
+    ///   describe CPU execution modes, which GPU counters do not have. Kernels
+    ///   that mark the PMU as not supporting exclusion reject them, and the
+    ///   affected engines then fail to open.
+    ///
+    /// Only used on Linux.
+    pub fn perf_flags(mut self, flags: u64) -> Self {
+        self.options.engine_event_flags = flags;
+        self
+    }
+
+    /// Open the perf counters stopped
+    ///
+    /// No counting happens until `IntelGpu::enable_counters` is called, e.g.
+    /// when a monitoring UI becomes visible; until then `read_stats` reports
+    /// idle engines. Applies to engine, frequency and RC6 events. Defaults to
+    /// false. Only used on Linux.
+    pub fn start_disabled(mut self, start_disabled: bool) -> Self {
+        self.options.start_disabled = start_disabled;
+        self
+    }
+
+    /// Report video and video enhance as a single media engine
+    ///
+    /// Every sample from `read_stats` and the sampling thread then has the
+    /// combined figure in `engines.video` and an idle `engines.video_enhance`,
+    /// see [`EngineStats::merge_media`](crate::EngineStats::merge_media).
+    /// Useful for UIs showing one "Media" bar. Defaults to false.
+    pub fn merge_media(mut self, merge_media: bool) -> Self {
+        self.options.merge_media = merge_media;
+        self
+    }
+
+    /// Choose how compute engine (CCS) utilization is reported
+    ///
+    /// With [`ComputeReporting::Separate`] every sample has an
+    /// `engines.compute`, and with [`ComputeReporting::MergeIntoRender`] none
+    /// has, whether or not the GPU has a compute engine; see
+    /// [`EngineStats::report_compute`](crate::EngineStats::report_compute).
+    /// Gives charts the same engines across integrated and Arc GPUs.
+    /// Defaults to [`ComputeReporting::Auto`], reporting compute only where
+    /// the GPU has it. [`capabilities`](crate::IntelGpu::capabilities) still
+    /// describes the hardware.
+    pub fn compute_reporting(mut self, mode: ComputeReporting) -> Self {
+        self.options.compute_reporting = mode;
+        self
+    }
+
+    /// Open the GPU's sensors even if its PMU can't be used
+    ///
+    /// Normally opening fails when no PMU is found for the GPU, e.g. in a
+    /// container without `/sys/bus/event_source`. With this set the GPU is
+    /// opened anyway: temperature, power, throttle and sysfs frequency keep
+    /// working while engine utilization and RC6 are absent, as reported by
+    /// `capabilities()`. Defaults to false. Only used on Linux.
+    pub fn best_effort(mut self, best_effort: bool) -> Self {
+        self.options.best_effort = best_effort;
+        self
+    }
+
+    /// Prime the counters while opening so the first read is meaningful
+    ///
+    /// Right after opening, the first `read_stats` would measure the few
+    /// microseconds since the counters were opened, which gives noisy
+    /// utilization and no power reading. With this set, `open` takes an
+    /// initial reading and then waits [`WARMUP_SETTLE`] (100ms), so the
+    /// first `read_stats` covers at least that window. Opening takes that
+    /// much longer. Defaults to false.
+    pub fn warmup(mut self, warmup: bool) -> Self {
+        self.options.warmup = warmup;
+        self
+    }
+
+    /// Check that the PMU frequency counters work while opening
+    ///
+    /// Some kernels expose the frequency events but never advance them. The
+    /// counters are checked on every read, so
+    /// `IntelGpu::has_working_frequency` is only reliable after the first
+    /// `read_stats`. With this set, `open` waits [`FREQUENCY_PROBE_WINDOW`]
+    /// (20ms) and checks them, so it is reliable right away. Opening takes
+    /// that much longer per GPU. Defaults to false. Only used on Linux, and
+    /// not with [`start_disabled`](Self::start_disabled).
+    pub fn probe_frequency(mut self, probe_frequency: bool) -> Self {
+        self.options.probe_frequency = probe_frequency;
+        self
+    }
+
+    /// Report system memory bandwidth with the stats of an integrated GPU
+    ///
+    /// Opens the CPU's memory controller (uncore IMC) counters, which count
+    /// all DRAM traffic, the CPU's included; see
+    /// [`GpuStats::system_memory_bandwidth_gbps`](crate::GpuStats::system_memory_bandwidth_gbps).
+    /// Defaults to false. Only used on Linux, and ignored for discrete GPUs.
+    pub fn system_memory_bandwidth(mut self, enabled: bool) -> Self {
+        self.options.system_memory_bandwidth = enabled;
+        self
+    }
+
+    /// Open the selected GPU
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    pub fn open(self) -> Result<IntelGpu> {
+        let gpu_info = match self.selection {
+            Selection::Info(gpu_info) => gpu_info,
+            Selection::First => first_usable_gpu(IntelGpu::list_gpus()?)?,
+            Selection::Preferred(preference) => {
+                let gpus = IntelGpu::list_gpus()?;
+                let engine_counts = match preference {
+                    GpuPreference::MostEngines => IntelGpu::engine_counts(&gpus),
+                    _ => Vec::new(),
+                };
+                first_usable_gpu(rank_gpus(gpus, preference, &engine_counts))?
+            }
+            Selection::Card(card_id) => IntelGpu::list_gpus()?
+                .into_iter()
+                .find(|g| g.id == card_id)
+                .ok_or(Error::DeviceNotFound {
+                    path: card_id.into(),
+                })?,
+        };
+
+        let warmup = self.options.warmup;
+        let mut gpu = IntelGpu::open_with_options(gpu_info, self.options)?;
+        if warmup {
+            gpu.read_stats()?;
+            std::thread::sleep(WARMUP_SETTLE);
+        }
+        Ok(gpu)
+    }
+}
+
+impl Default for IntelGpuBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Pick the GPU opened by default: the first one not passed through to a VM
+///
+/// Returns [`Error::GpuPassthrough`] if every GPU is passed through, so the
+/// caller learns why nothing could be opened.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+pub(crate) fn first_usable_gpu(gpus: Vec<GpuInfo>) -> Result<GpuInfo> {
+    let passthrough = gpus
+        .iter()
+        .find(|g| g.is_passthrough())
+        .map(|g| g.id.clone());
+    match gpus.into_iter().find(|g| !g.is_passthrough()) {
+        Some(gpu) => Ok(gpu),
+        None => Err(match passthrough {
+            Some(card_id) => Error::GpuPassthrough { card_id },
+            None => Error::NoGpuFound,
+        }),
+    }
+}
+
+/// Order GPUs by how well they match a preference, best first
+///
+/// `engine_counts` holds the number of engine instances of each GPU and is
+/// only used for [`GpuPreference::MostEngines`]. The sort is stable, so
+/// equally good GPUs keep their discovery order.
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn rank_gpus(
+    gpus: Vec<GpuInfo>,
+    preference: GpuPreference,
+    engine_counts: &[usize],
+) -> Vec<GpuInfo> {
+    let mut ranked: Vec<(usize, GpuInfo)> = gpus
+        .into_iter()
+        .enumerate()
+        .map(|(index, gpu)| {
+            let score = match preference {
+                GpuPreference::Discrete => usize::from(!gpu.is_integrated()),
+                GpuPreference::Integrated => usize::from(gpu.is_integrated()),
+                GpuPreference::MostEngines => engine_counts.get(index).copied().unwrap_or(0),
+                GpuPreference::Driver(driver) => usize::from(gpu.driver == Some(driver)),
+            };
+            (score, gpu)
+        })
+        .collect();
+    ranked.sort_by_key(|&(score, _)| core::cmp::Reverse(score));
+    ranked.into_iter().map(|(_, gpu)| gpu).collect()
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl IntelGpu {
+    /// Create a builder for opening a GPU with custom options
+    pub fn builder() -> IntelGpuBuilder {
+        IntelGpuBuilder::new()
+    }
+
+    /// Detect and open the Intel GPU that best matches a preference
+    ///
+    /// Like `detect()`, but ranks the discovered GPUs instead of taking the
+    /// first one, e.g. to pick the Arc card rather than the iGPU on a hybrid
+    /// laptop. Falls back to the first usable GPU if none matches.
+    pub fn detect_preferring(preference: GpuPreference) -> Result<Self> {
+        Self::builder().prefer(preference).open()
+    }
+
+    /// Detect and open the first available Intel GPU, even without a PMU
+    ///
+    /// Like `detect()`, but if the GPU's PMU can't be used the reader still
+    /// provides temperature, power, throttle and sysfs frequency, with
+    /// engine utilization absent. Check [`capabilities`](Self::capabilities)
+    /// for what is working. See
+    /// [`IntelGpuBuilder::best_effort`] to combine this with other options.
+    pub fn detect_best_effort() -> Result<Self> {
+        Self::builder().best_effort(true).open()
+    }
+
+    /// Open every usable Intel GPU
+    ///
+    /// One reader per GPU from `list_gpus()`, in discovery order, e.g. both
+    /// the iGPU and an Arc card on a hybrid system. Each reader is
+    /// independent; read them in turn or give each its own sampling thread.
+    /// GPUs passed through to a VM are skipped. Fails if any other GPU
+    /// fails to open, and with [`Error::NoGpuFound`] if none is usable.
+    pub fn open_all() -> Result<Vec<Self>> {
+        let gpus: Vec<Self> = Self::list_gpus()?
+            .into_iter()
+            .filter(|gpu| !gpu.is_passthrough())
+            .map(Self::open_info)
+            .collect::<Result<_>>()?;
+        if gpus.is_empty() {
+            return Err(Error::NoGpuFound);
+        }
+        Ok(gpus)
+    }
+
+    /// Detect and open the first available Intel GPU with primed counters
+    ///
+    /// Like `detect()`, but the first `read_stats` already covers a valid
+    /// window, so no initial read and sleep is needed. Blocks for
+    /// [`WARMUP_SETTLE`] (100ms) longer; see [`IntelGpuBuilder::warmup`].
+    pub fn detect_warm() -> Result<Self> {
+        Self::builder().warmup(true).open()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_options() {
+        let builder = IntelGpuBuilder::new();
+        assert_eq!(
+            builder.options.min_sample_interval,
+            DEFAULT_MIN_SAMPLE_INTERVAL
+        );
+
+        let builder = builder
+            .card("card1")
+            .min_sample_interval(Duration::from_millis(50))
+            .engine_instance(EngineClass::Video, 1)
+            .perf_flags(1)
+            .start_disabled(true)
+            .merge_media(true)
+            .compute_reporting(ComputeReporting::MergeIntoRender)
+            .best_effort(true)
+            .warmup(true)
+            .probe_frequency(true)
+            .system_memory_bandwidth(true);
+        assert!(matches!(builder.selection, Selection::Card(ref id) if id == "card1"));
+        assert_eq!(builder.options.engine_instances[&EngineClass::Video], 1);
+        assert_eq!(builder.options.engine_event_flags, 1);
+        assert!(builder.options.start_disabled);
+        assert!(builder.options.merge_media);
+        assert_eq!(
+            builder.options.compute_reporting,
+            ComputeReporting::MergeIntoRender
+        );
+        assert!(builder.options.best_effort);
+        assert!(builder.options.warmup);
+        assert!(builder.options.probe_frequency);
+        assert!(builder.options.system_memory_bandwidth);
+        assert_eq!(
+            builder.options.min_sample_interval,
+            Duration::from_millis(50)
+        );
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    fn gpu(id: &str, driver: crate::types::GpuDriver) -> GpuInfo {
+        GpuInfo {
+            id: id.into(),
+            pci_path: String::new(),
+            device_name: None,
+            vendor_id: 0x8086,
+            device_id: 0x56a0,
+            render_node: None,
+            card_node: None,
+            driver: Some(driver),
+            driver_name: Some(driver.name().into()),
+            pcie_link_speed: None,
+            pcie_link_width: None,
+            media_engines: None,
+        }
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    #[test]
+    fn test_first_usable_gpu() {
+        use crate::types::GpuDriver;
+
+        let gpus = vec![gpu("card0", GpuDriver::Vfio), gpu("card1", GpuDriver::Xe)];
+        assert_eq!(first_usable_gpu(gpus).unwrap().id, "card1");
+
+        let gpus = vec![gpu("card0", GpuDriver::Vfio)];
+        assert!(matches!(
+            first_usable_gpu(gpus),
+            Err(Error::GpuPassthrough { card_id }) if card_id == "card0"
+        ));
+        assert!(matches!(
+            first_usable_gpu(Vec::new()),
+            Err(Error::NoGpuFound)
+        ));
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "windows"))]
+    #[test]
+    fn test_rank_gpus() {
+        use crate::types::GpuDriver;
+
+        let mut igpu = gpu("card0", GpuDriver::I915);
+        igpu.pci_path = "../../../0000:00:02.0".into();
+        let mut arc = gpu("card1", GpuDriver::Xe);
+        arc.pci_path = "../../../0000:00:01.0/0000:03:00.0".into();
+        let gpus = vec![igpu, arc];
+        let ids = |ranked: Vec<GpuInfo>| ranked.into_iter().map(|g| g.id).collect::<Vec<_>>();
+
+        assert_eq!(
+            ids(rank_gpus(gpus.clone(), GpuPreference::Discrete, &[])),
+            ["card1", "card0"]
+        );
+        assert_eq!(
+            ids(rank_gpus(gpus.clone(), GpuPreference::Integrated, &[])),
+            ["card0", "card1"]
+        );
+        assert_eq!(
+            ids(rank_gpus(gpus.clone(), GpuPreference::MostEngines, &[4, 9])),
+            ["card1", "card0"]
+        );
+        let i915 = GpuPreference::Driver(GpuDriver::I915);
+        assert_eq!(ids(rank_gpus(gpus, i915, &[])), ["card0", "card1"]);
+    }
+}
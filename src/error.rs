@@ -15,6 +15,14 @@ pub enum Error {
     #[error("No Intel GPU found on this system")]
     NoGpuFound,
 
+    /// The GPU is bound to vfio-pci (passed through to a VM) and cannot be
+    /// monitored from the host
+    #[error("GPU {card_id} is bound to vfio-pci (passed through to a VM) and cannot be monitored")]
+    GpuPassthrough {
+        /// Card ID of the passed-through GPU
+        card_id: String,
+    },
+
     /// The specified GPU device was not found
     #[error("GPU device not found: {path}")]
     DeviceNotFound {
@@ -22,9 +30,12 @@ pub enum Error {
         path: PathBuf,
     },
 
-    /// The i915 PMU (Performance Monitoring Unit) is not available
-    #[error("i915 PMU not available - ensure you have an Intel GPU with i915 driver loaded")]
-    PmuNotAvailable,
+    /// No usable i915/xe PMU (Performance Monitoring Unit) was found
+    #[error("GPU PMU not available: {reason}")]
+    PmuNotAvailable {
+        /// Why no PMU could be used
+        reason: String,
+    },
 
     /// A specific PMU event is not supported by this GPU/driver
     #[error("PMU event not supported: {event}")]
@@ -90,10 +101,24 @@ pub enum Error {
         message: String,
     },
 
+    /// A monitoring feature is not available on this GPU or platform
+    #[error("{feature} is not available on this GPU")]
+    FeatureNotAvailable {
+        /// The feature that is unavailable
+        feature: String,
+    },
+
     /// Platform not supported
     #[error("This platform is not currently supported")]
     PlatformNotSupported,
 
+    /// Recorded or serialized data could not be decoded
+    #[error("Failed to decode data: {message}")]
+    Decode {
+        /// Description of the decode error
+        message: String,
+    },
+
     /// Engine instance not found
     #[error("Engine {class}:{instance} not found")]
     EngineNotFound {
@@ -126,6 +151,24 @@ impl Error {
         }
     }
 
+    /// Create an error for a failed sysfs write
+    ///
+    /// Control files are only writable by root, so a permission error gets a
+    /// message naming the file rather than a generic I/O error.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn sysfs_write(path: &std::path::Path, source: io::Error) -> Self {
+        if source.kind() == io::ErrorKind::PermissionDenied {
+            Error::PermissionDenied {
+                message: format!("cannot write {}", path.display()),
+            }
+        } else {
+            Error::Io {
+                context: format!("Failed to write {}", path.display()),
+                source,
+            }
+        }
+    }
+
     /// Create a sysfs parse error
     #[cfg(target_os = "linux")]
     pub(crate) fn sysfs_parse(path: impl Into<PathBuf>, message: impl Into<String>) -> Self {
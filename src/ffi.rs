@@ -0,0 +1,160 @@
+//! C ABI for non-Rust consumers
+//!
+//! Enabled with the `ffi` feature. Exposes an opaque [`IgsGpu`] handle and a
+//! flat [`CStats`] struct mirroring [`GpuSummary`]:
+//!
+//! ```c
+//! #include "intel_gpu_stats.h"
+//!
+//! IgsGpu *gpu = igs_detect();
+//! if (gpu) {
+//!     CStats stats;
+//!     if (igs_read_stats(gpu, &stats) == IGS_OK) {
+//!         printf("encode: %.1f%%\n", stats.encode_percent);
+//!     }
+//!     igs_free(gpu);
+//! }
+//! ```
+//!
+//! The header in `include/intel_gpu_stats.h` is generated by cbindgen from
+//! this module. Builds with the `ffi` feature write it to `OUT_DIR`; after
+//! changing this module, refresh the checked-in copy with
+//! `INTEL_GPU_STATS_UPDATE_HEADER=1 cargo build --features ffi`. Build a
+//! library to link against with e.g.
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use crate::types::GpuSummary;
+use crate::IntelGpu;
+
+/// The call succeeded
+pub const IGS_OK: i32 = 0;
+/// A required pointer argument was null
+pub const IGS_ERR_NULL: i32 = -1;
+/// Reading statistics from the GPU failed
+pub const IGS_ERR_READ: i32 = -2;
+
+/// Opaque handle to an opened GPU
+pub struct IgsGpu {
+    gpu: IntelGpu,
+}
+
+/// Flattened GPU statistics, mirroring [`GpuSummary`]
+///
+/// Missing data is reported as zero (or `false` for `throttled`).
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CStats {
+    /// Highest busy percentage across all engines (0.0 - 100.0)
+    pub busy_percent: f64,
+    /// Quick Sync encode (video enhance) busy percentage
+    pub encode_percent: f64,
+    /// Quick Sync decode (video) busy percentage
+    pub decode_percent: f64,
+    /// Actual GPU frequency in MHz
+    pub actual_mhz: u32,
+    /// GPU power draw in watts
+    pub gpu_watts: f64,
+    /// GPU temperature in Celsius
+    pub temperature_celsius: f64,
+    /// Whether any throttle reason is active
+    pub throttled: bool,
+}
+
+impl From<GpuSummary> for CStats {
+    fn from(summary: GpuSummary) -> Self {
+        Self {
+            busy_percent: summary.busy_percent,
+            encode_percent: summary.encode_percent,
+            decode_percent: summary.decode_percent,
+            actual_mhz: summary.actual_mhz,
+            gpu_watts: summary.gpu_watts,
+            temperature_celsius: summary.temperature_celsius,
+            throttled: summary.throttled,
+        }
+    }
+}
+
+/// Detect and open the first available Intel GPU
+///
+/// Returns null if no GPU could be opened. The handle must be released with
+/// [`igs_free`].
+#[no_mangle]
+pub extern "C" fn igs_detect() -> *mut IgsGpu {
+    match IntelGpu::detect() {
+        Ok(gpu) => Box::into_raw(Box::new(IgsGpu { gpu })),
+        Err(_) => std::ptr::null_mut(),
+    }
+}
+
+/// Read current statistics into `out`
+///
+/// Utilization is measured since the previous read (or since the GPU was
+/// opened). Returns [`IGS_OK`] on success, [`IGS_ERR_NULL`] if an argument is
+/// null, or [`IGS_ERR_READ`] if reading failed; `out` is only written on
+/// success.
+///
+/// # Safety
+///
+/// `gpu` must be null or a handle returned by [`igs_detect`] that has not
+/// been freed, and `out` must be null or valid for writing a [`CStats`]. The
+/// handle must not be used from several threads at the same time.
+#[no_mangle]
+pub unsafe extern "C" fn igs_read_stats(gpu: *mut IgsGpu, out: *mut CStats) -> i32 {
+    if gpu.is_null() || out.is_null() {
+        return IGS_ERR_NULL;
+    }
+    match (*gpu).gpu.read_stats() {
+        Ok(stats) => {
+            out.write(stats.summary().into());
+            IGS_OK
+        }
+        Err(_) => IGS_ERR_READ,
+    }
+}
+
+/// Close a GPU handle
+///
+/// Passing null is a no-op.
+///
+/// # Safety
+///
+/// `gpu` must be null or a handle returned by [`igs_detect`] that has not
+/// already been freed. It must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn igs_free(gpu: *mut IgsGpu) {
+    if !gpu.is_null() {
+        drop(Box::from_raw(gpu));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_handles() {
+        let mut stats = CStats::default();
+        unsafe {
+            assert_eq!(
+                igs_read_stats(std::ptr::null_mut(), &mut stats),
+                IGS_ERR_NULL
+            );
+            igs_free(std::ptr::null_mut());
+        }
+        assert_eq!(stats, CStats::default());
+    }
+
+    #[test]
+    fn test_from_summary() {
+        let summary = GpuSummary {
+            encode_percent: 42.0,
+            actual_mhz: 1200,
+            throttled: true,
+            ..Default::default()
+        };
+        let stats = CStats::from(summary);
+        assert_eq!(stats.encode_percent, 42.0);
+        assert_eq!(stats.actual_mhz, 1200);
+        assert!(stats.throttled);
+    }
+}
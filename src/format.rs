@@ -0,0 +1,155 @@
+//! Text output in the layout of `intel_gpu_top -l`
+//!
+//! Eases migrating from `intel_gpu_top` and validating against it side by
+//! side: [`intel_gpu_top_header`] prints the two header lines and
+//! [`format_intel_gpu_top_style`] one line per sample.
+//!
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! use intel_gpu_stats::format::{format_intel_gpu_top_style, intel_gpu_top_header};
+//! use intel_gpu_stats::IntelGpu;
+//! use std::time::Duration;
+//!
+//! let mut gpu = IntelGpu::detect()?;
+//! let stats = gpu.read_stats()?;
+//! println!("{}", intel_gpu_top_header(&stats));
+//! loop {
+//!     std::thread::sleep(Duration::from_secs(1));
+//!     println!("{}", format_intel_gpu_top_style(&gpu.read_stats()?));
+//! }
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+//!
+//! Engines are labeled with the i915 abbreviations (see
+//! [`EngineClass::abbreviation`]) and listed per instance where the platform
+//! reports instances. The IRQ column is left out since it is not measured;
+//! other unavailable values are shown as `-` rather than zero.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::types::{EngineClass, EngineStats, EngineUtilization, GpuStats};
+
+/// Width of an engine column: `%`, `se` and `wa`
+const ENGINE_WIDTH: usize = 15;
+
+/// Column headers matching the engines in `stats`
+///
+/// Two lines, without a trailing newline. The engine columns depend on which
+/// engines and instances the sample reports, so pass a sample from the same
+/// GPU as the lines that follow.
+pub fn intel_gpu_top_header(stats: &GpuStats) -> String {
+    let mut top = format!("{:>9} {:>3} {:>11}", "Freq MHz", "RC6", "Power W");
+    let mut bottom = format!(
+        "{:>4} {:>4} {:>3} {:>5} {:>5}",
+        "req", "act", "%", "gpu", "pkg"
+    );
+    for (label, _) in engine_columns(&stats.engines) {
+        top.push_str(&format!(" {:>width$}", label, width = ENGINE_WIDTH));
+        bottom.push_str(&format!(" {:>7} {:>3} {:>3}", "%", "se", "wa"));
+    }
+    format!("{}\n{}", top, bottom)
+}
+
+/// One line of values in the layout of [`intel_gpu_top_header`]
+pub fn format_intel_gpu_top_style(stats: &GpuStats) -> String {
+    let power = stats.power.as_ref();
+    let mut line = format!(
+        "{:>4} {:>4} {} {} {}",
+        stats.frequency.requested_mhz,
+        stats.frequency.actual_mhz,
+        or_dash(stats.rc6.as_ref().map(|rc6| rc6.residency_percent), 3, 0),
+        or_dash(power.map(|power| power.gpu_watts), 5, 2),
+        or_dash(power.and_then(|power| power.package_watts), 5, 2),
+    );
+    for (_, engine) in engine_columns(&stats.engines) {
+        line.push_str(&format!(
+            " {:>7.2} {} {}",
+            engine.busy_percent,
+            or_dash(engine.sema_percent, 3, 0),
+            or_dash(engine.wait_percent, 3, 0),
+        ));
+    }
+    line
+}
+
+/// Format a value right-aligned, or `-` if it is unavailable
+fn or_dash(value: Option<f64>, width: usize, precision: usize) -> String {
+    match value {
+        Some(value) => format!("{:>width$.precision$}", value),
+        None => format!("{:>width$}", "-"),
+    }
+}
+
+/// Engine columns in `intel_gpu_top` order, labeled e.g. "VCS/1"
+fn engine_columns(engines: &EngineStats) -> Vec<(String, &EngineUtilization)> {
+    let classes = [
+        (EngineClass::Render, Some(&engines.render), &[][..]),
+        (EngineClass::Copy, Some(&engines.blitter), &[][..]),
+        (
+            EngineClass::Video,
+            Some(&engines.video),
+            &engines.video_instances[..],
+        ),
+        (
+            EngineClass::VideoEnhance,
+            Some(&engines.video_enhance),
+            &engines.video_enhance_instances[..],
+        ),
+        (
+            EngineClass::Compute,
+            engines.compute.as_ref(),
+            &engines.compute_instances[..],
+        ),
+    ];
+
+    let mut columns = Vec::new();
+    for (class, primary, instances) in classes {
+        let label = |instance: usize| format!("{}/{}", class.abbreviation(), instance);
+        if instances.is_empty() {
+            columns.extend(primary.map(|engine| (label(0), engine)));
+        } else {
+            columns.extend(
+                instances
+                    .iter()
+                    .enumerate()
+                    .map(|(instance, engine)| (label(instance), engine)),
+            );
+        }
+    }
+    columns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FrequencyStats, PowerStats, Rc6Stats};
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_intel_gpu_top_style() {
+        let mut stats = GpuStats::new(std::time::Instant::now(), 1_000_000_000);
+        stats.frequency = FrequencyStats::new(1150, 1200);
+        stats.rc6 = Some(Rc6Stats::new(12.0));
+        stats.power = Some(PowerStats::new(8.5, None));
+        stats.engines.render = EngineUtilization::new(45.5, 1.0, 2.0);
+        stats.engines.video_instances = vec![
+            EngineUtilization::busy_only(80.0),
+            EngineUtilization::busy_only(10.0),
+        ];
+
+        let header = intel_gpu_top_header(&stats);
+        let mut lines = header.lines();
+        let top = lines.next().unwrap();
+        assert!(top.contains("RCS/0") && top.contains("BCS/0") && top.contains("VECS/0"));
+        assert!(top.contains("VCS/0") && top.contains("VCS/1"));
+        assert!(!top.contains("CCS"));
+
+        let line = format_intel_gpu_top_style(&stats);
+        assert_eq!(line.len(), lines.next().unwrap().len());
+        assert!(line.starts_with("1200 1150  12  8.50     -"));
+        assert!(line.contains("  45.50   2   1"));
+        assert!(line.contains("  80.00   -   -"));
+    }
+}
@@ -16,18 +16,20 @@
 //! - RC6 power-saving state residency
 //! - Temperature and fan speed monitoring (via hwmon)
 //! - Power consumption monitoring (via RAPL)
-//! - Throttle detection (thermal, power limit, etc.)
+//! - Throttle detection (thermal, power limit, etc.) and residency history
 //! - Per-process GPU usage tracking (via DRM fdinfo)
 //! - Multi-driver support (i915 and xe)
 //! - Continuous sampling with callbacks
 //!
 //! # Quick Start
 //!
-//! ```rust,no_run
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
 //! use intel_gpu_stats::IntelGpu;
 //!
-//! // Detect and open the first Intel GPU
-//! let mut gpu = IntelGpu::detect()?;
+//! // Detect and open the first Intel GPU, priming the counters so the
+//! // first read covers a meaningful window
+//! let mut gpu = IntelGpu::detect_warm()?;
 //!
 //! // Read current statistics
 //! let stats = gpu.read_stats()?;
@@ -43,6 +45,13 @@
 //! # Ok::<(), intel_gpu_stats::Error>(())
 //! ```
 //!
+//! # `no_std` Support
+//!
+//! The statistics types in [`types`] only need `core` and `alloc`. Building
+//! with `default-features = false` drops the `std` feature and everything that
+//! reads from the GPU, leaving just those types for sharing with `no_std`
+//! consumers. The examples here need `std` and are skipped without it.
+//!
 //! # Permissions
 //!
 //! On Linux, reading GPU statistics requires one of:
@@ -52,7 +61,8 @@
 //!
 //! # Example with Continuous Sampling
 //!
-//! ```rust,no_run
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
 //! use intel_gpu_stats::IntelGpu;
 //! use std::time::Duration;
 //!
@@ -73,7 +83,8 @@
 //!
 //! # Per-Process GPU Tracking
 //!
-//! ```rust,no_run
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
 //! use intel_gpu_stats::IntelGpu;
 //!
 //! // List all processes using the GPU
@@ -85,28 +96,77 @@
 //! // Find Quick Sync users specifically
 //! let quicksync_users = IntelGpu::find_quicksync_clients();
 //! ```
+//!
+//! For a ready-made background monitor of encoder/decoder load, see
+//! [`QuickSyncMonitor`].
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
 
-pub mod error;
+extern crate alloc;
+
+pub mod format;
 pub mod types;
 
-#[cfg(target_os = "linux")]
+#[cfg(feature = "std")]
+pub mod binary;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "std")]
+pub mod logging;
+#[cfg(feature = "std")]
+pub mod provider;
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "windows")))]
+pub mod quicksync;
+#[cfg(feature = "std")]
+pub mod sampling;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod snapshot;
+#[cfg(feature = "std")]
+pub mod throttle_history;
+
+#[cfg(feature = "json")]
+pub mod record;
+
+#[cfg(all(feature = "ffi", any(target_os = "linux", target_os = "windows")))]
+pub mod ffi;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
 pub mod linux;
 
-#[cfg(target_os = "windows")]
+#[cfg(all(feature = "std", target_os = "windows"))]
 pub mod windows;
 
 // Re-export main types at crate root
-pub use error::{Error, Result};
 pub use types::*;
 
-#[cfg(target_os = "linux")]
-pub use linux::{IntelGpu, SamplingHandle};
+#[cfg(feature = "std")]
+pub use builder::IntelGpuBuilder;
+#[cfg(feature = "std")]
+pub use error::{Error, Result};
+#[cfg(feature = "std")]
+pub use provider::GpuStatsProvider;
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "windows")))]
+pub use quicksync::QuickSyncMonitor;
+#[cfg(feature = "std")]
+pub use sampling::{SamplingHandle, SamplingOptions, SamplingTimingStats};
+#[cfg(feature = "std")]
+pub use session::{Percentiles, SessionReport, StatsAccumulator};
+#[cfg(feature = "std")]
+pub use snapshot::{AbsoluteStats, CounterSnapshot, EngineSnapshot, Rc6Snapshot};
+#[cfg(feature = "std")]
+pub use throttle_history::ThrottleTracker;
+
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub use linux::IntelGpu;
 
-#[cfg(target_os = "windows")]
-pub use windows::{IntelGpu, SamplingHandle};
+#[cfg(all(feature = "std", target_os = "windows"))]
+pub use windows::IntelGpu;
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -148,4 +208,16 @@ mod tests {
         let status = platform_support_status();
         assert!(!status.is_empty());
     }
+
+    #[cfg(all(feature = "std", any(target_os = "linux", target_os = "windows")))]
+    #[test]
+    fn test_send_bounds() {
+        // Compile-time check: readers and handles can move across threads
+        fn assert_send<T: Send>() {}
+        assert_send::<IntelGpu>();
+        assert_send::<SamplingHandle>();
+        assert_send::<QuickSyncMonitor>();
+        assert_send::<GpuStats>();
+        assert_send::<Error>();
+    }
 }
@@ -0,0 +1,246 @@
+//! Memory bandwidth from the CPU's memory controller PMU
+//!
+//! Neither i915 nor xe expose bandwidth counters through their PMUs, and
+//! the OA (observation architecture) metrics need a driver-specific perf
+//! stream and per-platform metric sets. Integrated GPUs share system memory
+//! with the CPU though, so the uncore memory controller (IMC) counters see
+//! all their traffic:
+//!
+//! /sys/bus/event_source/devices/uncore_imc_free_running_0/events/data_read
+//! /sys/bus/event_source/devices/uncore_imc_free_running_0/events/data_write
+//! /sys/bus/event_source/devices/uncore_imc/events/data_reads (older CPUs)
+//!
+//! Each count is scaled by the event's `.scale` into its `.unit` (MiB). The
+//! counters include the CPU's traffic, so they are an upper bound for the
+//! GPU's. Discrete GPUs have no VRAM bandwidth counter and report none.
+//!
+//! Uncore events are counted per package on the CPUs listed in the PMU's
+//! `cpumask`, one counter per listed CPU.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use super::perf::{EventConfig, PerfEvent, PerfEventAttr};
+use super::pmu::{parse_hex_or_dec, read_pmu_format, PMU_BASE_PATH};
+
+/// IMC events counting data read from and written to memory
+const DATA_EVENTS: &[&str] = &["data_read", "data_write", "data_reads", "data_writes"];
+
+/// A memory controller counter and its conversion to bytes
+struct BandwidthCounter {
+    event: PerfEvent,
+    bytes_per_count: f64,
+    last_value: u64,
+}
+
+/// Memory bandwidth reader
+pub struct BandwidthReader {
+    counters: Vec<BandwidthCounter>,
+    /// Last read timestamp
+    last_timestamp: Instant,
+}
+
+impl BandwidthReader {
+    /// Create a bandwidth reader, without counters unless `enabled`
+    ///
+    /// Only enable it for integrated GPUs, see the module docs. Counters
+    /// that can't be opened (e.g. without CAP_PERFMON) are skipped.
+    pub fn new(enabled: bool) -> Self {
+        let mut counters = Vec::new();
+        if enabled {
+            for pmu in find_imc_pmus() {
+                counters.extend(open_counters(&pmu));
+            }
+        }
+        Self {
+            counters,
+            last_timestamp: Instant::now(),
+        }
+    }
+
+    /// Check if memory bandwidth is available
+    pub fn is_available(&self) -> bool {
+        !self.counters.is_empty()
+    }
+
+    /// Read the memory bandwidth in GB/s since the last read
+    pub fn read(&mut self) -> Option<f64> {
+        if self.counters.is_empty() {
+            return None;
+        }
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_timestamp).as_secs_f64();
+        if elapsed < 0.001 {
+            // Need at least 1ms between reads
+            return None;
+        }
+
+        let mut bytes = 0.0;
+        for counter in &mut self.counters {
+            let current = counter.event.read_value().ok()?;
+            let delta = current.saturating_sub(counter.last_value);
+            counter.last_value = current;
+            bytes += delta as f64 * counter.bytes_per_count;
+        }
+        self.last_timestamp = now;
+
+        Some(bytes / elapsed / 1e9)
+    }
+}
+
+/// Find the IMC PMUs, preferring the free-running counters
+fn find_imc_pmus() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(PMU_BASE_PATH) else {
+        return Vec::new();
+    };
+    let mut free_running = Vec::new();
+    let mut imc = Vec::new();
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("uncore_imc_free_running") {
+            free_running.push(entry.path());
+        } else if name.starts_with("uncore_imc") {
+            imc.push(entry.path());
+        }
+    }
+    if free_running.is_empty() {
+        imc
+    } else {
+        free_running
+    }
+}
+
+/// Open the data read and write events of an IMC PMU
+fn open_counters(pmu_path: &Path) -> Vec<BandwidthCounter> {
+    let Some(type_id) = fs::read_to_string(pmu_path.join("type"))
+        .ok()
+        .and_then(|type_id| type_id.trim().parse().ok())
+    else {
+        return Vec::new();
+    };
+    let format = read_pmu_format(pmu_path);
+    let events = pmu_path.join("events");
+    let cpus = fs::read_to_string(pmu_path.join("cpumask"))
+        .ok()
+        .and_then(|mask| parse_cpumask(&mask))
+        .unwrap_or_else(|| vec![0]);
+
+    DATA_EVENTS
+        .iter()
+        .flat_map(|name| cpus.iter().map(move |&cpu| (*name, cpu)))
+        .filter_map(|(name, cpu)| {
+            let spec = fs::read_to_string(events.join(name)).ok()?;
+            let config = event_config(&spec, &format)?;
+            let read =
+                |suffix: &str| fs::read_to_string(events.join(format!("{}.{}", name, suffix)));
+            let scale = read("scale")
+                .ok()
+                .and_then(|scale| scale.trim().parse().ok())
+                .unwrap_or(1.0);
+            let unit = read("unit").unwrap_or_default();
+            let bytes_per_count = scale * unit_bytes(&unit)?;
+            let attr = PerfEventAttr::new_i915(type_id, 0).with_config(EventConfig::from(config));
+            let mut event = PerfEvent::open(&attr, -1, cpu, -1, 0, name).ok()?;
+            let last_value = event.read_value().ok()?;
+            Some(BandwidthCounter {
+                event,
+                bytes_per_count,
+                last_value,
+            })
+        })
+        .collect()
+}
+
+/// Build an event config from terms like "event=0xff,umask=0x20"
+///
+/// Each term's value is placed at the low bit of its format field; a bare
+/// `config` term is used as is. `None` if a term is unknown or invalid.
+fn event_config(spec: &str, format: &HashMap<String, (u32, u32)>) -> Option<u64> {
+    let mut config = 0u64;
+    for term in spec.trim().split(',') {
+        let (field, value) = term.trim().split_once('=')?;
+        let value = parse_hex_or_dec(value)?;
+        let low = match field.trim() {
+            "config" => 0,
+            field => format.get(field)?.0,
+        };
+        config |= value.checked_shl(low)?;
+    }
+    Some(config)
+}
+
+/// Parse a PMU `cpumask` like "0" or "0,18" or "0-1"
+///
+/// `None` if it lists no CPU or is malformed.
+fn parse_cpumask(mask: &str) -> Option<Vec<i32>> {
+    let mut cpus = Vec::new();
+    for part in mask.trim().split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => cpus.extend(first.parse::<i32>().ok()?..=last.parse().ok()?),
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    (!cpus.is_empty()).then_some(cpus)
+}
+
+/// Bytes per unit of a perf event `.unit` string, `None` if not a size
+///
+/// A missing unit counts as bytes.
+fn unit_bytes(unit: &str) -> Option<f64> {
+    match unit.trim() {
+        "" | "B" | "bytes" => Some(1.0),
+        "KiB" => Some(1024.0),
+        "MiB" => Some(1024.0 * 1024.0),
+        "GiB" => Some(1024.0 * 1024.0 * 1024.0),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_config() {
+        let format: HashMap<String, (u32, u32)> = [
+            ("event".to_string(), (0, 7)),
+            ("umask".to_string(), (8, 15)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            event_config("event=0xff,umask=0x20\n", &format),
+            Some(0x20ff)
+        );
+        assert_eq!(event_config("config=0x1", &format), Some(1));
+        assert_eq!(event_config("event=0xff,cmask=0x1", &format), None);
+        assert_eq!(event_config("event=bogus", &format), None);
+    }
+
+    #[test]
+    fn test_parse_cpumask() {
+        assert_eq!(parse_cpumask("0\n"), Some(vec![0]));
+        assert_eq!(parse_cpumask("0,18"), Some(vec![0, 18]));
+        assert_eq!(parse_cpumask("2-4"), Some(vec![2, 3, 4]));
+        assert_eq!(parse_cpumask(""), None);
+        assert_eq!(parse_cpumask("x"), None);
+    }
+
+    #[test]
+    fn test_unit_bytes() {
+        // data_read on uncore_imc_free_running: 6.103515625e-5 MiB = 64 bytes
+        let bytes = 6.103515625e-5 * unit_bytes("MiB\n").unwrap();
+        assert_eq!(bytes, 64.0);
+        assert_eq!(unit_bytes(""), Some(1.0));
+        assert_eq!(unit_bytes("Joules"), None);
+    }
+
+    #[test]
+    fn test_discrete_has_no_bandwidth() {
+        let mut reader = BandwidthReader::new(false);
+        assert!(!reader.is_available());
+        assert_eq!(reader.read(), None);
+    }
+}
@@ -0,0 +1,90 @@
+//! Minimal DRM device node queries
+//!
+//! Used to identify GPUs from their `/dev/dri` nodes when sysfs is not
+//! available, e.g. in containers that only bind-mount the device nodes.
+
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+
+use super::perf::retry_on_eintr;
+
+/// Character device major number of DRM nodes
+pub const DRM_MAJOR: u32 = 226;
+
+/// First minor number used by render nodes (`/dev/dri/renderD128`)
+pub const RENDER_MINOR_BASE: u32 = 128;
+
+/// Maximum driver name length we read from DRM_IOCTL_VERSION
+const NAME_CAPACITY: usize = 64;
+
+/// `struct drm_version` from drm.h
+#[repr(C)]
+struct DrmVersion {
+    version_major: libc::c_int,
+    version_minor: libc::c_int,
+    version_patchlevel: libc::c_int,
+    name_len: libc::size_t,
+    name: *mut libc::c_char,
+    date_len: libc::size_t,
+    date: *mut libc::c_char,
+    desc_len: libc::size_t,
+    desc: *mut libc::c_char,
+}
+
+/// `_IOWR('d', 0x00, struct drm_version)`
+const DRM_IOCTL_VERSION: libc::c_ulong = (3 << 30)
+    | ((std::mem::size_of::<DrmVersion>() as libc::c_ulong) << 16)
+    | ((b'd' as libc::c_ulong) << 8);
+
+/// Query the kernel driver name (e.g. "i915" or "xe") of a DRM device node
+///
+/// Returns `None` if the node cannot be opened or is not a DRM device.
+pub fn driver_name(node: &Path) -> Option<String> {
+    let file = File::open(node).ok()?;
+    let mut name = [0u8; NAME_CAPACITY];
+    let mut version = DrmVersion {
+        version_major: 0,
+        version_minor: 0,
+        version_patchlevel: 0,
+        name_len: name.len(),
+        name: name.as_mut_ptr() as *mut libc::c_char,
+        date_len: 0,
+        date: std::ptr::null_mut(),
+        desc_len: 0,
+        desc: std::ptr::null_mut(),
+    };
+
+    let ret = retry_on_eintr(|| unsafe {
+        libc::ioctl(file.as_raw_fd(), DRM_IOCTL_VERSION, &mut version)
+    });
+    if ret < 0 {
+        return None;
+    }
+
+    // The kernel reports the full length but copies at most our capacity
+    let len = version.name_len.min(name.len());
+    String::from_utf8(name[..len].to_vec()).ok()
+}
+
+/// Parse the render node number from a `/dev/dri` entry name ("renderD128" -> 128)
+pub fn parse_render_node(name: &str) -> Option<u32> {
+    name.strip_prefix("renderD")?
+        .parse()
+        .ok()
+        .filter(|&minor| minor >= RENDER_MINOR_BASE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_render_node() {
+        assert_eq!(parse_render_node("renderD128"), Some(128));
+        assert_eq!(parse_render_node("renderD129"), Some(129));
+        assert_eq!(parse_render_node("renderD12"), None);
+        assert_eq!(parse_render_node("card0"), None);
+        assert_eq!(parse_render_node("renderD"), None);
+    }
+}
@@ -13,52 +13,140 @@
 //! drm-engine-video-enhance:       0 ns
 //! drm-memory-resident:    1234567
 //! ```
+//!
+//! xe reports GPU cycles per engine class instead, named by the class
+//! abbreviations (`rcs`, `bcs`, `vcs`, `vecs`, `ccs`):
+//! ```text
+//! drm-driver:     xe
+//! drm-client-id:  3
+//! drm-cycles-rcs: 28257900
+//! drm-total-cycles-rcs:   7655183225
+//! drm-cycles-vcs: 0
+//! drm-total-cycles-vcs:   7655183225
+//! drm-engine-capacity-vcs:        2
+//! ```
+//!
+//! `drm-cycles-<class>` counts the cycles the client's work ran and
+//! `drm-total-cycles-<class>` the GPU timestamp cycles elapsed, so
+//! utilization is the ratio of their deltas; they are stored in
+//! `DrmClient::engine_cycles`.
+//!
+//! The driver is read first and decides how the engine keys are
+//! interpreted. fdinfo carries no driver version, and the in-tree i915 and
+//! xe modules export none either; the keys a driver writes identify its
+//! format instead. Unrecognized engine keys are skipped and reported once
+//! each through [`logging`], so new formats can be spotted.
+//!
+//! Drivers that submit to several engines of a class report how many with
+//! `drm-engine-capacity-<engine>` lines (e.g. `drm-engine-capacity-video: 2`),
+//! stored in `DrmClient::engine_capacity`.
+//!
+//! Drivers that count resets or faults per client report them under keys
+//! ending in `-resets` or `-faults` (e.g. `drm-engine-render-resets`); all
+//! such counters are summed into `DrmClient::resets` and `DrmClient::faults`.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::paths;
+use crate::logging;
+use crate::types::{DrmClient, DrmEngineCapacity, DrmEngineCycles, EngineClass, GpuDriver};
 
-use crate::types::DrmClient;
+/// Unrecognized engine keys already reported, as "driver:key"
+static REPORTED_KEYS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
 
 /// Parse fdinfo for a specific file descriptor
 fn parse_fdinfo(pid: u32, fd: &str) -> Option<FdinfoData> {
     let fdinfo_path = format!("/proc/{}/fdinfo/{}", pid, fd);
     let file = File::open(&fdinfo_path).ok()?;
-    let reader = BufReader::new(file);
+    let lines: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+    parse_fdinfo_lines(&lines)
+}
 
-    let mut data = FdinfoData::default();
-    let mut is_i915_or_xe = false;
+/// Parse the lines of an fdinfo file, `None` unless the driver is i915 or xe
+///
+/// The driver line is found before anything else is read, so the engine
+/// keys are interpreted for the right driver wherever the line appears.
+fn parse_fdinfo_lines(lines: &[String]) -> Option<FdinfoData> {
+    let driver = match lines
+        .iter()
+        .find_map(|line| line.trim().strip_prefix("drm-driver:"))?
+        .trim()
+    {
+        "i915" => GpuDriver::I915,
+        "xe" => GpuDriver::Xe,
+        _ => return None,
+    };
 
-    for line in reader.lines().map_while(Result::ok) {
+    let mut data = FdinfoData::default();
+    for line in lines {
         let line = line.trim();
 
-        if line.starts_with("drm-driver:") {
-            let driver = line.split(':').nth(1)?.trim();
-            if driver == "i915" || driver == "xe" {
-                is_i915_or_xe = true;
-            }
-        } else if line.starts_with("drm-client-id:") {
+        if line.starts_with("drm-client-id:") {
             data.client_id = line.split(':').nth(1)?.trim().parse().ok();
-        } else if line.starts_with("drm-engine-render:") {
-            data.render_ns = parse_engine_ns(line);
-        } else if line.starts_with("drm-engine-copy:") {
-            data.copy_ns = parse_engine_ns(line);
-        } else if line.starts_with("drm-engine-video:") && !line.contains("video-enhance") {
-            data.video_ns = parse_engine_ns(line);
-        } else if line.starts_with("drm-engine-video-enhance:") {
-            data.video_enhance_ns = parse_engine_ns(line);
-        } else if line.starts_with("drm-engine-compute:") {
-            data.compute_ns = parse_engine_ns(line);
         } else if line.starts_with("drm-memory-resident:") {
             data.memory_bytes = parse_memory_bytes(line);
+        } else if let Some(resets) = parse_counter(line, "-resets") {
+            data.resets = data.resets.saturating_add(resets);
+        } else if let Some(faults) = parse_counter(line, "-faults") {
+            data.faults = data.faults.saturating_add(faults);
+        } else if let Some((key, value)) = line.split_once(':') {
+            if let Some(engine) = key.strip_prefix("drm-engine-capacity-") {
+                match (engine_class(driver, engine), value.trim().parse()) {
+                    (Some(class), Ok(capacity)) => data.engine_capacity.set(class, capacity),
+                    _ => report_unknown_key(driver, key),
+                }
+            } else if let Some(engine) = key.strip_prefix("drm-total-cycles-") {
+                match (engine_class(driver, engine), parse_value(value)) {
+                    (Some(class), Some(total)) => data.engine_cycles.get_mut(class).total = total,
+                    _ => report_unknown_key(driver, key),
+                }
+            } else if let Some(engine) = key.strip_prefix("drm-cycles-") {
+                match (engine_class(driver, engine), parse_value(value)) {
+                    (Some(class), Some(busy)) => data.engine_cycles.get_mut(class).busy = busy,
+                    _ => report_unknown_key(driver, key),
+                }
+            } else if let Some(engine) = key.strip_prefix("drm-engine-") {
+                match engine_class(driver, engine) {
+                    Some(class) => *data.engine_ns_mut(class) = parse_engine_ns(line),
+                    None => report_unknown_key(driver, key),
+                }
+            }
         }
     }
 
-    if is_i915_or_xe {
-        Some(data)
-    } else {
-        None
+    Some(data)
+}
+
+/// Engine class of an engine name in a driver's engine keys
+///
+/// i915 names the engine classes; xe uses their abbreviations.
+fn engine_class(driver: GpuDriver, engine: &str) -> Option<EngineClass> {
+    let class = match (driver, engine) {
+        (GpuDriver::I915, "render") | (GpuDriver::Xe, "rcs") => EngineClass::Render,
+        (GpuDriver::I915, "copy") | (GpuDriver::Xe, "bcs") => EngineClass::Copy,
+        (GpuDriver::I915, "video") | (GpuDriver::Xe, "vcs") => EngineClass::Video,
+        (GpuDriver::I915, "video-enhance") | (GpuDriver::Xe, "vecs") => EngineClass::VideoEnhance,
+        (GpuDriver::I915, "compute") | (GpuDriver::Xe, "ccs") => EngineClass::Compute,
+        _ => return None,
+    };
+    Some(class)
+}
+
+/// Log an unrecognized engine key, once per driver and key
+fn report_unknown_key(driver: GpuDriver, key: &str) {
+    let reported = REPORTED_KEYS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(format!("{}:{}", driver, key));
+    if reported {
+        logging::info(format_args!(
+            "Unrecognized {} fdinfo key {}, please report it",
+            driver, key
+        ));
     }
 }
 
@@ -71,6 +159,11 @@ fn parse_engine_ns(line: &str) -> u64 {
         .unwrap_or(0)
 }
 
+/// Parse the number at the start of a value like " 7655183225"
+fn parse_value(value: &str) -> Option<u64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
 /// Parse memory in bytes from a line like "drm-memory-resident: 1234567"
 fn parse_memory_bytes(line: &str) -> u64 {
     line.split(':')
@@ -79,6 +172,17 @@ fn parse_memory_bytes(line: &str) -> u64 {
         .unwrap_or(0)
 }
 
+/// Parse a counter from a line like "drm-engine-render-resets: 2"
+///
+/// `None` unless the key is a `drm-` key ending in `suffix`.
+fn parse_counter(line: &str, suffix: &str) -> Option<u64> {
+    let (key, value) = line.split_once(':')?;
+    if !key.starts_with("drm-") || !key.ends_with(suffix) {
+        return None;
+    }
+    value.split_whitespace().next()?.parse().ok()
+}
+
 /// Get the process name from /proc/<pid>/comm
 fn get_process_name(pid: u32) -> String {
     fs::read_to_string(format!("/proc/{}/comm", pid))
@@ -86,15 +190,49 @@ fn get_process_name(pid: u32) -> String {
         .unwrap_or_else(|_| format!("pid:{}", pid))
 }
 
-/// Check if fd points to a DRM render node
-fn is_drm_render_fd(pid: u32, fd: &str) -> bool {
+/// Check if a path is a DRM render or card node
+///
+/// Nodes are looked for in [`paths::dri_dir`], `/dev/dri` by default.
+fn is_drm_node(path: &Path) -> bool {
+    is_drm_node_in(path, &paths::dri_dir())
+}
+
+fn is_drm_node_in(path: &Path, dri_dir: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    path.parent() == Some(dri_dir) && (name.starts_with("renderD") || name.starts_with("card"))
+}
+
+/// Get the DRM node an fd points to, if it points to one
+fn drm_fd_target(pid: u32, fd: &str) -> Option<PathBuf> {
     let link_path = format!("/proc/{}/fd/{}", pid, fd);
-    if let Ok(target) = fs::read_link(&link_path) {
-        let target_str = target.to_string_lossy();
-        target_str.contains("/dev/dri/renderD") || target_str.contains("/dev/dri/card")
-    } else {
-        false
+    fs::read_link(link_path)
+        .ok()
+        .filter(|target| is_drm_node(target))
+}
+
+/// Resolve the card ID ("card1") of a DRM node
+///
+/// Card nodes name the card directly; render nodes are looked up through
+/// sysfs, where `/sys/class/drm/renderD128/device/drm` lists the card node
+/// of the same device.
+fn card_id_for_node(node: &Path) -> Option<String> {
+    let name = node.file_name()?.to_str()?;
+    if name.starts_with("card") {
+        return Some(name.to_string());
     }
+    let siblings = fs::read_dir(paths::card_sysfs_dir(name).join("device/drm")).ok()?;
+    siblings
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .find(|sibling| is_card_name(sibling))
+}
+
+/// Check if a name is a card node name ("card0", not "card0-DP-1")
+fn is_card_name(name: &str) -> bool {
+    name.strip_prefix("card")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
 }
 
 /// Internal fdinfo data
@@ -107,31 +245,76 @@ struct FdinfoData {
     video_enhance_ns: u64,
     compute_ns: u64,
     memory_bytes: u64,
+    resets: u64,
+    faults: u64,
+    engine_capacity: DrmEngineCapacity,
+    engine_cycles: DrmEngineCycles,
+}
+
+impl FdinfoData {
+    fn engine_ns_mut(&mut self, class: EngineClass) -> &mut u64 {
+        match class {
+            EngineClass::Render => &mut self.render_ns,
+            EngineClass::Copy => &mut self.copy_ns,
+            EngineClass::Video => &mut self.video_ns,
+            EngineClass::VideoEnhance => &mut self.video_enhance_ns,
+            EngineClass::Compute => &mut self.compute_ns,
+        }
+    }
 }
 
 /// List all DRM clients (processes using the GPU)
 ///
 /// This reads /proc to find all processes with open DRM render node
-/// file descriptors and parses their fdinfo to get GPU usage.
+/// file descriptors and parses their fdinfo to get GPU usage. A process
+/// using several GPUs is listed once per card, see `DrmClient::card_id`.
 pub fn list_drm_clients() -> Vec<DrmClient> {
-    let mut clients: HashMap<u32, DrmClient> = HashMap::new();
+    collect_drm_clients(proc_pids(), |_| true)
+}
 
-    let proc_path = Path::new("/proc");
-    let entries = match fs::read_dir(proc_path) {
+/// List DRM clients among the given processes only
+///
+/// Scanning every process in `/proc` gets expensive on hosts with many
+/// processes. When the interesting processes are already known (e.g. the
+/// encoder workers this application spawned), only their fds are
+/// inspected. PIDs that do not exist or cannot be read are skipped, and
+/// duplicates are ignored.
+pub fn list_drm_clients_for_pids(pids: &[u32]) -> Vec<DrmClient> {
+    let mut pids = pids.to_vec();
+    pids.sort_unstable();
+    pids.dedup();
+    collect_drm_clients(pids, |_| true)
+}
+
+/// List DRM clients with fds open on any of the given device nodes
+///
+/// `nodes` are paths such as `/dev/dri/renderD128` or `/dev/dri/card0`,
+/// typically a GPU's `render_node` and `card_node`. Only usage through fds
+/// pointing at these nodes is counted, so a process using two GPUs is
+/// reported separately for each.
+pub fn list_drm_clients_for_nodes(nodes: &[&str]) -> Vec<DrmClient> {
+    collect_drm_clients(proc_pids(), |target| {
+        nodes.iter().any(|node| target == Path::new(node))
+    })
+}
+
+/// PIDs of all processes in /proc
+fn proc_pids() -> Vec<u32> {
+    let entries = match fs::read_dir("/proc") {
         Ok(e) => e,
         Err(_) => return Vec::new(),
     };
 
-    for entry in entries.flatten() {
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
-
-        // Only process numeric directories (PIDs)
-        let pid: u32 = match name_str.parse() {
-            Ok(p) => p,
-            Err(_) => continue,
-        };
+    // Only numeric directories are processes
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_string_lossy().parse().ok())
+        .collect()
+}
 
+/// Call `f` with the PID, fd number and node of every DRM fd of `pids`
+fn for_each_drm_fd(pids: impl IntoIterator<Item = u32>, mut f: impl FnMut(u32, &str, PathBuf)) {
+    for pid in pids {
         // Read the fd directory for this process
         let fd_path = format!("/proc/{}/fd", pid);
         let fd_entries = match fs::read_dir(&fd_path) {
@@ -142,31 +325,69 @@ pub fn list_drm_clients() -> Vec<DrmClient> {
         for fd_entry in fd_entries.flatten() {
             let fd = fd_entry.file_name();
             let fd_str = fd.to_string_lossy();
-
-            // Check if this fd is a DRM render node
-            if !is_drm_render_fd(pid, &fd_str) {
-                continue;
+            if let Some(target) = drm_fd_target(pid, &fd_str) {
+                f(pid, &fd_str, target);
             }
+        }
+    }
+}
+
+/// Collect DRM clients of `pids` from fds whose node matches `filter`
+fn collect_drm_clients(
+    pids: impl IntoIterator<Item = u32>,
+    filter: impl Fn(&Path) -> bool,
+) -> Vec<DrmClient> {
+    let mut clients: HashMap<(u32, Option<String>), DrmClient> = HashMap::new();
+    let mut card_ids: HashMap<PathBuf, Option<String>> = HashMap::new();
+
+    for_each_drm_fd(pids, |pid, fd, target| {
+        // Check if this fd is a DRM node we are interested in
+        if !filter(&target) {
+            return;
+        }
 
-            // Parse the fdinfo
-            if let Some(data) = parse_fdinfo(pid, &fd_str) {
-                let client = clients.entry(pid).or_insert_with(|| {
-                    let name = get_process_name(pid);
-                    DrmClient::new(pid, name)
+        // Parse the fdinfo
+        if let Some(data) = parse_fdinfo(pid, fd) {
+            let card_id = card_ids
+                .entry(target)
+                .or_insert_with_key(|target| card_id_for_node(target))
+                .clone();
+            let client = clients
+                .entry((pid, card_id))
+                .or_insert_with_key(|(_, card_id)| {
+                    let mut client = DrmClient::new(pid, get_process_name(pid));
+                    client.card_id = card_id.clone();
+                    client
                 });
 
-                // Accumulate usage (a process may have multiple DRM fds)
-                client.render_ns = client.render_ns.saturating_add(data.render_ns);
-                client.copy_ns = client.copy_ns.saturating_add(data.copy_ns);
-                client.video_ns = client.video_ns.saturating_add(data.video_ns);
-                client.video_enhance_ns = client
-                    .video_enhance_ns
-                    .saturating_add(data.video_enhance_ns);
-                client.compute_ns = client.compute_ns.saturating_add(data.compute_ns);
-                client.memory_bytes = client.memory_bytes.max(data.memory_bytes);
+            // Accumulate usage (a process may have multiple DRM fds)
+            client.render_ns = client.render_ns.saturating_add(data.render_ns);
+            client.copy_ns = client.copy_ns.saturating_add(data.copy_ns);
+            client.video_ns = client.video_ns.saturating_add(data.video_ns);
+            client.video_enhance_ns = client
+                .video_enhance_ns
+                .saturating_add(data.video_enhance_ns);
+            client.compute_ns = client.compute_ns.saturating_add(data.compute_ns);
+            client.memory_bytes = client.memory_bytes.max(data.memory_bytes);
+            client.resets = client.resets.saturating_add(data.resets);
+            client.faults = client.faults.saturating_add(data.faults);
+            // The same for every fd on a card
+            client.engine_capacity = data.engine_capacity;
+            // Busy cycles add up across fds; elapsed cycles are a shared clock
+            for class in [
+                EngineClass::Render,
+                EngineClass::Copy,
+                EngineClass::Video,
+                EngineClass::VideoEnhance,
+                EngineClass::Compute,
+            ] {
+                let fd_cycles = data.engine_cycles.get(class);
+                let cycles = client.engine_cycles.get_mut(class);
+                cycles.busy = cycles.busy.saturating_add(fd_cycles.busy);
+                cycles.total = cycles.total.max(fd_cycles.total);
             }
         }
-    }
+    });
 
     // Convert to vec and sort by total usage (descending)
     let mut result: Vec<_> = clients.into_values().collect();
@@ -174,6 +395,64 @@ pub fn list_drm_clients() -> Vec<DrmClient> {
     result
 }
 
+/// Count the processes with an open i915 or xe DRM fd
+///
+/// Cheaper than `list_drm_clients().len()`: only the fdinfo lines up to
+/// the client ID are read, and no process names are looked up. Unlike
+/// `list_drm_clients`, a process using several GPUs counts once.
+pub fn drm_client_count() -> usize {
+    let mut pids: Vec<u32> = drm_contexts().into_iter().map(|(pid, _)| pid).collect();
+    pids.dedup();
+    pids.len()
+}
+
+/// Count the open DRM contexts (`drm-client-id`s) of i915 and xe devices
+///
+/// Every open of a DRM node creates a context with its own client ID;
+/// fds shared through `dup` or `fork` count once. A count that keeps
+/// growing in a long-running service points at a context leak.
+pub fn drm_context_count() -> usize {
+    let mut contexts: Vec<(PathBuf, u64)> = drm_contexts()
+        .into_iter()
+        .map(|(_, context)| context)
+        .collect();
+    contexts.sort();
+    contexts.dedup();
+    contexts.len()
+}
+
+/// Node and client ID of every i915/xe DRM fd, grouped by PID
+fn drm_contexts() -> Vec<(u32, (PathBuf, u64))> {
+    let mut contexts = Vec::new();
+    for_each_drm_fd(proc_pids(), |pid, fd, target| {
+        if let Some(client_id) = read_client_id(pid, fd) {
+            contexts.push((pid, (target, client_id)));
+        }
+    });
+    contexts
+}
+
+/// Read the client ID of an i915 or xe DRM fd
+///
+/// `drm-driver` is the first key of the fdinfo, and reading stops at
+/// `drm-client-id`, so the engine and memory lines are never parsed.
+fn read_client_id(pid: u32, fd: &str) -> Option<u64> {
+    let file = File::open(format!("/proc/{}/fdinfo/{}", pid, fd)).ok()?;
+    client_id_from_lines(BufReader::new(file).lines().map_while(Result::ok))
+}
+
+fn client_id_from_lines(lines: impl Iterator<Item = String>) -> Option<u64> {
+    let mut is_i915_or_xe = false;
+    for line in lines {
+        if let Some(driver) = line.strip_prefix("drm-driver:") {
+            is_i915_or_xe = matches!(driver.trim(), "i915" | "xe");
+        } else if let Some(client_id) = line.strip_prefix("drm-client-id:") {
+            return client_id.trim().parse().ok().filter(|_| is_i915_or_xe);
+        }
+    }
+    None
+}
+
 /// Find DRM clients using Quick Sync (video encode/decode)
 pub fn find_quicksync_clients() -> Vec<DrmClient> {
     list_drm_clients()
@@ -205,6 +484,142 @@ mod tests {
         assert_eq!(parse_memory_bytes("drm-memory-resident:    0"), 0);
     }
 
+    #[test]
+    fn test_client_id_from_lines() {
+        let lines = |text: &'static str| text.lines().map(String::from);
+        assert_eq!(
+            client_id_from_lines(lines(
+                "pos:\t0\ndrm-driver:\txe\ndrm-client-id:\t42\ndrm-engine-render:\t1 ns"
+            )),
+            Some(42)
+        );
+        assert_eq!(
+            client_id_from_lines(lines("drm-driver:\tamdgpu\ndrm-client-id:\t7")),
+            None
+        );
+        assert_eq!(client_id_from_lines(lines("drm-driver:\ti915")), None);
+    }
+
+    #[test]
+    fn test_list_drm_clients_for_pids() {
+        // The test process holds no DRM fds and PID 0 never exists in /proc
+        let pid = std::process::id();
+        assert!(list_drm_clients_for_pids(&[pid, pid, 0]).is_empty());
+        assert!(list_drm_clients_for_pids(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_is_drm_node() {
+        assert!(is_drm_node(Path::new("/dev/dri/renderD128")));
+        assert!(is_drm_node(Path::new("/dev/dri/card1")));
+        assert!(!is_drm_node(Path::new("/dev/null")));
+        assert!(!is_drm_node(Path::new("socket:[12345]")));
+
+        // Nodes bind-mounted elsewhere in a container
+        let gpu = Path::new("/gpu");
+        assert!(is_drm_node_in(Path::new("/gpu/renderD128"), gpu));
+        assert!(!is_drm_node_in(Path::new("/dev/dri/renderD128"), gpu));
+    }
+
+    #[test]
+    fn test_card_id_for_node() {
+        assert_eq!(
+            card_id_for_node(Path::new("/dev/dri/card1")).as_deref(),
+            Some("card1")
+        );
+        assert!(is_card_name("card0"));
+        assert!(!is_card_name("card0-DP-1"));
+        assert!(!is_card_name("card"));
+        assert!(!is_card_name("renderD128"));
+    }
+
+    #[test]
+    fn test_parse_counter() {
+        assert_eq!(
+            parse_counter("drm-engine-render-resets:\t2", "-resets"),
+            Some(2)
+        );
+        assert_eq!(parse_counter("drm-page-faults: 17", "-faults"), Some(17));
+        assert_eq!(
+            parse_counter("drm-engine-render: 12345 ns", "-resets"),
+            None
+        );
+        assert_eq!(parse_counter("other-resets: 1", "-resets"), None);
+        assert_eq!(
+            parse_counter("drm-engine-video-resets: n/a", "-resets"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_fdinfo_lines() {
+        let lines = |text: &str| -> Vec<String> { text.lines().map(String::from).collect() };
+
+        // The driver line may come after the engine keys
+        let data = parse_fdinfo_lines(&lines(
+            "drm-client-id:\t7\n\
+             drm-engine-video:\t999 ns\n\
+             drm-engine-video-enhance:\t5 ns\n\
+             drm-engine-capacity-video:\t2\n\
+             drm-engine-video-resets:\t1\n\
+             drm-driver:\ti915\n",
+        ))
+        .unwrap();
+        assert_eq!(data.client_id, Some(7));
+        assert_eq!(data.video_ns, 999);
+        assert_eq!(data.video_enhance_ns, 5);
+        assert_eq!(data.engine_capacity.get(EngineClass::Video), 2);
+        assert_eq!(data.resets, 1);
+
+        // fdinfo of an xe client (Lunar Lake, kernel 6.12)
+        let data = parse_fdinfo_lines(&lines(
+            "pos:\t0\n\
+             flags:\t02100002\n\
+             mnt_id:\t26\n\
+             ino:\t1093\n\
+             drm-driver:\txe\n\
+             drm-client-id:\t3\n\
+             drm-pdev:\t0000:00:02.0\n\
+             drm-total-system:\t0\n\
+             drm-shared-system:\t0\n\
+             drm-active-system:\t0\n\
+             drm-resident-system:\t0\n\
+             drm-purgeable-system:\t0\n\
+             drm-total-gtt:\t192 KiB\n\
+             drm-shared-gtt:\t0\n\
+             drm-active-gtt:\t0\n\
+             drm-resident-gtt:\t192 KiB\n\
+             drm-cycles-rcs:\t28257900\n\
+             drm-total-cycles-rcs:\t7655183225\n\
+             drm-cycles-bcs:\t0\n\
+             drm-total-cycles-bcs:\t7655183225\n\
+             drm-cycles-vcs:\t1200\n\
+             drm-total-cycles-vcs:\t7655183225\n\
+             drm-engine-capacity-vcs:\t2\n\
+             drm-cycles-vecs:\t0\n\
+             drm-total-cycles-vecs:\t7655183225\n\
+             drm-engine-capacity-vecs:\t2\n\
+             drm-cycles-ccs:\t0\n\
+             drm-total-cycles-ccs:\t7655183225\n\
+             drm-engine-capacity-ccs:\t4\n\
+             drm-cycles-gsc:\t0\n",
+        ))
+        .unwrap();
+        assert_eq!(data.client_id, Some(3));
+        let render = data.engine_cycles.get(EngineClass::Render);
+        assert_eq!((render.busy, render.total), (28_257_900, 7_655_183_225));
+        assert_eq!(data.engine_cycles.get(EngineClass::Video).busy, 1200);
+        assert_eq!(data.engine_capacity.get(EngineClass::VideoEnhance), 2);
+        assert_eq!(data.engine_capacity.get(EngineClass::Compute), 4);
+        assert_eq!(data.render_ns, 0);
+
+        // Each driver has its own engine names
+        assert_eq!(engine_class(GpuDriver::I915, "vcs"), None);
+        assert_eq!(engine_class(GpuDriver::Xe, "video"), None);
+        assert!(parse_fdinfo_lines(&lines("drm-driver:\tamdgpu\n")).is_none());
+        assert!(parse_fdinfo_lines(&lines("drm-engine-render:\t1 ns\n")).is_none());
+    }
+
     #[test]
     fn test_drm_client() {
         let mut client = DrmClient::new(1234, "test".to_string());
@@ -214,5 +629,11 @@ mod tests {
 
         client.render_ns = 500;
         assert_eq!(client.total_usage_ns(), 1500);
+
+        let mut later = client.clone();
+        later.resets = 3;
+        client.resets = 1;
+        assert_eq!(later.since(&client).resets, 2);
+        assert_eq!(client.since(&later).resets, 0);
     }
 }
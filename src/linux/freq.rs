@@ -0,0 +1,334 @@
+//! GPU frequency reading from sysfs
+//!
+//! The PMU frequency counters are the primary source, but on some systems
+//! their values are implausible. This module reads the frequencies the driver
+//! exposes in sysfs, which are used to sanity check the PMU and as a
+//! fallback. The layout depends on the driver:
+//!
+//! i915:
+//! /sys/class/drm/card0/gt/gt0/rps_{act,cur,boost,RP0,RPn}_freq_mhz
+//! /sys/class/drm/card0/gt_{act,cur,boost,RP0,RPn}_freq_mhz (older kernels)
+//!
+//! xe:
+//! /sys/class/drm/card0/device/tile0/gt0/freq0/{act,cur,rp0,rpn}_freq
+//!
+//! On xe platforms with a standalone media GT the media engines clock
+//! independently, and the media GT has its own `freq0` directory, e.g.
+//! /sys/class/drm/card0/device/tile0/gt1/freq0/act_freq
+//!
+//! i915 instead reports the fixed ratio of the media clock to the GT clock
+//! in `gt/gt0/media_freq_factor`, a u8.8 fixed point value scaled by
+//! `media_freq_factor.scale`. 0 means the firmware picks the ratio
+//! dynamically.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths;
+use super::sysfs::SysfsFile;
+use crate::types::GpuDriver;
+
+/// Upper bound used when the hardware maximum frequency is unknown
+pub const MAX_PLAUSIBLE_MHZ: u32 = 5000;
+
+/// Headroom allowed above the hardware maximum before a reading is rejected
+///
+/// PMU frequencies are averaged over the sample window, so rounding and
+/// timing jitter can push them slightly above RP0.
+const MAX_OVERSHOOT: f64 = 1.1;
+
+/// Candidate sysfs files (relative to the card directory) for each value
+struct FrequencyFiles {
+    actual: &'static [&'static str],
+    requested: &'static [&'static str],
+    min: &'static [&'static str],
+    max: &'static [&'static str],
+    boost: &'static [&'static str],
+    media_factor: &'static [&'static str],
+}
+
+/// i915 layout: per-GT files first, then the legacy card-level files
+const I915_FILES: FrequencyFiles = FrequencyFiles {
+    actual: &["gt/gt0/rps_act_freq_mhz", "gt_act_freq_mhz"],
+    requested: &["gt/gt0/rps_cur_freq_mhz", "gt_cur_freq_mhz"],
+    min: &["gt/gt0/rps_RPn_freq_mhz", "gt_RPn_freq_mhz"],
+    max: &["gt/gt0/rps_RP0_freq_mhz", "gt_RP0_freq_mhz"],
+    boost: &["gt/gt0/rps_boost_freq_mhz", "gt_boost_freq_mhz"],
+    media_factor: &["gt/gt0/media_freq_factor"],
+};
+
+/// xe layout: per-tile, per-GT frequency domain directories
+const XE_FILES: FrequencyFiles = FrequencyFiles {
+    actual: &["device/tile0/gt0/freq0/act_freq"],
+    requested: &["device/tile0/gt0/freq0/cur_freq"],
+    min: &["device/tile0/gt0/freq0/rpn_freq"],
+    max: &["device/tile0/gt0/freq0/rp0_freq"],
+    boost: &[],
+    media_factor: &[],
+};
+
+/// Passed-through GPUs expose no frequency files on the host
+const NO_FILES: FrequencyFiles = FrequencyFiles {
+    actual: &[],
+    requested: &[],
+    min: &[],
+    max: &[],
+    boost: &[],
+    media_factor: &[],
+};
+
+impl FrequencyFiles {
+    fn for_driver(driver: GpuDriver) -> &'static Self {
+        match driver {
+            GpuDriver::I915 => &I915_FILES,
+            GpuDriver::Xe => &XE_FILES,
+            GpuDriver::Vfio | GpuDriver::WindowsDch => &NO_FILES,
+        }
+    }
+}
+
+/// Find the first existing file among candidate paths
+fn find_file(card_path: &Path, candidates: &[&str]) -> Option<PathBuf> {
+    candidates
+        .iter()
+        .map(|candidate| card_path.join(candidate))
+        .find(|path| path.exists())
+}
+
+/// Find the actual frequency file of an xe GT on any tile
+fn find_xe_gt_actual(card_path: &Path, gt: u16) -> Option<PathBuf> {
+    fs::read_dir(card_path.join("device"))
+        .ok()?
+        .flatten()
+        .filter(|tile| tile.file_name().to_string_lossy().starts_with("tile"))
+        .map(|tile| tile.path().join(format!("gt{}/freq0/act_freq", gt)))
+        .find(|path| path.exists())
+}
+
+/// Read a frequency file in MHz
+fn read_mhz(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Scale of `media_freq_factor` when no `.scale` file is present (u8.8)
+const DEFAULT_MEDIA_FACTOR_SCALE: f64 = 1.0 / 256.0;
+
+/// Convert a raw `media_freq_factor` value into a clock ratio
+///
+/// Returns `None` for 0, which means the ratio is chosen dynamically.
+fn media_factor(raw: u32, scale: f64) -> Option<f64> {
+    (raw != 0).then_some(raw as f64 * scale)
+}
+
+/// Check whether a frequency reading is plausible
+///
+/// Zero is accepted since an idle GPU legitimately reports 0 MHz. Otherwise
+/// the value must not exceed the hardware maximum (with a small margin), or
+/// [`MAX_PLAUSIBLE_MHZ`] when the maximum is unknown.
+pub fn is_plausible(mhz: u32, max_mhz: Option<u32>) -> bool {
+    match max_mhz {
+        Some(max) => mhz as f64 <= max as f64 * MAX_OVERSHOOT,
+        None => mhz <= MAX_PLAUSIBLE_MHZ,
+    }
+}
+
+/// Sysfs frequency reader for a card
+///
+/// The files read per sample are kept open (see [`sysfs`](super::sysfs)).
+#[derive(Debug)]
+pub struct SysfsFrequency {
+    actual: Option<SysfsFile>,
+    requested: Option<SysfsFile>,
+    boost: Option<SysfsFile>,
+    media_actual: Option<SysfsFile>,
+    media_factor: Option<SysfsFile>,
+    media_factor_scale: f64,
+    min_mhz: Option<u32>,
+    max_mhz: Option<u32>,
+}
+
+impl SysfsFrequency {
+    /// Create a new sysfs frequency reader for a card using the given driver's layout
+    ///
+    /// `media_gt` is the GT hosting the media engines when it is separate
+    /// from the render GT.
+    pub fn new(card_id: &str, driver: GpuDriver, media_gt: Option<u16>) -> Self {
+        Self::from_card_path(&paths::card_sysfs_dir(card_id), driver, media_gt)
+    }
+
+    /// Create a reader from a card's sysfs directory
+    fn from_card_path(card_path: &Path, driver: GpuDriver, media_gt: Option<u16>) -> Self {
+        let files = FrequencyFiles::for_driver(driver);
+        let open = |path: Option<PathBuf>| SysfsFile::open(&path?);
+        let actual = open(find_file(card_path, files.actual));
+        let requested = open(find_file(card_path, files.requested));
+        let boost = open(find_file(card_path, files.boost));
+        let media_actual = open(
+            media_gt
+                .filter(|_| driver == GpuDriver::Xe)
+                .and_then(|gt| find_xe_gt_actual(card_path, gt)),
+        );
+        let media_factor_path = find_file(card_path, files.media_factor);
+        let media_factor_scale = media_factor_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path.with_extension("scale")).ok())
+            .and_then(|scale| scale.trim().parse().ok())
+            .unwrap_or(DEFAULT_MEDIA_FACTOR_SCALE);
+        let min_mhz = find_file(card_path, files.min).and_then(|path| read_mhz(&path));
+        let max_mhz = find_file(card_path, files.max).and_then(|path| read_mhz(&path));
+
+        Self {
+            actual,
+            requested,
+            boost,
+            media_actual,
+            media_factor: open(media_factor_path),
+            media_factor_scale,
+            min_mhz,
+            max_mhz,
+        }
+    }
+
+    /// Check if any sysfs frequency file is available
+    pub fn is_available(&self) -> bool {
+        self.actual.is_some() || self.requested.is_some()
+    }
+
+    /// Read the actual frequency in MHz
+    pub fn read_actual(&self) -> Option<u32> {
+        self.actual.as_ref()?.read_value()
+    }
+
+    /// Read the requested frequency in MHz
+    pub fn read_requested(&self) -> Option<u32> {
+        self.requested.as_ref()?.read_value()
+    }
+
+    /// Read the boost frequency in MHz
+    ///
+    /// The frequency i915 requests when boosting a client waiting on the
+    /// GPU. It can be changed at runtime, so it is read on every call.
+    pub fn read_boost(&self) -> Option<u32> {
+        self.boost.as_ref()?.read_value()
+    }
+
+    /// Read the actual frequency of the separate media GT in MHz
+    pub fn read_media_actual(&self) -> Option<u32> {
+        self.media_actual.as_ref()?.read_value()
+    }
+
+    /// Read the ratio of the media clock to the GT clock
+    ///
+    /// `None` if the file is missing or the ratio is dynamic.
+    pub fn read_media_freq_factor(&self) -> Option<f64> {
+        media_factor(
+            self.media_factor.as_ref()?.read_value()?,
+            self.media_factor_scale,
+        )
+    }
+
+    /// Hardware minimum frequency (RPn) in MHz, if reported
+    pub fn min_mhz(&self) -> Option<u32> {
+        self.min_mhz
+    }
+
+    /// Hardware maximum frequency (RP0) in MHz, if reported
+    pub fn max_mhz(&self) -> Option<u32> {
+        self.max_mhz
+    }
+
+    /// Check a frequency reading against this card's hardware range
+    pub fn is_plausible(&self, mhz: u32) -> bool {
+        is_plausible(mhz, self.max_mhz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_plausible() {
+        assert!(is_plausible(0, Some(1300)));
+        assert!(is_plausible(1400, Some(1300)));
+        assert!(!is_plausible(2000, Some(1300)));
+        assert!(is_plausible(2400, None));
+        assert!(!is_plausible(1_000_000, None));
+    }
+
+    #[test]
+    fn test_media_factor() {
+        assert_eq!(media_factor(128, 0.00390625), Some(0.5));
+        assert_eq!(media_factor(256, DEFAULT_MEDIA_FACTOR_SCALE), Some(1.0));
+        assert_eq!(media_factor(0, 0.00390625), None);
+    }
+
+    /// Create an empty fake card directory unique to this test
+    fn fake_card(name: &str) -> PathBuf {
+        let card = std::env::temp_dir().join(format!("igs-freq-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&card);
+        fs::create_dir_all(&card).unwrap();
+        card
+    }
+
+    #[test]
+    fn test_i915_layout() {
+        let card = fake_card("i915");
+        let gt = card.join("gt/gt0");
+        fs::create_dir_all(&gt).unwrap();
+        fs::write(gt.join("rps_act_freq_mhz"), "900\n").unwrap();
+        fs::write(gt.join("rps_cur_freq_mhz"), "1100\n").unwrap();
+        fs::write(gt.join("rps_RP0_freq_mhz"), "1300\n").unwrap();
+        fs::write(card.join("gt_boost_freq_mhz"), "1250\n").unwrap();
+        // Legacy files are ignored when the per-GT ones exist
+        fs::write(card.join("gt_cur_freq_mhz"), "1\n").unwrap();
+        fs::write(gt.join("media_freq_factor"), "128\n").unwrap();
+        fs::write(gt.join("media_freq_factor.scale"), "0.00390625\n").unwrap();
+
+        let freq = SysfsFrequency::from_card_path(&card, GpuDriver::I915, None);
+        assert!(freq.is_available());
+        assert_eq!(freq.read_actual(), Some(900));
+        assert_eq!(freq.read_requested(), Some(1100));
+        assert_eq!(freq.max_mhz(), Some(1300));
+        assert_eq!(freq.min_mhz(), None);
+        assert_eq!(freq.read_boost(), Some(1250));
+        assert_eq!(freq.read_media_freq_factor(), Some(0.5));
+        assert!(!freq.is_plausible(10_000));
+
+        // The xe layout is not present
+        assert!(!SysfsFrequency::from_card_path(&card, GpuDriver::Xe, None).is_available());
+
+        fs::remove_dir_all(&card).unwrap();
+    }
+
+    #[test]
+    fn test_xe_layout() {
+        let card = fake_card("xe");
+        let freq0 = card.join("device/tile0/gt0/freq0");
+        fs::create_dir_all(&freq0).unwrap();
+        fs::write(freq0.join("act_freq"), "2050\n").unwrap();
+        fs::write(freq0.join("cur_freq"), "2400\n").unwrap();
+        fs::write(freq0.join("rpn_freq"), "300\n").unwrap();
+        fs::write(freq0.join("rp0_freq"), "2400\n").unwrap();
+        let media = card.join("device/tile0/gt1/freq0");
+        fs::create_dir_all(&media).unwrap();
+        fs::write(media.join("act_freq"), "1100\n").unwrap();
+
+        let freq = SysfsFrequency::from_card_path(&card, GpuDriver::Xe, None);
+        assert_eq!(freq.read_media_actual(), None);
+
+        let freq = SysfsFrequency::from_card_path(&card, GpuDriver::Xe, Some(1));
+        assert_eq!(freq.read_actual(), Some(2050));
+        assert_eq!(freq.read_media_actual(), Some(1100));
+        assert_eq!(freq.read_media_freq_factor(), None);
+        assert_eq!(freq.read_boost(), None);
+        assert_eq!(freq.read_requested(), Some(2400));
+        assert_eq!(freq.min_mhz(), Some(300));
+        assert_eq!(freq.max_mhz(), Some(2400));
+
+        // The i915 layout is not present
+        assert!(!SysfsFrequency::from_card_path(&card, GpuDriver::I915, None).is_available());
+
+        fs::remove_dir_all(&card).unwrap();
+    }
+}
@@ -5,13 +5,28 @@
 //!
 //! Common hwmon files:
 //! - temp1_input: Temperature in millidegrees Celsius
+//! - tempN_input, tempN_label: Further sensors and their names (label optional)
+//! - temp1_crit: Critical temperature threshold in millidegrees Celsius
+//! - temp1_max: Maximum temperature, used as the threshold if temp1_crit is missing
 //! - fan1_input: Fan speed in RPM
 //! - pwm1: Fan PWM duty cycle (0-255)
+//! - pwm1_enable: Fan control mode (0=off, 1=manual, 2=auto)
+//!
+//! - pwm1_auto_pointN_{temp,pwm}: Points of the automatic fan curve (also
+//!   named temp1_auto_pointN_{temp,pwm} by some drivers)
+//!
+//! `pwm1` and `pwm1_enable` are writable by root, which allows basic manual
+//! fan control.
+//!
+//! [`HwmonReader`] finds the sensors once and keeps their files open between
+//! reads (see [`sysfs`](super::sysfs)).
 
 use std::fs;
 use std::path::{Path, PathBuf};
 
-use crate::types::TemperatureStats;
+use super::sysfs::SysfsFile;
+use crate::error::{Error, Result};
+use crate::types::{FanMode, TemperatureSensor, TemperatureStats};
 
 /// Find the hwmon path for an Intel GPU
 ///
@@ -67,6 +82,32 @@ fn read_fan_rpm(hwmon_path: &Path) -> Option<u32> {
     None
 }
 
+/// Write a value to a hwmon control file
+fn write_control(hwmon_path: &Path, file: &str, value: u32) -> Result<()> {
+    let path = hwmon_path.join(file);
+    if !path.exists() {
+        return Err(Error::FeatureNotAvailable {
+            feature: "Fan control".to_string(),
+        });
+    }
+    fs::write(&path, value.to_string()).map_err(|e| Error::sysfs_write(&path, e))
+}
+
+/// Read the hardware critical temperature threshold from hwmon
+///
+/// Returns the `temp1_crit` value in Celsius, falling back to `temp1_max`
+/// on drivers that only expose the latter, or None if neither is exposed.
+pub fn read_critical_temperature(hwmon_path: &Path) -> Option<f64> {
+    ["temp1_crit", "temp1_max"].iter().find_map(|name| {
+        let millicelsius = fs::read_to_string(hwmon_path.join(name))
+            .ok()?
+            .trim()
+            .parse::<i64>()
+            .ok()?;
+        Some(millicelsius as f64 / 1000.0)
+    })
+}
+
 /// Read GPU temperature from hwmon
 ///
 /// Returns the temperature in Celsius, or None if not available.
@@ -90,26 +131,144 @@ pub fn read_temperature(hwmon_path: &Path) -> Option<TemperatureStats> {
     None
 }
 
+/// Read every `tempN_input` sensor of a hwmon device, ordered by index
+///
+/// Sensors without a `tempN_label` file are named "tempN" rather than
+/// skipped. Unreadable inputs are left out.
+pub fn read_temperature_sensors(hwmon_path: &Path) -> Vec<TemperatureSensor> {
+    find_temperature_sensors(hwmon_path)
+        .into_iter()
+        .filter_map(|(index, label, input)| {
+            let millicelsius: i64 = fs::read_to_string(input).ok()?.trim().parse().ok()?;
+            Some(TemperatureSensor {
+                index,
+                label,
+                celsius: millicelsius as f64 / 1000.0,
+            })
+        })
+        .collect()
+}
+
+/// Find the `tempN_input` sensors of a hwmon device as (index, label, input)
+///
+/// Ordered by index and labeled as in [`read_temperature_sensors`].
+fn find_temperature_sensors(hwmon_path: &Path) -> Vec<(u32, String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(hwmon_path) else {
+        return Vec::new();
+    };
+
+    let mut sensors: Vec<(u32, String, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let index: u32 = name
+                .to_str()?
+                .strip_prefix("temp")?
+                .strip_suffix("_input")?
+                .parse()
+                .ok()?;
+            let label = fs::read_to_string(hwmon_path.join(format!("temp{}_label", index)))
+                .ok()
+                .map(|label| label.trim().to_string())
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| format!("temp{}", index));
+            Some((index, label, entry.path()))
+        })
+        .collect();
+    sensors.sort_by_key(|(index, _, _)| *index);
+    sensors
+}
+
+/// Read the automatic fan curve as (Celsius, PWM) points, ordered by index
+///
+/// Pairs the `_temp` and `_pwm` files of each auto point, looking under
+/// both the `pwm1_` and `temp1_` prefixes. Points missing either value are
+/// left out; an empty curve means the hardware exposes none.
+pub fn read_fan_curve(hwmon_path: &Path) -> Vec<(f64, u8)> {
+    let Ok(entries) = fs::read_dir(hwmon_path) else {
+        return Vec::new();
+    };
+
+    let mut indices: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let name = name.to_str()?;
+            let point = ["pwm1_auto_point", "temp1_auto_point"]
+                .iter()
+                .find_map(|prefix| name.strip_prefix(prefix))?;
+            point.split('_').next()?.parse().ok()
+        })
+        .collect();
+    indices.sort_unstable();
+    indices.dedup();
+
+    let read = |index: u32, value: &str| -> Option<i64> {
+        ["pwm1", "temp1"].iter().find_map(|prefix| {
+            let file = format!("{}_auto_point{}_{}", prefix, index, value);
+            fs::read_to_string(hwmon_path.join(file))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()
+        })
+    };
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let millicelsius = read(index, "temp")?;
+            let pwm = u8::try_from(read(index, "pwm")?).ok()?;
+            Some((millicelsius as f64 / 1000.0, pwm))
+        })
+        .collect()
+}
+
 /// GPU hwmon reader
 #[derive(Debug)]
 pub struct HwmonReader {
     /// Path to the hwmon directory
     hwmon_path: Option<PathBuf>,
-    /// Whether fan speed is available
-    has_fan: bool,
+    /// `temp1_input`
+    temperature: Option<SysfsFile>,
+    /// `fan1_input`
+    fan: Option<SysfsFile>,
+    /// `pwm1_enable`
+    fan_mode: Option<SysfsFile>,
+    /// Every `tempN_input` with its index and label
+    sensors: Vec<(u32, String, SysfsFile)>,
+    /// Hardware critical temperature, read once at open
+    critical_celsius: Option<f64>,
 }
 
 impl HwmonReader {
     /// Create a new hwmon reader for a GPU
     pub fn new(pci_path: &str) -> Self {
-        let hwmon_path = find_gpu_hwmon(pci_path);
-        let has_fan = hwmon_path
+        Self::from_path(find_gpu_hwmon(pci_path))
+    }
+
+    /// Create a reader for a hwmon directory, opening its files
+    fn from_path(hwmon_path: Option<PathBuf>) -> Self {
+        let open = |file: &str| SysfsFile::open(&hwmon_path.as_ref()?.join(file));
+        let temperature = open("temp1_input");
+        let fan = open("fan1_input");
+        let fan_mode = open("pwm1_enable");
+        let sensors = hwmon_path
+            .as_deref()
+            .map(find_temperature_sensors)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(index, label, input)| Some((index, label, SysfsFile::open(&input)?)))
+            .collect();
+        let critical_celsius = hwmon_path
             .as_ref()
-            .map(|p| p.join("fan1_input").exists())
-            .unwrap_or(false);
+            .and_then(|p| read_critical_temperature(p));
         Self {
             hwmon_path,
-            has_fan,
+            temperature,
+            fan,
+            fan_mode,
+            sensors,
+            critical_celsius,
         }
     }
 
@@ -120,12 +279,87 @@ impl HwmonReader {
 
     /// Check if fan speed monitoring is available
     pub fn has_fan(&self) -> bool {
-        self.has_fan
+        self.fan.is_some()
+    }
+
+    /// Get the hardware critical temperature threshold, if reported
+    pub fn critical_celsius(&self) -> Option<f64> {
+        self.critical_celsius
     }
 
     /// Read the current temperature (and fan speed if available)
     pub fn read(&self) -> Option<TemperatureStats> {
-        self.hwmon_path.as_ref().and_then(|p| read_temperature(p))
+        let millicelsius = self.temperature.as_ref()?.read_value::<i64>()?;
+        let celsius = millicelsius as f64 / 1000.0;
+        let mut stats = match self.fan.as_ref().and_then(SysfsFile::read_value::<u32>) {
+            Some(fan_rpm) => TemperatureStats::with_fan(celsius, fan_rpm),
+            None => TemperatureStats::new(celsius),
+        };
+        stats.critical_celsius = self.critical_celsius;
+        stats.fan_control_mode = self
+            .fan_mode
+            .as_ref()
+            .and_then(SysfsFile::read_value::<u32>)
+            .map(FanMode::from_pwm_enable);
+        stats.sensors = self
+            .sensors
+            .iter()
+            .filter_map(|(index, label, input)| {
+                Some(TemperatureSensor {
+                    index: *index,
+                    label: label.clone(),
+                    celsius: input.read_value::<i64>()? as f64 / 1000.0,
+                })
+            })
+            .collect();
+        Some(stats)
+    }
+
+    /// Read the automatic fan curve, empty if not exposed
+    pub fn fan_curve(&self) -> Vec<(f64, u8)> {
+        self.hwmon_path
+            .as_deref()
+            .map(read_fan_curve)
+            .unwrap_or_default()
+    }
+
+    /// Switch the fan to manual control with a fixed PWM duty cycle (0-255)
+    ///
+    /// If the duty cycle can't be written, the previous fan mode is restored.
+    pub fn set_fan_pwm(&self, pwm: u8) -> Result<()> {
+        let hwmon_path = self.control_path()?;
+        if !hwmon_path.join("pwm1").exists() {
+            return Err(Error::FeatureNotAvailable {
+                feature: "Fan control".to_string(),
+            });
+        }
+        let previous = self
+            .fan_mode
+            .as_ref()
+            .and_then(SysfsFile::read_value::<u32>);
+        write_control(hwmon_path, "pwm1_enable", FanMode::Manual.pwm_enable())?;
+        write_control(hwmon_path, "pwm1", pwm.into()).inspect_err(|_| {
+            if let Some(previous) = previous {
+                let _ = write_control(hwmon_path, "pwm1_enable", previous);
+            }
+        })
+    }
+
+    /// Hand fan control back to the firmware or driver
+    pub fn set_fan_auto(&self) -> Result<()> {
+        write_control(
+            self.control_path()?,
+            "pwm1_enable",
+            FanMode::Auto.pwm_enable(),
+        )
+    }
+
+    fn control_path(&self) -> Result<&Path> {
+        self.hwmon_path
+            .as_deref()
+            .ok_or_else(|| Error::FeatureNotAvailable {
+                feature: "Fan control".to_string(),
+            })
     }
 }
 
@@ -147,4 +381,128 @@ mod tests {
         assert!(temp.is_high());
         assert!(temp.is_critical());
     }
+
+    #[test]
+    fn test_temperature_sensors() {
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-temp-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&hwmon);
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::write(hwmon.join("temp1_input"), "52000\n").unwrap();
+        fs::write(hwmon.join("temp1_label"), "pkg\n").unwrap();
+        // Board sensor without a label file
+        fs::write(hwmon.join("temp2_input"), "41500\n").unwrap();
+        fs::write(hwmon.join("temp10_input"), "60000\n").unwrap();
+        fs::write(hwmon.join("temp10_label"), "vram\n").unwrap();
+        fs::write(hwmon.join("temp3_crit"), "100000\n").unwrap();
+
+        let sensors = read_temperature_sensors(&hwmon);
+        let labels: Vec<(u32, &str)> = sensors
+            .iter()
+            .map(|s| (s.index, s.label.as_str()))
+            .collect();
+        assert_eq!(labels, [(1, "pkg"), (2, "temp2"), (10, "vram")]);
+        assert_eq!(sensors[1].celsius, 41.5);
+
+        // The reader keeps the same files open and sees new values
+        let reader = HwmonReader::from_path(Some(hwmon.clone()));
+        assert!(!reader.has_fan());
+        fs::write(hwmon.join("temp2_input"), "43000\n").unwrap();
+        let stats = reader.read().unwrap();
+        assert_eq!(stats.gpu_celsius, 52.0);
+        assert_eq!(stats.sensors.len(), 3);
+        assert_eq!(stats.sensors[1].celsius, 43.0);
+        assert_eq!(stats.sensors[2].label, "vram");
+
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
+
+    #[test]
+    fn test_fan_control() {
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-fan-{}", std::process::id()));
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::write(hwmon.join("temp1_input"), "52000\n").unwrap();
+        fs::write(hwmon.join("pwm1_enable"), "2\n").unwrap();
+
+        let reader = HwmonReader::from_path(Some(hwmon.clone()));
+        assert_eq!(reader.read().unwrap().fan_control_mode, Some(FanMode::Auto));
+
+        // No pwm1 file: the fan is left in its previous mode
+        assert!(matches!(
+            reader.set_fan_pwm(128),
+            Err(Error::FeatureNotAvailable { .. })
+        ));
+        assert_eq!(reader.read().unwrap().fan_control_mode, Some(FanMode::Auto));
+
+        // An unwritable pwm1 (a directory here) restores the previous mode
+        fs::create_dir(hwmon.join("pwm1")).unwrap();
+        assert!(reader.set_fan_pwm(128).is_err());
+        assert_eq!(reader.read().unwrap().fan_control_mode, Some(FanMode::Auto));
+        fs::remove_dir(hwmon.join("pwm1")).unwrap();
+
+        fs::write(hwmon.join("pwm1"), "0\n").unwrap();
+        reader.set_fan_pwm(128).unwrap();
+        assert_eq!(fs::read_to_string(hwmon.join("pwm1")).unwrap(), "128");
+        assert_eq!(
+            reader.read().unwrap().fan_control_mode,
+            Some(FanMode::Manual)
+        );
+        reader.set_fan_auto().unwrap();
+        assert_eq!(reader.read().unwrap().fan_control_mode, Some(FanMode::Auto));
+
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
+
+    #[test]
+    fn test_fan_curve() {
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-curve-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&hwmon);
+        fs::create_dir_all(&hwmon).unwrap();
+        assert!(read_fan_curve(&hwmon).is_empty());
+
+        fs::write(hwmon.join("pwm1_auto_point2_temp"), "70000\n").unwrap();
+        fs::write(hwmon.join("pwm1_auto_point2_pwm"), "200\n").unwrap();
+        fs::write(hwmon.join("pwm1_auto_point1_temp"), "45000\n").unwrap();
+        fs::write(hwmon.join("pwm1_auto_point1_pwm"), "80\n").unwrap();
+        // Named after the temperature channel instead
+        fs::write(hwmon.join("temp1_auto_point10_temp"), "90500\n").unwrap();
+        fs::write(hwmon.join("temp1_auto_point10_pwm"), "255\n").unwrap();
+        // Incomplete point
+        fs::write(hwmon.join("pwm1_auto_point3_temp"), "80000\n").unwrap();
+
+        assert_eq!(
+            read_fan_curve(&hwmon),
+            [(45.0, 80), (70.0, 200), (90.5, 255)]
+        );
+
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
+
+    #[test]
+    fn test_hardware_critical_threshold() {
+        let mut temp = TemperatureStats::new(95.0);
+        temp.critical_celsius = Some(105.0);
+        assert!(!temp.is_critical());
+
+        temp.critical_celsius = Some(85.0);
+        temp.gpu_celsius = 87.0;
+        assert!(temp.is_critical());
+    }
+
+    #[test]
+    fn test_thermal_margin() {
+        let mut temp = TemperatureStats::new(72.5);
+        assert_eq!(temp.thermal_margin_celsius(), None);
+
+        temp.critical_celsius = Some(100.0);
+        assert_eq!(temp.thermal_margin_celsius(), Some(27.5));
+
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-max-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&hwmon);
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::write(hwmon.join("temp1_max"), "95000\n").unwrap();
+        assert_eq!(read_critical_temperature(&hwmon), Some(95.0));
+        fs::write(hwmon.join("temp1_crit"), "105000\n").unwrap();
+        assert_eq!(read_critical_temperature(&hwmon), Some(105.0));
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
 }
@@ -3,77 +3,84 @@
 //! This module provides access to Intel GPU statistics on Linux systems
 //! through the i915 or xe driver's PMU (Performance Monitoring Unit) interface.
 
+pub mod bandwidth;
+pub mod drm;
 pub mod fdinfo;
+pub mod freq;
 pub mod hwmon;
+pub mod paths;
 pub mod perf;
 pub mod pmu;
 pub mod rapl;
+pub mod rc6;
+pub mod sysfs;
 pub mod throttle;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread::{self, JoinHandle};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::builder::{first_usable_gpu, OpenOptions, FREQUENCY_PROBE_WINDOW};
 use crate::error::{Error, Result};
+use crate::logging;
+use crate::sampling::{read_window, SamplingOptions, SamplingTimer, Subscribers};
+use crate::snapshot::{
+    percent, AbsoluteStats, CounterSnapshot, EngineDeltas, EngineSnapshot, Rc6Snapshot,
+    IN_USE_WINDOW,
+};
+use crate::throttle_history::{lock_shared, ThrottleTracker};
 use crate::types::*;
 
+pub use crate::sampling::SamplingHandle;
+pub use perf::has_perfmon_capability;
+
+use bandwidth::BandwidthReader;
+use freq::SysfsFrequency;
 use hwmon::HwmonReader;
-use perf::{open_i915_event, PerfEvent};
-use pmu::{discover_gpus, discover_pmu, get_engine_instances, PmuInfo};
+use perf::{
+    open_i915_event, open_i915_event_stopped, open_i915_event_with_flags, EventConfig, PerfEvent,
+};
+use pmu::{
+    discover_gpus, discover_pmu, get_engine_instances, media_engine_count, GtEvent, PmuInfo,
+};
 use rapl::RaplReader;
+use sysfs::SysfsFile;
 use throttle::ThrottleReader;
 
-/// Handle for controlling background sampling
-pub struct SamplingHandle {
-    stop_flag: Arc<AtomicBool>,
-    thread: Option<JoinHandle<()>>,
-}
-
-impl SamplingHandle {
-    /// Stop the background sampling
-    pub fn stop(mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        if let Some(thread) = self.thread.take() {
-            let _ = thread.join();
-        }
-    }
-
-    /// Check if sampling is still running
-    pub fn is_running(&self) -> bool {
-        !self.stop_flag.load(Ordering::SeqCst)
-    }
-}
-
-impl Drop for SamplingHandle {
-    fn drop(&mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        if let Some(thread) = self.thread.take() {
-            let _ = thread.join();
-        }
-    }
-}
+/// How often throttle reasons are polled between samples in the sampling thread
+const THROTTLE_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Tracks the state of a single engine's counters
+///
+/// `busy` counts busy nanoseconds, except on xe kernels with only tick
+/// events: there it counts active ticks and `total` counts all ticks.
 struct EngineCounters {
+    instance: u16,
     busy: Option<PerfEvent>,
     wait: Option<PerfEvent>,
     sema: Option<PerfEvent>,
+    total: Option<PerfEvent>,
     last_busy: u64,
     last_wait: u64,
     last_sema: u64,
+    last_total: u64,
 }
 
 impl EngineCounters {
-    fn new() -> Self {
+    fn new(instance: u16) -> Self {
         Self {
+            instance,
             busy: None,
             wait: None,
             sema: None,
+            total: None,
             last_busy: 0,
             last_wait: 0,
             last_sema: 0,
+            last_total: 0,
         }
     }
 
@@ -93,9 +100,9 @@ impl EngineCounters {
             let current = event.read_value()?;
             let delta = current.saturating_sub(self.last_wait);
             self.last_wait = current;
-            delta
+            Some(delta)
         } else {
-            0
+            None
         };
 
         // Read sema delta
@@ -103,33 +110,237 @@ impl EngineCounters {
             let current = event.read_value()?;
             let delta = current.saturating_sub(self.last_sema);
             self.last_sema = current;
-            delta
+            Some(delta)
         } else {
-            0
+            None
         };
 
-        let elapsed_ns = elapsed_ns as f64;
-        let busy_percent = if elapsed_ns > 0.0 {
-            (busy as f64 / elapsed_ns * 100.0).min(100.0)
+        // Tick events count all engine ticks alongside the active ones
+        let total = if let Some(ref mut event) = self.total {
+            let current = event.read_value()?;
+            let delta = current.saturating_sub(self.last_total);
+            self.last_total = current;
+            Some(delta)
         } else {
-            0.0
+            None
         };
-        let wait_percent = if elapsed_ns > 0.0 {
-            (wait as f64 / elapsed_ns * 100.0).min(100.0)
-        } else {
-            0.0
+
+        let deltas = EngineDeltas {
+            busy,
+            wait,
+            sema,
+            total,
         };
-        let sema_percent = if elapsed_ns > 0.0 {
-            (sema as f64 / elapsed_ns * 100.0).min(100.0)
-        } else {
-            0.0
+        Ok(deltas.utilization(elapsed_ns))
+    }
+
+    /// Read the cumulative counter values without advancing the baseline
+    fn snapshot(&mut self, class: EngineClass, primary: bool) -> Result<EngineSnapshot> {
+        let read =
+            |event: &mut Option<PerfEvent>| event.as_mut().map(PerfEvent::read_value).transpose();
+        Ok(EngineSnapshot {
+            class,
+            instance: self.instance,
+            primary,
+            busy: read(&mut self.busy)?.unwrap_or(0),
+            wait: read(&mut self.wait)?,
+            sema: read(&mut self.sema)?,
+            total_ticks: read(&mut self.total)?,
+            context_switches: None,
+        })
+    }
+}
+
+/// Tracks the frequency counters of a single GT
+struct GtFrequencyCounters {
+    gt: u16,
+    actual: Option<PerfEvent>,
+    requested: Option<PerfEvent>,
+    last_actual: u64,
+    last_requested: u64,
+    /// Whether either counter has advanced since opening
+    counting: bool,
+    /// Frequency computed on the most recent read
+    last_stats: FrequencyStats,
+}
+
+impl GtFrequencyCounters {
+    fn open(pmu: &PmuInfo, gt: u16, start_disabled: bool) -> Self {
+        let open = |event: GtEvent| {
+            let name = pmu.gt_event_name(event)?;
+            let config = pmu.gt_event_config(name, gt)?;
+            open_gt_event(pmu, config, name, start_disabled).ok()
         };
 
-        Ok(EngineUtilization::new(
-            busy_percent,
-            wait_percent,
-            sema_percent,
-        ))
+        let mut counters = Self {
+            gt,
+            actual: open(GtEvent::ActualFrequency),
+            requested: open(GtEvent::RequestedFrequency),
+            last_actual: 0,
+            last_requested: 0,
+            counting: false,
+            last_stats: FrequencyStats::default(),
+        };
+
+        // Initialize last values
+        if let Some(ref mut freq) = counters.actual {
+            counters.last_actual = freq.read_value().unwrap_or(0);
+        }
+        if let Some(ref mut freq) = counters.requested {
+            counters.last_requested = freq.read_value().unwrap_or(0);
+        }
+
+        counters
+    }
+
+    /// Check whether the counters advanced since opening
+    ///
+    /// Leaves the last values untouched, so the first read still covers the
+    /// time since opening.
+    fn probe(&mut self) {
+        let advanced = |event: &mut Option<PerfEvent>, last: u64| {
+            event
+                .as_mut()
+                .and_then(|event| event.read_value().ok())
+                .is_some_and(|value| value > last)
+        };
+        self.counting |= advanced(&mut self.actual, self.last_actual)
+            || advanced(&mut self.requested, self.last_requested);
+    }
+
+    fn read(&mut self, elapsed_ns: u64) -> Result<()> {
+        let mut actual_mhz = 0u32;
+        let mut requested_mhz = 0u32;
+
+        if let Some(ref mut freq) = self.actual {
+            let current = freq.read_value()?;
+            let delta = current.saturating_sub(self.last_actual);
+            self.last_actual = current;
+            self.counting |= delta > 0;
+
+            // Frequency is reported in MHz * ns, so divide by elapsed ns to get MHz
+            if let Some(mhz) = delta.checked_div(elapsed_ns) {
+                actual_mhz = mhz as u32;
+            }
+        }
+
+        if let Some(ref mut freq) = self.requested {
+            let current = freq.read_value()?;
+            let delta = current.saturating_sub(self.last_requested);
+            self.last_requested = current;
+            self.counting |= delta > 0;
+
+            if let Some(mhz) = delta.checked_div(elapsed_ns) {
+                requested_mhz = mhz as u32;
+            }
+        }
+
+        self.last_stats = FrequencyStats::new(actual_mhz, requested_mhz);
+        Ok(())
+    }
+}
+
+/// Tracks the RC6 residency counter of a single GT
+struct GtRc6Counter {
+    gt: u16,
+    event: PerfEvent,
+    last_value: u64,
+    /// Nanoseconds per counter increment, from the event's unit
+    ns_per_count: f64,
+    /// Sysfs residency to check the unit against, dropped once checked
+    reference: Option<Rc6Reference>,
+    /// Residency computed on the most recent read
+    last_stats: Option<Rc6Stats>,
+}
+
+/// Sysfs RC6 residency and PMU count when the unit check started
+struct Rc6Reference {
+    file: SysfsFile,
+    start_ms: u64,
+    start_count: u64,
+}
+
+impl GtRc6Counter {
+    fn open(
+        pmu: &PmuInfo,
+        name: &str,
+        gt: u16,
+        reference: Option<PathBuf>,
+        start_disabled: bool,
+    ) -> Option<Self> {
+        let config = pmu.gt_event_config(name, gt)?;
+        let mut event = open_gt_event(pmu, config, name, start_disabled).ok()?;
+        let last_value = event.read_value().unwrap_or(0);
+        let ns_per_count = pmu.gt_event_unit_ns(name, gt).unwrap_or(1.0);
+        let reference = reference
+            .as_deref()
+            .and_then(SysfsFile::open)
+            .and_then(|file| {
+                let start_ms = file.read_value()?;
+                Some(Rc6Reference {
+                    file,
+                    start_ms,
+                    start_count: last_value,
+                })
+            });
+        Some(Self {
+            gt,
+            event,
+            last_value,
+            ns_per_count,
+            reference,
+            last_stats: None,
+        })
+    }
+
+    fn read(&mut self, elapsed_ns: u64) -> Result<Rc6Stats> {
+        let current = self.event.read_value()?;
+        let delta = current.saturating_sub(self.last_value);
+        self.last_value = current;
+        self.check_unit(current);
+
+        let stats = Rc6Stats::new(percent(self.to_ns(delta), elapsed_ns));
+        self.last_stats = Some(stats.clone());
+        Ok(stats)
+    }
+
+    /// Counter value converted to nanoseconds
+    fn to_ns(&self, count: u64) -> u64 {
+        (count as f64 * self.ns_per_count) as u64
+    }
+
+    /// Correct the counter unit once enough sysfs residency has accumulated
+    ///
+    /// Stops checking if the sysfs file can no longer be read.
+    fn check_unit(&mut self, current: u64) {
+        let Some(reference) = &self.reference else {
+            return;
+        };
+        let Some(ms) = reference.file.read_value::<u64>() else {
+            self.reference = None;
+            return;
+        };
+        let counts = current.saturating_sub(reference.start_count);
+        if let Some(ns_per_count) =
+            rc6::ns_per_count_from_reference(counts, ms.saturating_sub(reference.start_ms))
+        {
+            self.ns_per_count = ns_per_count;
+            self.reference = None;
+        }
+    }
+}
+
+/// Open a frequency or RC6 event, stopped if the counters start disabled
+fn open_gt_event(
+    pmu: &PmuInfo,
+    config: EventConfig,
+    name: &str,
+    start_disabled: bool,
+) -> Result<PerfEvent> {
+    if start_disabled {
+        open_i915_event_stopped(pmu.type_id, config, 0, name)
+    } else {
+        open_i915_event(pmu.type_id, config, name)
     }
 }
 
@@ -137,54 +348,67 @@ impl EngineCounters {
 ///
 /// This struct provides access to Intel GPU statistics on Linux through
 /// the i915 or xe driver's PMU interface.
+///
+/// `IntelGpu` is `Send`: it can be moved into another thread or a blocking
+/// task (e.g. `tokio::task::spawn_blocking`). Reads take `&mut self`, so wrap
+/// it in a `Mutex` if several threads need to read from the same instance.
 pub struct IntelGpu {
-    /// PMU information
-    pmu: PmuInfo,
+    /// PMU information, `None` when opened best effort without a PMU
+    pmu: Option<PmuInfo>,
+    /// Driver bound to the GPU
+    driver: GpuDriver,
     /// GPU information
     gpu_info: GpuInfo,
-    /// Engine counters
-    engines: HashMap<EngineClass, EngineCounters>,
-    /// Frequency requested event
-    freq_req: Option<PerfEvent>,
-    /// Frequency actual event
-    freq_act: Option<PerfEvent>,
-    /// RC6 residency event
-    rc6: Option<PerfEvent>,
-    /// Last frequency requested value
-    last_freq_req: u64,
-    /// Last frequency actual value
-    last_freq_act: u64,
-    /// Last RC6 value
-    last_rc6: u64,
+    /// Engine counters, one entry per opened instance of each class
+    engines: HashMap<EngineClass, Vec<EngineCounters>>,
+    /// Instance reported as the primary engine of each class
+    primary_instances: HashMap<EngineClass, u16>,
+    /// Frequency counters, one entry per GT
+    frequency: Vec<GtFrequencyCounters>,
+    /// Sysfs frequency reader, used to validate and replace PMU readings
+    sysfs_freq: SysfsFrequency,
+    /// GT hosting the media engines, if separate from the render GT
+    media_gt: Option<u16>,
+    /// Runtime power management state of the PCI device
+    runtime_status: Option<SysfsFile>,
+    /// Source of the actual frequency on the most recent read
+    actual_freq_source: FrequencySource,
+    /// Source of the requested frequency on the most recent read
+    requested_freq_source: FrequencySource,
+    /// RC6 residency counters, one entry per GT
+    rc6: Vec<GtRc6Counter>,
+    /// When the perf counters were opened
+    opened_at: Instant,
     /// Last read timestamp
     last_timestamp: Instant,
-    /// Whether compute engine is available
-    has_compute: bool,
+    /// Stats returned by the most recent read
+    last_stats: Option<GpuStats>,
+    /// Options set through the builder
+    options: OpenOptions,
+    /// Number of compute engine (CCS) instances the PMU lists
+    compute_instances: u32,
     /// Hwmon reader for temperature and fan speed
     hwmon: HwmonReader,
     /// Throttle reader
     throttle_reader: ThrottleReader,
     /// RAPL power reader
     rapl_reader: RaplReader,
+    /// Memory bandwidth reader (integrated GPUs)
+    bandwidth_reader: BandwidthReader,
+    /// Throttle residency history, shared with the sampling handle
+    throttle_history: Arc<Mutex<ThrottleTracker>>,
+    /// Non-fatal problems met while opening
+    open_warnings: Vec<OpenWarning>,
 }
 
 impl IntelGpu {
     /// Detect and open the first available Intel GPU
+    ///
+    /// GPUs bound to vfio-pci (passed through to a VM) are skipped.
     pub fn detect() -> Result<Self> {
-        let gpus = discover_gpus()?;
-        let gpu = gpus.into_iter().next().ok_or(Error::NoGpuFound)?;
+        let gpu = first_usable_gpu(discover_gpus()?)?;
 
-        let pmus = discover_pmu()?;
-        let pmu = pmus
-            .into_iter()
-            .find(|p| p.card_id == gpu.id)
-            .or_else(|| {
-                // Fallback: use the first PMU
-                discover_pmu().ok()?.into_iter().next()
-            })
-            .ok_or(Error::PmuNotAvailable)?;
-
-        Self::open_with_pmu(gpu, pmu)
+        Self::open_info(gpu)
     }
 
     /// Open a specific GPU by card ID (e.g., "card0")
@@ -197,14 +421,36 @@ impl IntelGpu {
                     path: card_id.into(),
                 })?;
 
-        let pmus = discover_pmu()?;
-        let pmu = pmus
-            .into_iter()
-            .find(|p| p.card_id == gpu.id)
-            .or_else(|| discover_pmu().ok()?.into_iter().next())
-            .ok_or(Error::PmuNotAvailable)?;
+        Self::open_info(gpu)
+    }
 
-        Self::open_with_pmu(gpu, pmu)
+    /// Open a GPU previously returned by [`list_gpus`](Self::list_gpus)
+    ///
+    /// Skips GPU rediscovery and only looks up the matching PMU, which avoids
+    /// redundant sysfs scans when opening every GPU in a list.
+    pub fn open_info(gpu_info: GpuInfo) -> Result<Self> {
+        Self::open_with_options(gpu_info, OpenOptions::default())
+    }
+
+    /// Internal: open GPU with builder options
+    pub(crate) fn open_with_options(gpu_info: GpuInfo, options: OpenOptions) -> Result<Self> {
+        if gpu_info.is_passthrough() {
+            return Err(Error::GpuPassthrough {
+                card_id: gpu_info.id,
+            });
+        }
+        let (pmu, pmu_error) = match find_pmu_for(&gpu_info) {
+            Ok(pmu) => (Some(pmu), None),
+            Err(e) if options.best_effort => (None, Some(e)),
+            Err(e) => return Err(e),
+        };
+        let mut gpu = Self::open_with_pmu(gpu_info, pmu, options)?;
+        if let Some(e) = pmu_error {
+            gpu.open_warnings.push(OpenWarning::PmuUnavailable {
+                reason: e.to_string(),
+            });
+        }
+        Ok(gpu)
     }
 
     /// List all available Intel GPUs
@@ -212,35 +458,87 @@ impl IntelGpu {
         discover_gpus()
     }
 
-    /// Internal: open GPU with specific PMU
-    fn open_with_pmu(gpu_info: GpuInfo, pmu: PmuInfo) -> Result<Self> {
-        let available_engines = get_engine_instances(&pmu);
-        let has_compute = available_engines.contains_key(&EngineClass::Compute);
+    /// Number of engine instances the PMU exposes for each GPU
+    ///
+    /// GPUs whose PMU cannot be found count as having none.
+    pub(crate) fn engine_counts(gpus: &[GpuInfo]) -> Vec<usize> {
+        let pmus = discover_pmu().unwrap_or_default();
+        gpus.iter()
+            .map(|gpu| {
+                pmus.iter()
+                    .find(|pmu| pmu.matches_gpu(gpu))
+                    .map_or(0, |pmu| {
+                        get_engine_instances(pmu).values().map(Vec::len).sum()
+                    })
+            })
+            .collect()
+    }
+
+    /// Internal: open GPU with specific PMU, or only its sensors without one
+    fn open_with_pmu(
+        mut gpu_info: GpuInfo,
+        pmu: Option<PmuInfo>,
+        options: OpenOptions,
+    ) -> Result<Self> {
+        let available_engines = pmu.as_ref().map(get_engine_instances).unwrap_or_default();
+        // Without a PMU the sysfs layout follows the bound driver
+        let driver = pmu
+            .as_ref()
+            .map(|pmu| pmu.driver)
+            .or(gpu_info.driver)
+            .unwrap_or(GpuDriver::I915);
+        let compute_instances = available_engines
+            .get(&EngineClass::Compute)
+            .map_or(0, |instances| instances.len() as u32);
+        if gpu_info.media_engines.is_none() {
+            gpu_info.media_engines =
+                Some(media_engine_count(&available_engines)).filter(|&count| count > 0);
+        }
 
         // Initialize hwmon reader for temperature and fan speed
         let hwmon = HwmonReader::new(&gpu_info.pci_path);
 
         // Initialize throttle reader
-        let throttle_reader = ThrottleReader::new(&gpu_info.id);
+        let throttle_reader = ThrottleReader::new(&gpu_info.id, &gpu_info.pci_path);
 
         // Initialize RAPL power reader
-        let rapl_reader = RaplReader::new(&gpu_info.pci_path);
+        let rapl_reader = RaplReader::new(&gpu_info.pci_path, gpu_info.is_integrated());
+
+        // Initialize memory bandwidth reader
+        let bandwidth_reader =
+            BandwidthReader::new(options.system_memory_bandwidth && gpu_info.is_integrated());
+
+        // Initialize sysfs frequency reader for validating PMU frequencies
+        let media_gt = separate_media_gt(&gpu_info.id, driver);
+        let sysfs_freq = SysfsFrequency::new(&gpu_info.id, driver, media_gt);
+        let runtime_status = SysfsFile::open(
+            &paths::card_sysfs_dir(&gpu_info.id).join("device/power/runtime_status"),
+        );
 
         let mut gpu = Self {
             pmu,
+            driver,
             gpu_info,
             engines: HashMap::new(),
-            freq_req: None,
-            freq_act: None,
-            rc6: None,
-            last_freq_req: 0,
-            last_freq_act: 0,
-            last_rc6: 0,
+            primary_instances: HashMap::new(),
+            frequency: Vec::new(),
+            sysfs_freq,
+            media_gt,
+            runtime_status,
+            actual_freq_source: FrequencySource::Unavailable,
+            requested_freq_source: FrequencySource::Unavailable,
+            rc6: Vec::new(),
+            opened_at: Instant::now(),
             last_timestamp: Instant::now(),
-            has_compute,
+            last_stats: None,
+            options,
+            compute_instances,
             hwmon,
             throttle_reader,
             rapl_reader,
+            bandwidth_reader,
+            throttle_history: Arc::new(Mutex::new(ThrottleTracker::new())),
+            open_warnings: Vec::new(),
         };
 
         // Open engine events
@@ -260,6 +558,16 @@ impl IntelGpu {
         &mut self,
         available_engines: &HashMap<EngineClass, Vec<u16>>,
     ) -> Result<()> {
+        let Some(uses_engine_ticks) = self.pmu.as_ref().map(PmuInfo::uses_engine_ticks) else {
+            return Ok(());
+        };
+        // GT hosting each engine class, needed to address the xe tick events
+        let engine_gts = if uses_engine_ticks {
+            pmu::xe_engine_gts(&self.gpu_info.id)
+        } else {
+            HashMap::new()
+        };
+
         let engine_classes = [
             EngineClass::Render,
             EngineClass::Copy,
@@ -270,11 +578,42 @@ impl IntelGpu {
 
         for class in engine_classes {
             if let Some(instances) = available_engines.get(&class) {
-                // Use instance 0 (primary) for each engine type
-                if instances.contains(&0) {
-                    if let Err(e) = self.open_engine(class, 0) {
+                let preferred = self.options.engine_instances.get(&class).copied();
+                let Some(primary) = select_instance(instances, preferred) else {
+                    continue;
+                };
+                self.primary_instances.insert(class, primary);
+
+                // Media and compute engines are opened on every instance so
+                // per-instance utilization can be reported; other classes
+                // only the primary
+                let mut to_open = vec![primary];
+                if matches!(
+                    class,
+                    EngineClass::Video | EngineClass::VideoEnhance | EngineClass::Compute
+                ) {
+                    to_open.extend(instances.iter().filter(|&&i| i != primary));
+                    to_open.sort();
+                }
+
+                for instance in to_open {
+                    let result = match engine_gts.get(&class) {
+                        Some(&gt) => self.open_engine_ticks(class, instance, gt),
+                        None => self.open_engine(class, instance),
+                    };
+                    if let Err(e) = result {
                         // Log warning but continue - some engines may not be available
-                        eprintln!("Warning: Could not open {} engine: {}", class.name(), e);
+                        logging::warn(format_args!(
+                            "Could not open {} engine instance {}: {}",
+                            class.name(),
+                            instance,
+                            e
+                        ));
+                        self.open_warnings.push(OpenWarning::EngineUnavailable {
+                            class,
+                            instance,
+                            reason: e.to_string(),
+                        });
                     }
                 }
             }
@@ -283,26 +622,48 @@ impl IntelGpu {
         Ok(())
     }
 
+    /// The PMU, or an error if the GPU was opened without one
+    fn pmu(&self) -> Result<&PmuInfo> {
+        self.pmu.as_ref().ok_or_else(|| Error::PmuNotAvailable {
+            reason: format!("no PMU was opened for {}", self.gpu_info.id),
+        })
+    }
+
+    /// Open an engine event with the attribute flags from the open options
+    fn open_engine_event(
+        &self,
+        config: impl Into<EventConfig>,
+        name: impl Into<String>,
+    ) -> Result<PerfEvent> {
+        let type_id = self.pmu()?.type_id;
+        let flags = self.options.engine_event_flags;
+        if self.options.start_disabled {
+            open_i915_event_stopped(type_id, config, flags, name)
+        } else {
+            open_i915_event_with_flags(type_id, config, flags, name)
+        }
+    }
+
     /// Open perf events for a specific engine
     fn open_engine(&mut self, class: EngineClass, instance: u16) -> Result<()> {
-        let mut counters = EngineCounters::new();
+        let mut counters = EngineCounters::new(instance);
 
         // Try to open busy counter (required)
         let busy_config = PmuInfo::engine_config(class, instance, 0);
         let busy_name = format!("{}-busy", class.name());
-        counters.busy = Some(open_i915_event(self.pmu.type_id, busy_config, &busy_name)?);
+        counters.busy = Some(self.open_engine_event(busy_config, busy_name)?);
 
         // Try to open wait counter (optional)
         let wait_config = PmuInfo::engine_config(class, instance, 1);
         let wait_name = format!("{}-wait", class.name());
-        if let Ok(event) = open_i915_event(self.pmu.type_id, wait_config, &wait_name) {
+        if let Ok(event) = self.open_engine_event(wait_config, wait_name) {
             counters.wait = Some(event);
         }
 
         // Try to open sema counter (optional)
         let sema_config = PmuInfo::engine_config(class, instance, 2);
         let sema_name = format!("{}-sema", class.name());
-        if let Ok(event) = open_i915_event(self.pmu.type_id, sema_config, &sema_name) {
+        if let Ok(event) = self.open_engine_event(sema_config, sema_name) {
             counters.sema = Some(event);
         }
 
@@ -317,46 +678,89 @@ impl IntelGpu {
             counters.last_sema = sema.read_value().unwrap_or(0);
         }
 
-        self.engines.insert(class, counters);
+        self.engines.entry(class).or_default().push(counters);
+        Ok(())
+    }
+
+    /// Open the xe tick counters for a specific engine
+    ///
+    /// Used when the xe PMU has no group-busy events. Utilization is the
+    /// ratio of active to total engine ticks; wait and sema are unavailable.
+    fn open_engine_ticks(&mut self, class: EngineClass, instance: u16, gt: u16) -> Result<()> {
+        let open = |event: &str| -> Result<PerfEvent> {
+            let config = self
+                .pmu()?
+                .xe_engine_config(event, class, instance, gt)
+                .ok_or_else(|| Error::EventNotSupported {
+                    event: event.to_string(),
+                })?;
+            self.open_engine_event(config, format!("{}-{}", class.name(), event))
+        };
+
+        let mut counters = EngineCounters::new(instance);
+        let mut active = open(pmu::XE_ACTIVE_TICKS)?;
+        let mut total = open(pmu::XE_TOTAL_TICKS)?;
+        counters.last_busy = active.read_value().unwrap_or(0);
+        counters.last_total = total.read_value().unwrap_or(0);
+        counters.busy = Some(active);
+        counters.total = Some(total);
+
+        self.engines.entry(class).or_default().push(counters);
         Ok(())
     }
 
     /// Open frequency events
+    ///
+    /// Event names differ between drivers, see [`GtEvent::names`].
     fn open_frequency_events(&mut self) -> Result<()> {
-        // Try named events first
-        if let Some(config) = self.pmu.event_config("actual-frequency") {
-            if let Ok(event) = open_i915_event(self.pmu.type_id, config, "actual-frequency") {
-                self.freq_act = Some(event);
+        let Some(pmu) = &self.pmu else {
+            return Ok(());
+        };
+        let mut gts: Vec<u16> = [GtEvent::ActualFrequency, GtEvent::RequestedFrequency]
+            .into_iter()
+            .filter_map(|event| pmu.gt_event_name(event))
+            .flat_map(|name| pmu.gt_ids(name))
+            .collect();
+        gts.sort();
+        gts.dedup();
+
+        for gt in gts {
+            let counters = GtFrequencyCounters::open(pmu, gt, self.options.start_disabled);
+            if counters.actual.is_some() || counters.requested.is_some() {
+                self.frequency.push(counters);
             }
         }
 
-        if let Some(config) = self.pmu.event_config("requested-frequency") {
-            if let Ok(event) = open_i915_event(self.pmu.type_id, config, "requested-frequency") {
-                self.freq_req = Some(event);
+        // Stopped counters can't be probed; they are checked on later reads
+        if self.options.probe_frequency
+            && !self.frequency.is_empty()
+            && !self.options.start_disabled
+        {
+            thread::sleep(FREQUENCY_PROBE_WINDOW);
+            for counters in &mut self.frequency {
+                counters.probe();
             }
         }
 
-        // Initialize last values
-        if let Some(ref mut freq) = self.freq_act {
-            self.last_freq_act = freq.read_value().unwrap_or(0);
-        }
-        if let Some(ref mut freq) = self.freq_req {
-            self.last_freq_req = freq.read_value().unwrap_or(0);
-        }
-
         Ok(())
     }
 
-    /// Open RC6 residency event
+    /// Open RC6 residency events, one per GT
     fn open_rc6_event(&mut self) -> Result<()> {
-        if let Some(config) = self.pmu.event_config("rc6-residency") {
-            if let Ok(event) = open_i915_event(self.pmu.type_id, config, "rc6-residency") {
-                self.rc6 = Some(event);
-                if let Some(ref mut rc6) = self.rc6 {
-                    self.last_rc6 = rc6.read_value().unwrap_or(0);
-                }
-            }
-        }
+        let Some(pmu) = &self.pmu else {
+            return Ok(());
+        };
+        let Some(name) = pmu.gt_event_name(GtEvent::Rc6Residency) else {
+            return Ok(());
+        };
+        self.rc6 = pmu
+            .gt_ids(name)
+            .into_iter()
+            .filter_map(|gt| {
+                let reference = rc6::find_residency_ms(&self.gpu_info.id, self.driver, gt);
+                GtRc6Counter::open(pmu, name, gt, reference, self.options.start_disabled)
+            })
+            .collect();
 
         Ok(())
     }
@@ -365,131 +769,617 @@ impl IntelGpu {
     ///
     /// Returns a snapshot of the current GPU state. The utilization percentages
     /// are calculated based on the time elapsed since the last read.
+    ///
+    /// If called again within the minimum sample interval (1ms by default,
+    /// see [`IntelGpuBuilder::min_sample_interval`](crate::IntelGpuBuilder::min_sample_interval)),
+    /// the previous stats are returned unchanged.
     pub fn read_stats(&mut self) -> Result<GpuStats> {
+        let mut stats = GpuStats::new(Instant::now(), 0);
+        self.read_stats_into(&mut stats)?;
+        Ok(stats)
+    }
+
+    /// Read current GPU statistics into an existing `GpuStats`
+    ///
+    /// Like [`read_stats`](Self::read_stats), but overwrites `out` in place
+    /// so a sampling loop can reuse one buffer. The per-instance engine lists
+    /// keep their capacity, so once warmed up no allocation is made for
+    /// them; sensor readings such as the temperature sensor list are still
+    /// built fresh. On error `out` may be partially updated.
+    pub fn read_stats_into(&mut self, out: &mut GpuStats) -> Result<()> {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_timestamp);
+        if elapsed < self.options.min_sample_interval {
+            if let Some(ref previous) = self.last_stats {
+                out.copy_from(previous);
+                return Ok(());
+            }
+        }
         let elapsed_ns = elapsed.as_nanos() as u64;
 
-        let mut stats = GpuStats::new(now, elapsed_ns);
+        out.timestamp = now;
+        out.sample_duration_ns = elapsed_ns;
 
         // Read engine utilizations
-        if let Some(counters) = self.engines.get_mut(&EngineClass::Render) {
-            stats.engines.render = counters.read_utilization(elapsed_ns)?;
-        }
-        if let Some(counters) = self.engines.get_mut(&EngineClass::Video) {
-            stats.engines.video = counters.read_utilization(elapsed_ns)?;
-        }
-        if let Some(counters) = self.engines.get_mut(&EngineClass::VideoEnhance) {
-            stats.engines.video_enhance = counters.read_utilization(elapsed_ns)?;
-        }
-        if let Some(counters) = self.engines.get_mut(&EngineClass::Copy) {
-            stats.engines.blitter = counters.read_utilization(elapsed_ns)?;
-        }
-        if let Some(counters) = self.engines.get_mut(&EngineClass::Compute) {
-            stats.engines.compute = Some(counters.read_utilization(elapsed_ns)?);
-        }
+        let engines = &mut out.engines;
+        engines.render = self
+            .read_engine_class(EngineClass::Render, elapsed_ns, None)?
+            .unwrap_or_default();
+        engines.video = self
+            .read_engine_class(
+                EngineClass::Video,
+                elapsed_ns,
+                Some(&mut engines.video_instances),
+            )?
+            .unwrap_or_default();
+        engines.video_enhance = self
+            .read_engine_class(
+                EngineClass::VideoEnhance,
+                elapsed_ns,
+                Some(&mut engines.video_enhance_instances),
+            )?
+            .unwrap_or_default();
+        engines.blitter = self
+            .read_engine_class(EngineClass::Copy, elapsed_ns, None)?
+            .unwrap_or_default();
+        engines.compute = self.read_engine_class(
+            EngineClass::Compute,
+            elapsed_ns,
+            Some(&mut engines.compute_instances),
+        )?;
 
         // Read frequency
-        stats.frequency = self.read_frequency(elapsed_ns)?;
+        out.frequency = self.read_frequency(elapsed_ns)?;
 
         // Read RC6
-        stats.rc6 = self.read_rc6(elapsed_ns)?;
+        out.rc6 = self.read_rc6(elapsed_ns)?;
 
         // Read temperature (and fan speed if available)
-        stats.temperature = self.hwmon.read();
+        out.temperature = self.hwmon.read();
 
         // Read throttle information
-        stats.throttle = self.throttle_reader.read();
+        out.throttle = self.throttle_reader.read();
+        lock_shared(&self.throttle_history).record(now, out.throttle.as_ref());
 
         // Read power consumption
-        stats.power = self.rapl_reader.read();
+        out.power = self.rapl_reader.read();
+
+        // Read memory bandwidth
+        out.system_memory_bandwidth_gbps = self.bandwidth_reader.read();
+
+        out.memory = None;
+        out.perf_state = self.read_perf_state(&out.frequency);
+
+        out.engines.report_compute(self.options.compute_reporting);
+        if self.options.merge_media {
+            out.engines.merge_media();
+        }
 
         self.last_timestamp = now;
+        match self.last_stats {
+            Some(ref mut last) => last.copy_from(out),
+            None => self.last_stats = Some(out.clone()),
+        }
 
-        Ok(stats)
+        Ok(())
     }
 
-    /// Read frequency statistics
-    fn read_frequency(&mut self, elapsed_ns: u64) -> Result<FrequencyStats> {
-        let mut actual_mhz = 0u32;
-        let mut requested_mhz = 0u32;
+    /// Start all perf counters
+    ///
+    /// Counterpart of [`disable_counters`](Self::disable_counters) and of
+    /// opening with
+    /// [`IntelGpuBuilder::start_disabled`](crate::IntelGpuBuilder::start_disabled).
+    /// The measurement window restarts, so the next `read_stats` covers only
+    /// the time since this call.
+    pub fn enable_counters(&mut self) -> Result<()> {
+        self.for_each_event(|event| event.enable())?;
+        self.restart_window()
+    }
 
-        if let Some(ref mut freq) = self.freq_act {
-            let current = freq.read_value()?;
-            let delta = current.saturating_sub(self.last_freq_act);
-            self.last_freq_act = current;
+    /// Stop all perf counters to avoid counting overhead while idle
+    ///
+    /// Until [`enable_counters`](Self::enable_counters) is called,
+    /// `read_stats` reports idle engines, zero PMU frequency and no RC6
+    /// residency; sysfs and hwmon readings are unaffected.
+    pub fn disable_counters(&mut self) -> Result<()> {
+        self.for_each_event(|event| event.disable())
+    }
 
-            // Frequency is reported in MHz * ns, so divide by elapsed ns to get MHz
-            if elapsed_ns > 0 {
-                actual_mhz = (delta / elapsed_ns) as u32;
+    /// Apply `f` to every open perf event
+    fn for_each_event(&mut self, mut f: impl FnMut(&mut PerfEvent) -> Result<()>) -> Result<()> {
+        for counters in self.engines.values_mut().flatten() {
+            for event in [
+                &mut counters.busy,
+                &mut counters.wait,
+                &mut counters.sema,
+                &mut counters.total,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                f(event)?;
+            }
+        }
+        for counters in &mut self.frequency {
+            for event in [&mut counters.actual, &mut counters.requested]
+                .into_iter()
+                .flatten()
+            {
+                f(event)?;
             }
         }
+        for counter in &mut self.rc6 {
+            f(&mut counter.event)?;
+        }
+        Ok(())
+    }
 
-        if let Some(ref mut freq) = self.freq_req {
-            let current = freq.read_value()?;
-            let delta = current.saturating_sub(self.last_freq_req);
-            self.last_freq_req = current;
+    /// Make the current counter values the baseline of the next read
+    fn restart_window(&mut self) -> Result<()> {
+        let read = |event: &mut Option<PerfEvent>, last: &mut u64| -> Result<()> {
+            if let Some(event) = event {
+                *last = event.read_value()?;
+            }
+            Ok(())
+        };
+        for counters in self.engines.values_mut().flatten() {
+            read(&mut counters.busy, &mut counters.last_busy)?;
+            read(&mut counters.wait, &mut counters.last_wait)?;
+            read(&mut counters.sema, &mut counters.last_sema)?;
+            read(&mut counters.total, &mut counters.last_total)?;
+        }
+        for counters in &mut self.frequency {
+            read(&mut counters.actual, &mut counters.last_actual)?;
+            read(&mut counters.requested, &mut counters.last_requested)?;
+        }
+        for counter in &mut self.rc6 {
+            counter.last_value = counter.event.read_value()?;
+        }
+        self.last_timestamp = Instant::now();
+        self.last_stats = None;
+        Ok(())
+    }
+
+    /// Read the raw cumulative counters, for one-shot tools
+    ///
+    /// Unlike `read_stats`, a single call is meaningful: it reports the
+    /// counters' totals and how long they have been counting, without
+    /// computing percentages. The i915/xe PMU counters start when the GPU is
+    /// opened, not at boot, so a tool that opens the GPU and reads at once
+    /// sees near-zero totals; let some time pass first, or diff externally.
+    pub fn read_absolute(&mut self) -> Result<AbsoluteStats> {
+        let counters = self.snapshot_counters()?;
+        let counting_ns = counters
+            .timestamp
+            .saturating_duration_since(self.opened_at)
+            .as_nanos() as u64;
+        Ok(AbsoluteStats {
+            counters,
+            counting_ns: Some(counting_ns),
+        })
+    }
+
+    /// Check whether any engine is doing work right now
+    ///
+    /// Watches the engine busy counters for a short window (50 ms) instead
+    /// of parsing every process's fdinfo like
+    /// [`list_drm_clients`](Self::list_drm_clients), so it blocks for that
+    /// long. Returns `false` if the counters can't be read or are disabled
+    /// (see [`disable_counters`](Self::disable_counters)).
+    pub fn is_in_use(&mut self) -> bool {
+        let Ok(start) = self.snapshot_counters() else {
+            return false;
+        };
+        thread::sleep(IN_USE_WINDOW);
+        self.snapshot_counters()
+            .is_ok_and(|end| end.is_busy_since(&start))
+    }
+
+    /// Capture the cumulative counters for measuring a custom window
+    ///
+    /// Unlike `read_stats`, this does not advance the reader's own baseline,
+    /// so `read_stats` calls in between are unaffected and vice versa. Pass
+    /// two snapshots to [`GpuStats::from_snapshots`] to get the stats over
+    /// the window between them.
+    pub fn snapshot_counters(&mut self) -> Result<CounterSnapshot> {
+        let timestamp = Instant::now();
+
+        let mut engines = Vec::new();
+        for (class, instances) in &mut self.engines {
+            let primary = self.primary_instances.get(class).copied();
+            for counters in instances.iter_mut() {
+                let is_primary = primary == Some(counters.instance);
+                engines.push(counters.snapshot(*class, is_primary)?);
+            }
+        }
+        engines.sort_by_key(|e| (e.class as u16, e.instance));
+
+        let (actual_frequency, requested_frequency) = match self.frequency.first_mut() {
+            Some(gt0) => (
+                gt0.actual.as_mut().map(PerfEvent::read_value).transpose()?,
+                gt0.requested
+                    .as_mut()
+                    .map(PerfEvent::read_value)
+                    .transpose()?,
+            ),
+            None => (None, None),
+        };
+
+        let mut rc6 = Vec::with_capacity(self.rc6.len());
+        for counter in &mut self.rc6 {
+            rc6.push(Rc6Snapshot {
+                count: counter.event.read_value()?,
+                ns_per_count: counter.ns_per_count,
+            });
+        }
+
+        Ok(CounterSnapshot {
+            timestamp,
+            engines,
+            actual_frequency,
+            requested_frequency,
+            rc6,
+        })
+    }
 
-            if elapsed_ns > 0 {
-                requested_mhz = (delta / elapsed_ns) as u32;
+    /// Read utilization for every opened instance of an engine class
+    ///
+    /// Each reading is appended to `instances`, which is cleared first so
+    /// its capacity is reused. Returns the primary instance's reading.
+    fn read_engine_class(
+        &mut self,
+        class: EngineClass,
+        elapsed_ns: u64,
+        mut instances: Option<&mut Vec<EngineUtilization>>,
+    ) -> Result<Option<EngineUtilization>> {
+        if let Some(instances) = instances.as_deref_mut() {
+            instances.clear();
+        }
+        let primary = self.primary_instances.get(&class).copied();
+        let mut primary_reading = None;
+        for counters in self.engines.get_mut(&class).into_iter().flatten() {
+            let utilization = counters.read_utilization(elapsed_ns)?;
+            if Some(counters.instance) == primary {
+                primary_reading = Some(utilization.clone());
+            }
+            if let Some(instances) = instances.as_deref_mut() {
+                instances.push(utilization);
             }
         }
+        Ok(primary_reading)
+    }
+
+    /// Read frequency statistics
+    ///
+    /// Every GT is read so [`frequency_for_tile`](Self::frequency_for_tile)
+    /// stays current; the returned value is gt0's for compatibility. The gt0
+    /// values are checked against the hardware range from sysfs and replaced
+    /// by the sysfs frequency when missing or implausible. The media
+    /// frequency is the media GT's actual frequency, from the PMU if it has
+    /// counters for that GT and sysfs otherwise. Without a media GT it is
+    /// derived from the GT frequency when the driver reports a fixed media
+    /// frequency factor.
+    fn read_frequency(&mut self, elapsed_ns: u64) -> Result<FrequencyStats> {
+        for counters in &mut self.frequency {
+            counters.read(elapsed_ns)?;
+        }
+
+        // Counters that never advanced are treated as missing, so sysfs is
+        // used when it has a frequency
+        let primary = self.frequency.first().filter(|counters| counters.counting);
+        let pmu_actual = primary
+            .filter(|counters| counters.actual.is_some())
+            .map(|counters| counters.last_stats.actual_mhz);
+        let pmu_requested = primary
+            .filter(|counters| counters.requested.is_some())
+            .map(|counters| counters.last_stats.requested_mhz);
+
+        let (actual_mhz, actual_source) =
+            select_frequency(pmu_actual, &self.sysfs_freq, SysfsFrequency::read_actual);
+        let (requested_mhz, requested_source) = select_frequency(
+            pmu_requested,
+            &self.sysfs_freq,
+            SysfsFrequency::read_requested,
+        );
+        self.actual_freq_source = actual_source;
+        self.requested_freq_source = requested_source;
+
+        let media_mhz = self
+            .media_gt
+            .and_then(|gt| self.frequency.iter().find(|counters| counters.gt == gt))
+            .filter(|counters| counters.actual.is_some())
+            .map(|counters| counters.last_stats.actual_mhz)
+            .or_else(|| self.sysfs_freq.read_media_actual());
+        let media_freq_factor = self.sysfs_freq.read_media_freq_factor();
+        let media_mhz = media_mhz.or_else(|| {
+            media_freq_factor.map(|factor| (actual_mhz as f64 * factor).round() as u32)
+        });
 
-        Ok(FrequencyStats::new(actual_mhz, requested_mhz))
+        Ok(FrequencyStats::new(actual_mhz, requested_mhz)
+            .with_min_mhz(self.sysfs_freq.min_mhz())
+            .with_media_mhz(media_mhz)
+            .with_media_freq_factor(media_freq_factor))
+    }
+
+    /// Classify the performance level of a frequency reading
+    ///
+    /// `None` if neither the frequency nor the runtime power management
+    /// state is known.
+    fn read_perf_state(&self, frequency: &FrequencyStats) -> Option<PerfState> {
+        let suspended = self
+            .runtime_status
+            .as_ref()
+            .and_then(SysfsFile::read_value::<String>)
+            .is_some_and(|status| status == "suspended");
+        (suspended || self.actual_freq_source != FrequencySource::Unavailable)
+            .then(|| PerfState::classify(frequency, self.sysfs_freq.read_boost(), suspended))
     }
 
     /// Read RC6 residency
+    ///
+    /// Every GT is read so [`rc6_for_tile`](Self::rc6_for_tile) stays
+    /// current; the returned value is the mean over all GTs, which on
+    /// single-GT parts is simply gt0's residency.
     fn read_rc6(&mut self, elapsed_ns: u64) -> Result<Option<Rc6Stats>> {
-        if let Some(ref mut rc6) = self.rc6 {
-            let current = rc6.read_value()?;
-            let delta = current.saturating_sub(self.last_rc6);
-            self.last_rc6 = current;
-
-            let residency_percent = if elapsed_ns > 0 {
-                (delta as f64 / elapsed_ns as f64 * 100.0).min(100.0)
-            } else {
-                0.0
-            };
-
-            Ok(Some(Rc6Stats::new(residency_percent)))
-        } else {
-            Ok(None)
+        let mut residencies = Vec::with_capacity(self.rc6.len());
+        for counter in &mut self.rc6 {
+            residencies.push(counter.read(elapsed_ns)?.residency_percent);
         }
+        Ok(mean_rc6(&residencies))
     }
 
     /// Start continuous sampling with a callback
     ///
     /// The callback will be called with GPU statistics at the specified interval.
     /// Returns a handle that can be used to stop sampling.
-    pub fn start_sampling<F>(
+    pub fn start_sampling<F>(self, interval: Duration, callback: F) -> Result<SamplingHandle>
+    where
+        F: FnMut(GpuStats) + Send + 'static,
+    {
+        self.start_sampling_with(SamplingOptions::new(interval), callback)
+    }
+
+    /// Start continuous sampling with custom options
+    ///
+    /// Like [`start_sampling`](Self::start_sampling), but allows polling at a
+    /// faster inner rate to track peak utilization within each interval. See
+    /// [`SamplingOptions`].
+    pub fn start_sampling_with<F>(
         mut self,
-        interval: Duration,
+        options: SamplingOptions,
         mut callback: F,
     ) -> Result<SamplingHandle>
     where
         F: FnMut(GpuStats) + Send + 'static,
     {
+        options.validate()?;
+
         let stop_flag = Arc::new(AtomicBool::new(false));
         let stop_flag_clone = stop_flag.clone();
+        let throttle_history = self.throttle_history.clone();
+        let timer = SamplingTimer::new(options.interval);
+        let thread_timer = timer.clone();
+        let subscribers = Subscribers::new();
+        let thread_subscribers = subscribers.clone();
 
         let thread = thread::spawn(move || {
             while !stop_flag_clone.load(Ordering::SeqCst) {
-                thread::sleep(interval);
+                let result = match options.inner_interval {
+                    Some(inner) => read_window(options.interval, inner, || self.read_stats()),
+                    None => {
+                        self.sleep_polling_throttle(options.interval);
+                        self.read_stats()
+                    }
+                };
 
-                match self.read_stats() {
-                    Ok(stats) => callback(stats),
+                match result {
+                    Ok(stats) => {
+                        thread_timer.record(Instant::now());
+                        thread_subscribers.publish(&stats);
+                        callback(stats)
+                    }
                     Err(e) => {
-                        eprintln!("Error reading GPU stats: {}", e);
+                        logging::warn(format_args!("Error reading GPU stats: {}", e));
                         break;
                     }
                 }
             }
+            thread_subscribers.close();
         });
 
-        Ok(SamplingHandle {
+        Ok(
+            SamplingHandle::new(stop_flag, thread, throttle_history, timer)
+                .with_subscribers(subscribers),
+        )
+    }
+
+    /// Sleep for `interval`, sampling throttle reasons along the way
+    ///
+    /// Long sampling intervals would otherwise miss short throttle episodes
+    /// in the residency history.
+    fn sleep_polling_throttle(&mut self, interval: Duration) {
+        if !self.throttle_reader.is_available() {
+            thread::sleep(interval);
+            return;
+        }
+
+        let deadline = Instant::now() + interval;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining <= THROTTLE_POLL_INTERVAL {
+                thread::sleep(remaining);
+                return;
+            }
+            thread::sleep(THROTTLE_POLL_INTERVAL);
+            let throttle = self.throttle_reader.read();
+            lock_shared(&self.throttle_history).record(Instant::now(), throttle.as_ref());
+        }
+    }
+
+    /// Watch throttle reasons on a dedicated thread
+    ///
+    /// Throttle reasons are polled every `interval` and `callback` is called
+    /// once for every reason that becomes active or clears. Reasons already
+    /// active when watching starts are reported as becoming active. This is
+    /// much cheaper than reading full stats just to catch throttle
+    /// transitions, so `interval` can be short.
+    ///
+    /// The watcher is independent of this `IntelGpu`, which can keep being
+    /// used. Returns [`Error::FeatureNotAvailable`] if the GPU does not
+    /// expose throttle reasons.
+    pub fn watch_throttle<F>(&self, interval: Duration, mut callback: F) -> Result<SamplingHandle>
+    where
+        F: FnMut(ThrottleEvent) + Send + 'static,
+    {
+        if interval.is_zero() {
+            return Err(Error::InvalidConfig {
+                message: "throttle watch interval must be non-zero".to_string(),
+            });
+        }
+        if !self.throttle_reader.is_available() {
+            return Err(Error::FeatureNotAvailable {
+                feature: "Throttle monitoring".to_string(),
+            });
+        }
+
+        let reader = ThrottleReader::new(&self.gpu_info.id, &self.gpu_info.pci_path);
+        let stop_flag = Arc::new(AtomicBool::new(false));
+        let stop_flag_clone = stop_flag.clone();
+        let timer = SamplingTimer::new(interval);
+        let thread_timer = timer.clone();
+
+        let thread = thread::spawn(move || {
+            let mut previous = ThrottleInfo::new();
+            while !stop_flag_clone.load(Ordering::SeqCst) {
+                if let Some(current) = reader.read() {
+                    for event in current.changes_from(&previous) {
+                        callback(event);
+                    }
+                    previous = current;
+                }
+                thread_timer.record(Instant::now());
+                thread::sleep(interval);
+            }
+        });
+
+        Ok(SamplingHandle::new(
             stop_flag,
-            thread: Some(thread),
-        })
+            thread,
+            self.throttle_history.clone(),
+            timer,
+        ))
+    }
+
+    /// Percentage of the last `window` each throttle reason was active
+    ///
+    /// Throttle reasons are recorded on every [`read_stats`](Self::read_stats)
+    /// call, and every 100ms while [`start_sampling`](Self::start_sampling)
+    /// is running. Up to ten minutes of history is kept. Returns an empty map
+    /// if throttle monitoring is unavailable or nothing has been recorded yet.
+    pub fn throttle_residency(&self, window: Duration) -> HashMap<ThrottleReason, f64> {
+        lock_shared(&self.throttle_history).residency(window)
+    }
+
+    /// List the GT (tile) ids with frequency counters
+    ///
+    /// Single-GT parts report `[0]`. Empty if no frequency events are available.
+    pub fn tiles(&self) -> Vec<u16> {
+        self.frequency.iter().map(|counters| counters.gt).collect()
+    }
+
+    /// Get the frequency of a specific GT (tile)
+    ///
+    /// Returns the value computed on the most recent
+    /// [`read_stats`](Self::read_stats) call, or `None` if the GT has no
+    /// frequency counters. [`GpuStats::frequency`] always reports gt0.
+    pub fn frequency_for_tile(&self, tile: u16) -> Option<FrequencyStats> {
+        self.frequency
+            .iter()
+            .find(|counters| counters.gt == tile)
+            .map(|counters| counters.last_stats.clone())
+    }
+
+    /// Get the RC6 residency of a specific GT (tile)
+    ///
+    /// Returns the value computed on the most recent
+    /// [`read_stats`](Self::read_stats) call, or `None` before the first read
+    /// or if the GT has no RC6 counter. [`GpuStats::rc6`] is the mean over
+    /// all GTs.
+    pub fn rc6_for_tile(&self, tile: u16) -> Option<Rc6Stats> {
+        self.rc6
+            .iter()
+            .find(|counter| counter.gt == tile)
+            .and_then(|counter| counter.last_stats.clone())
+    }
+
+    /// Check if the GPU has a PMU counter for its actual frequency
+    ///
+    /// The counter may still not work on some kernels; see
+    /// [`has_working_frequency`](Self::has_working_frequency).
+    pub fn has_frequency_event(&self) -> bool {
+        self.frequency
+            .first()
+            .is_some_and(|counters| counters.actual.is_some())
+    }
+
+    /// Check if a reported frequency of 0 MHz can be trusted
+    ///
+    /// True if the PMU frequency counters advanced, checked on every read,
+    /// or if sysfs reports a frequency. Otherwise `frequency.actual_mhz == 0`
+    /// means the frequency is unavailable rather than the GPU running at
+    /// 0 MHz. Before the first read the counters are unchecked unless opened
+    /// with
+    /// [`IntelGpuBuilder::probe_frequency`](crate::IntelGpuBuilder::probe_frequency).
+    /// Counters opened stopped (see
+    /// [`IntelGpuBuilder::start_disabled`](crate::IntelGpuBuilder::start_disabled))
+    /// are only checked once enabled and read.
+    pub fn has_working_frequency(&self) -> bool {
+        self.frequency
+            .first()
+            .is_some_and(|counters| counters.counting)
+            || self.sysfs_freq.read_actual().is_some()
+    }
+
+    /// Source of the actual frequency reported by the most recent read
+    pub fn actual_frequency_source(&self) -> FrequencySource {
+        self.actual_freq_source
+    }
+
+    /// Source of the requested frequency reported by the most recent read
+    pub fn requested_frequency_source(&self) -> FrequencySource {
+        self.requested_freq_source
+    }
+
+    /// Hardware maximum frequency (RP0) in MHz, if the driver reports it
+    ///
+    /// The reference for [`EngineStats::normalized_load`].
+    pub fn max_frequency_mhz(&self) -> Option<u32> {
+        self.sysfs_freq.max_mhz()
+    }
+
+    /// Get all monitoring capabilities in one snapshot
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            render: self.engines.contains_key(&EngineClass::Render),
+            video: self.engines.contains_key(&EngineClass::Video),
+            video_enhance: self.engines.contains_key(&EngineClass::VideoEnhance),
+            blitter: self.engines.contains_key(&EngineClass::Copy),
+            compute: self.engines.contains_key(&EngineClass::Compute),
+            frequency: !self.frequency.is_empty() || self.sysfs_freq.is_available(),
+            rc6: !self.rc6.is_empty(),
+            temperature: self.has_temperature(),
+            fan: self.has_fan(),
+            throttle: self.has_throttle(),
+            power: self.has_power(),
+        }
+    }
+
+    /// Non-fatal problems met while opening the GPU
+    ///
+    /// E.g. an engine the PMU lists but that failed to open, or the missing
+    /// PMU when opened best effort. Empty if everything opened.
+    pub fn open_warnings(&self) -> &[OpenWarning] {
+        &self.open_warnings
     }
 
     /// Get information about this GPU
@@ -497,14 +1387,26 @@ impl IntelGpu {
         &self.gpu_info
     }
 
-    /// Check if compute engine is available (Intel Arc GPUs)
+    /// Check if dedicated compute engines (CCS) are available (Intel Arc GPUs)
+    ///
+    /// Without them compute work (OpenCL, oneAPI, XMX) runs on the render
+    /// engine and is counted in render utilization.
     pub fn has_compute_engine(&self) -> bool {
-        self.has_compute
+        self.compute_instances > 0
+    }
+
+    /// Number of compute engine (CCS) instances, e.g. 4 on Arc A770
+    ///
+    /// 0 when compute shares the render engine. Counts the instances the
+    /// PMU lists, including any that failed to open (see
+    /// [`open_warnings`](Self::open_warnings)).
+    pub fn compute_instance_count(&self) -> u32 {
+        self.compute_instances
     }
 
     /// Get the driver type in use
     pub fn driver(&self) -> GpuDriver {
-        self.pmu.driver
+        self.driver
     }
 
     /// Check if temperature monitoring is available
@@ -518,6 +1420,9 @@ impl IntelGpu {
     }
 
     /// Check if throttle monitoring is available
+    ///
+    /// Also true on older kernels where throttling is only inferred, see
+    /// [`ThrottleInfo::inferred`].
     pub fn has_throttle(&self) -> bool {
         self.throttle_reader.is_available()
     }
@@ -527,6 +1432,59 @@ impl IntelGpu {
         self.rapl_reader.is_available()
     }
 
+    /// Check if system memory bandwidth is reported
+    ///
+    /// Only on integrated GPUs opened with
+    /// [`IntelGpuBuilder::system_memory_bandwidth`](crate::IntelGpuBuilder::system_memory_bandwidth),
+    /// from the CPU's memory controller counters; see
+    /// [`GpuStats::system_memory_bandwidth_gbps`].
+    pub fn has_system_memory_bandwidth(&self) -> bool {
+        self.bandwidth_reader.is_available()
+    }
+
+    /// Switch the fan to manual control with a fixed PWM duty cycle (0-255)
+    ///
+    /// Sets hwmon `pwm1_enable` to manual and writes `pwm1`, which requires
+    /// root privileges. Use [`set_fan_auto`](Self::set_fan_auto) to hand
+    /// control back. Returns [`Error::PermissionDenied`] if the files are not
+    /// writable and [`Error::FeatureNotAvailable`] if the fan is not
+    /// controllable.
+    pub fn set_fan_pwm(&self, pwm: u8) -> Result<()> {
+        self.hwmon.set_fan_pwm(pwm)
+    }
+
+    /// Read the automatic fan curve as (Celsius, PWM 0-255) points
+    ///
+    /// From the hwmon `pwm1_auto_pointN_*` files of discrete GPUs, ordered
+    /// by point. Empty if the hardware doesn't expose a programmable curve.
+    pub fn fan_curve(&self) -> Vec<(f64, u8)> {
+        self.hwmon.fan_curve()
+    }
+
+    /// Return the fan to automatic control
+    pub fn set_fan_auto(&self) -> Result<()> {
+        self.hwmon.set_fan_auto()
+    }
+
+    /// Current GPU power limit in watts
+    ///
+    /// Read from hwmon `power1_max` on discrete GPUs, or on integrated GPUs
+    /// from the powercap constraint of a GPU RAPL domain. `None` if neither
+    /// is exposed.
+    pub fn power_limit_watts(&self) -> Option<f64> {
+        self.rapl_reader.power_limit_watts()
+    }
+
+    /// Set the GPU power limit in watts
+    ///
+    /// Writes the same file [`power_limit_watts`](Self::power_limit_watts)
+    /// reads, which requires root privileges. Returns
+    /// [`Error::PermissionDenied`] if the file is not writable and
+    /// [`Error::FeatureNotAvailable`] if the GPU exposes no power limit.
+    pub fn set_power_limit_watts(&self, watts: f64) -> Result<()> {
+        self.rapl_reader.set_power_limit_watts(watts)
+    }
+
     /// List all processes using the GPU (DRM clients)
     ///
     /// Returns a list of processes that have open file descriptors
@@ -535,6 +1493,46 @@ impl IntelGpu {
         fdinfo::list_drm_clients()
     }
 
+    /// List DRM clients among the given PIDs only
+    ///
+    /// Cheaper than [`list_drm_clients`](Self::list_drm_clients) on busy
+    /// hosts since only these processes' fds are inspected instead of every
+    /// process in `/proc`. Missing processes are skipped.
+    pub fn list_drm_clients_for_pids(pids: &[u32]) -> Vec<DrmClient> {
+        fdinfo::list_drm_clients_for_pids(pids)
+    }
+
+    /// Measure per-process utilization over `window`
+    ///
+    /// Lists the DRM clients twice, `window` apart, and turns the engine
+    /// time each process accumulated in between into percentages of the
+    /// measured window (see [`DrmClientUtil::between`]). Blocks for
+    /// `window`.
+    pub fn sample_drm_clients(window: Duration) -> Vec<DrmClientUtil> {
+        let before = fdinfo::list_drm_clients();
+        let start = Instant::now();
+        thread::sleep(window);
+        let after = fdinfo::list_drm_clients();
+        let window_ns = start.elapsed().as_nanos() as u64;
+        DrmClientUtil::between(&before, &after, window_ns)
+    }
+
+    /// Count the processes with an open DRM fd on any Intel GPU
+    ///
+    /// Cheaper than counting [`list_drm_clients`](Self::list_drm_clients)
+    /// since only the fdinfo client IDs are read.
+    pub fn drm_client_count() -> usize {
+        fdinfo::drm_client_count()
+    }
+
+    /// Count the open DRM contexts (fdinfo `drm-client-id`s) on Intel GPUs
+    ///
+    /// A count that keeps growing in a long-running service points at a
+    /// context leak.
+    pub fn drm_context_count() -> usize {
+        fdinfo::drm_context_count()
+    }
+
     /// Find processes using Quick Sync (video encode/decode)
     ///
     /// Returns only processes that are actively using the video
@@ -542,6 +1540,82 @@ impl IntelGpu {
     pub fn find_quicksync_clients() -> Vec<DrmClient> {
         fdinfo::find_quicksync_clients()
     }
+
+    /// List processes using a specific GPU card (e.g. "card1")
+    ///
+    /// Unlike [`list_drm_clients`](Self::list_drm_clients), only fds open on
+    /// this card's render or card node are counted, so usage is attributed to
+    /// the right GPU on multi-GPU systems.
+    pub fn list_drm_clients_for(card_id: &str) -> Result<Vec<DrmClient>> {
+        let gpu = discover_gpus()?
+            .into_iter()
+            .find(|g| g.id == card_id)
+            .ok_or_else(|| Error::DeviceNotFound {
+                path: card_id.into(),
+            })?;
+
+        let nodes: Vec<&str> = [gpu.render_node.as_deref(), gpu.card_node.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(fdinfo::list_drm_clients_for_nodes(&nodes))
+    }
+}
+
+/// GT hosting the media engines when it differs from the render GT
+///
+/// Only xe reports which GT each engine class lives on. i915 exposes a
+/// standalone media GT too, but not which of its GTs it is.
+fn separate_media_gt(card_id: &str, driver: GpuDriver) -> Option<u16> {
+    if driver != GpuDriver::Xe {
+        return None;
+    }
+    let gts = pmu::xe_engine_gts(card_id);
+    let media = *gts.get(&EngineClass::Video)?;
+    (gts.get(&EngineClass::Render) != Some(&media)).then_some(media)
+}
+
+/// Choose between a PMU frequency and its sysfs fallback
+///
+/// The PMU value is used when present and plausible. Otherwise the sysfs
+/// value is preferred; an implausible PMU value is only kept if sysfs has
+/// nothing to offer.
+fn select_frequency(
+    pmu_mhz: Option<u32>,
+    sysfs: &SysfsFrequency,
+    read_sysfs: fn(&SysfsFrequency) -> Option<u32>,
+) -> (u32, FrequencySource) {
+    if let Some(mhz) = pmu_mhz.filter(|mhz| sysfs.is_plausible(*mhz)) {
+        return (mhz, FrequencySource::Pmu);
+    }
+    match (read_sysfs(sysfs), pmu_mhz) {
+        (Some(mhz), _) => (mhz, FrequencySource::Sysfs),
+        (None, Some(mhz)) => (mhz, FrequencySource::Pmu),
+        (None, None) => (0, FrequencySource::Unavailable),
+    }
+}
+
+/// Average RC6 residency over GTs, `None` if there are none
+fn mean_rc6(residencies: &[f64]) -> Option<Rc6Stats> {
+    if residencies.is_empty() {
+        return None;
+    }
+    let mean = residencies.iter().sum::<f64>() / residencies.len() as f64;
+    Some(Rc6Stats::new(mean))
+}
+
+/// Find the PMU belonging to a GPU
+fn find_pmu_for(gpu_info: &GpuInfo) -> Result<PmuInfo> {
+    pmu::find_pmu_for_gpu(discover_pmu()?, gpu_info)
+}
+
+/// Choose the primary instance of an engine class
+///
+/// An explicitly requested instance wins, even if the PMU did not list it;
+/// otherwise the lowest discovered instance is used, since instance 0 may be
+/// fused off.
+fn select_instance(available: &[u16], preferred: Option<u16>) -> Option<u16> {
+    preferred.or_else(|| available.iter().min().copied())
 }
 
 #[cfg(test)]
@@ -555,6 +1629,23 @@ mod tests {
         assert!(!util.is_busy());
     }
 
+    #[test]
+    fn test_select_instance() {
+        // Instance 0 fused off: the lowest available instance is used
+        assert_eq!(select_instance(&[1, 2], None), Some(1));
+        assert_eq!(select_instance(&[2, 0, 1], None), Some(0));
+        assert_eq!(select_instance(&[1, 2], Some(2)), Some(2));
+        assert_eq!(select_instance(&[], None), None);
+    }
+
+    #[test]
+    fn test_mean_rc6() {
+        assert!(mean_rc6(&[]).is_none());
+        assert_eq!(mean_rc6(&[80.0]).unwrap().residency_percent, 80.0);
+        // A busy GT lowers the card-wide residency
+        assert_eq!(mean_rc6(&[90.0, 10.0]).unwrap().residency_percent, 50.0);
+    }
+
     #[test]
     fn test_frequency_stats() {
         let freq = FrequencyStats::new(1000, 1200);
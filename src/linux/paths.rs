@@ -0,0 +1,71 @@
+//! Base paths of the DRM sysfs directory and device nodes
+//!
+//! The crate looks for cards under `/sys/class/drm` and for device nodes
+//! under `/dev/dri`. Containers that bind-mount them elsewhere can point the
+//! crate at the new locations with environment variables, e.g.:
+//!
+//! ```bash
+//! INTEL_GPU_STATS_DRI_DIR=/gpu INTEL_GPU_STATS_DRM_SYSFS_DIR=/host-sys/class/drm ./app
+//! ```
+//!
+//! The variables are read whenever a path is needed, so set them before
+//! listing or opening GPUs.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Environment variable overriding the DRM sysfs directory
+pub const DRM_SYSFS_DIR_ENV: &str = "INTEL_GPU_STATS_DRM_SYSFS_DIR";
+
+/// Environment variable overriding the DRM device node directory
+pub const DRI_DIR_ENV: &str = "INTEL_GPU_STATS_DRI_DIR";
+
+/// Default DRM sysfs directory
+pub const DEFAULT_DRM_SYSFS_DIR: &str = "/sys/class/drm";
+
+/// Default DRM device node directory
+pub const DEFAULT_DRI_DIR: &str = "/dev/dri";
+
+/// DRM sysfs directory holding the `cardN` and `renderDN` entries
+pub fn drm_sysfs_dir() -> PathBuf {
+    path_or_default(std::env::var_os(DRM_SYSFS_DIR_ENV), DEFAULT_DRM_SYSFS_DIR)
+}
+
+/// Directory holding the DRM device nodes (`cardN`, `renderDN`)
+pub fn dri_dir() -> PathBuf {
+    path_or_default(std::env::var_os(DRI_DIR_ENV), DEFAULT_DRI_DIR)
+}
+
+/// Sysfs directory of a card, e.g. `/sys/class/drm/card0`
+pub fn card_sysfs_dir(card_id: &str) -> PathBuf {
+    drm_sysfs_dir().join(card_id)
+}
+
+/// An override if it is set and non-empty, else the default
+fn path_or_default(value: Option<OsString>, default: &str) -> PathBuf {
+    value
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_or_default() {
+        assert_eq!(
+            path_or_default(None, DEFAULT_DRI_DIR),
+            PathBuf::from("/dev/dri")
+        );
+        assert_eq!(
+            path_or_default(Some("".into()), DEFAULT_DRI_DIR),
+            PathBuf::from("/dev/dri")
+        );
+        assert_eq!(
+            path_or_default(Some("/gpu".into()), DEFAULT_DRI_DIR),
+            PathBuf::from("/gpu")
+        );
+    }
+}
@@ -95,15 +95,81 @@ impl Default for PerfEventAttr {
     }
 }
 
+/// The config words of a PMU event
+///
+/// Most i915 and xe events only use `config`; some encode parameters in
+/// `config1` or `config2` as well (see the PMU's sysfs `events/` files).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventConfig {
+    /// Value for `perf_event_attr.config`
+    pub config: u64,
+    /// Value for `perf_event_attr.config1`
+    pub config1: u64,
+    /// Value for `perf_event_attr.config2`
+    pub config2: u64,
+}
+
+impl From<u64> for EventConfig {
+    fn from(config: u64) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+}
+
 impl PerfEventAttr {
     /// Create a new PerfEventAttr for an i915 PMU event
+    ///
+    /// Requests the enabled/running times so reads can be scaled if the
+    /// kernel multiplexes the counter.
     pub fn new_i915(pmu_type: u32, config: u64) -> Self {
         Self {
             type_: pmu_type,
             config,
+            read_format: read_format::TOTAL_TIME_ENABLED | read_format::TOTAL_TIME_RUNNING,
             ..Self::default()
         }
     }
+
+    /// Set all config words of the event
+    pub fn with_config(mut self, config: EventConfig) -> Self {
+        self.config = config.config;
+        self.config1 = config.config1;
+        self.config2 = config.config2;
+        self
+    }
+
+    /// Set the attribute flag bits (see [`flags`])
+    ///
+    /// The bits are passed to the kernel unchanged.
+    pub fn with_flags(mut self, flags: u64) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Set the read format (see [`read_format`])
+    ///
+    /// Only `TOTAL_TIME_ENABLED` and `TOTAL_TIME_RUNNING` are supported by
+    /// [`PerfEvent::read_value`]; other bits change the layout of the data
+    /// the kernel returns and are ignored.
+    pub fn with_read_format(mut self, format: u64) -> Self {
+        self.read_format =
+            format & (read_format::TOTAL_TIME_ENABLED | read_format::TOTAL_TIME_RUNNING);
+        self
+    }
+}
+
+/// PERF_FORMAT_* bits for `PerfEventAttr::read_format`
+pub mod read_format {
+    /// Append the time the event was enabled
+    pub const TOTAL_TIME_ENABLED: u64 = 1 << 0;
+    /// Append the time the event was actually counting
+    pub const TOTAL_TIME_RUNNING: u64 = 1 << 1;
+    /// Append the event id
+    pub const ID: u64 = 1 << 2;
+    /// Read all events of a group at once
+    pub const GROUP: u64 = 1 << 3;
 }
 
 /// Flag bits for perf_event_open
@@ -143,6 +209,8 @@ pub mod perf_flags {
 pub struct PerfEvent {
     file: File,
     event_name: String,
+    /// Read format the event was opened with
+    read_format: u64,
 }
 
 impl PerfEvent {
@@ -166,7 +234,7 @@ impl PerfEvent {
     ) -> Result<Self> {
         let event_name = event_name.into();
 
-        let fd = unsafe {
+        let fd = retry_on_eintr(|| unsafe {
             perf_event_open(
                 attr as *const PerfEventAttr,
                 pid,
@@ -174,7 +242,7 @@ impl PerfEvent {
                 group_fd,
                 flags as libc::c_ulong,
             )
-        };
+        });
 
         if fd < 0 {
             let err = io::Error::last_os_error();
@@ -197,21 +265,58 @@ impl PerfEvent {
 
         let file = unsafe { File::from_raw_fd(fd) };
 
-        Ok(Self { file, event_name })
+        Ok(Self {
+            file,
+            event_name,
+            read_format: attr.read_format,
+        })
     }
 
     /// Read the current counter value
     ///
-    /// Returns the cumulative counter value as a u64.
+    /// Returns the cumulative counter value as a u64, scaled up by
+    /// [`read_scaled`](Self::read_scaled)'s factor when the kernel
+    /// multiplexed the counter. The value is returned unchanged when it was
+    /// not, and scaled in integer math otherwise, so large counters keep
+    /// their precision.
     pub fn read_value(&mut self) -> Result<u64> {
-        let mut buf = [0u8; 8];
+        let (value, times) = self.read_raw()?;
+        Ok(match times {
+            Some((enabled, running)) => scale_value(value, enabled, running),
+            None => value,
+        })
+    }
+
+    /// Read the raw counter value and its multiplexing scale factor
+    ///
+    /// When more events are open than the hardware can count at once, the
+    /// kernel time-shares them and a counter only runs for part of the time
+    /// it is enabled. The factor is `time_enabled / time_running`; it is 1.0
+    /// if the counter ran the whole time, has not run yet, or the event was
+    /// opened without both time fields in its read format.
+    pub fn read_scaled(&mut self) -> Result<(u64, f64)> {
+        let (value, times) = self.read_raw()?;
+        let scale = times.map_or(1.0, |(enabled, running)| scale_factor(enabled, running));
+        Ok((value, scale))
+    }
+
+    /// Read the raw counter value and, if in the read format, the enabled
+    /// and running times
+    fn read_raw(&mut self) -> Result<(u64, Option<(u64, u64)>)> {
+        let times = read_format::TOTAL_TIME_ENABLED | read_format::TOTAL_TIME_RUNNING;
+        let words = 1 + (self.read_format & times).count_ones() as usize;
+
+        let mut buf = [0u8; 24];
         self.file
-            .read_exact(&mut buf)
+            .read_exact(&mut buf[..words * 8])
             .map_err(|e| Error::PerfEventOpen {
                 event: self.event_name.clone(),
                 source: e,
             })?;
-        Ok(u64::from_ne_bytes(buf))
+        let word = |i: usize| u64::from_ne_bytes(buf[i * 8..i * 8 + 8].try_into().unwrap());
+
+        let times = (self.read_format & times == times).then(|| (word(1), word(2)));
+        Ok((word(0), times))
     }
 
     /// Get the raw file descriptor
@@ -226,25 +331,21 @@ impl PerfEvent {
 
     /// Enable the event counter
     pub fn enable(&self) -> Result<()> {
-        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), PERF_EVENT_IOC_ENABLE, 0) };
-        if ret < 0 {
-            return Err(Error::PerfEventRead(io::Error::last_os_error()));
-        }
-        Ok(())
+        self.ioctl(PERF_EVENT_IOC_ENABLE)
     }
 
     /// Disable the event counter
     pub fn disable(&self) -> Result<()> {
-        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), PERF_EVENT_IOC_DISABLE, 0) };
-        if ret < 0 {
-            return Err(Error::PerfEventRead(io::Error::last_os_error()));
-        }
-        Ok(())
+        self.ioctl(PERF_EVENT_IOC_DISABLE)
     }
 
     /// Reset the event counter
     pub fn reset(&self) -> Result<()> {
-        let ret = unsafe { libc::ioctl(self.file.as_raw_fd(), PERF_EVENT_IOC_RESET, 0) };
+        self.ioctl(PERF_EVENT_IOC_RESET)
+    }
+
+    fn ioctl(&self, request: libc::c_ulong) -> Result<()> {
+        let ret = retry_on_eintr(|| unsafe { libc::ioctl(self.file.as_raw_fd(), request, 0) });
         if ret < 0 {
             return Err(Error::PerfEventRead(io::Error::last_os_error()));
         }
@@ -252,6 +353,43 @@ impl PerfEvent {
     }
 }
 
+/// Repeat a raw syscall while it fails with `EINTR`
+///
+/// Signal-heavy processes (e.g. ones reaping child processes on SIGCHLD)
+/// can interrupt syscalls at any time. `f` returns the raw result, negative
+/// with `errno` set on failure. Counter reads go through `read_exact`, which
+/// already retries, and [`SysfsFile`](super::sysfs::SysfsFile) retries its
+/// own reads.
+pub(crate) fn retry_on_eintr(mut f: impl FnMut() -> libc::c_int) -> libc::c_int {
+    loop {
+        let ret = f();
+        if ret >= 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return ret;
+        }
+    }
+}
+
+/// Multiplexing scale factor from the enabled and running times
+fn scale_factor(time_enabled: u64, time_running: u64) -> f64 {
+    if time_running == 0 || time_running >= time_enabled {
+        1.0
+    } else {
+        time_enabled as f64 / time_running as f64
+    }
+}
+
+/// Scale a counter value up by `time_enabled / time_running`, rounded
+///
+/// Unchanged if the counter ran the whole time or has not run yet.
+fn scale_value(value: u64, time_enabled: u64, time_running: u64) -> u64 {
+    if time_running == 0 || time_running >= time_enabled {
+        return value;
+    }
+    let scaled =
+        (value as u128 * time_enabled as u128 + time_running as u128 / 2) / time_running as u128;
+    u64::try_from(scaled).unwrap_or(u64::MAX)
+}
+
 /// ioctl commands for perf events
 const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
 const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
@@ -277,15 +415,101 @@ unsafe fn perf_event_open(
 /// Helper to open an i915 PMU event with default settings
 pub fn open_i915_event(
     pmu_type: u32,
-    config: u64,
+    config: impl Into<EventConfig>,
+    event_name: impl Into<String>,
+) -> Result<PerfEvent> {
+    open_i915_event_with_flags(pmu_type, config, 0, event_name)
+}
+
+/// Open an i915 PMU event with the given attribute flags (see [`flags`])
+///
+/// With [`flags::DISABLED`] the event is opened stopped, then reset and
+/// enabled, so its count starts from zero instead of whatever accumulated
+/// between opening and the first read.
+pub fn open_i915_event_with_flags(
+    pmu_type: u32,
+    config: impl Into<EventConfig>,
+    attr_flags: u64,
+    event_name: impl Into<String>,
+) -> Result<PerfEvent> {
+    if attr_flags & flags::DISABLED != 0 {
+        let event = open_i915_event_stopped(pmu_type, config, attr_flags, event_name)?;
+        event.reset()?;
+        event.enable()?;
+        return Ok(event);
+    }
+    open_raw_i915_event(pmu_type, config.into(), attr_flags, event_name)
+}
+
+/// Open an i915 PMU event that does not count until [`PerfEvent::enable`]
+///
+/// [`flags::DISABLED`] is added to `attr_flags`.
+pub fn open_i915_event_stopped(
+    pmu_type: u32,
+    config: impl Into<EventConfig>,
+    attr_flags: u64,
     event_name: impl Into<String>,
 ) -> Result<PerfEvent> {
-    let attr = PerfEventAttr::new_i915(pmu_type, config);
+    open_raw_i915_event(
+        pmu_type,
+        config.into(),
+        attr_flags | flags::DISABLED,
+        event_name,
+    )
+}
+
+fn open_raw_i915_event(
+    pmu_type: u32,
+    config: EventConfig,
+    attr_flags: u64,
+    event_name: impl Into<String>,
+) -> Result<PerfEvent> {
+    let attr = PerfEventAttr::new_i915(pmu_type, 0)
+        .with_config(config)
+        .with_flags(attr_flags);
     // pid=-1, cpu=0, group_fd=-1, flags=0
     // We use cpu=0 as i915 PMU events are system-wide
     PerfEvent::open(&attr, -1, 0, -1, 0, event_name)
 }
 
+/// Capability number of CAP_SYS_ADMIN
+const CAP_SYS_ADMIN: u32 = 21;
+
+/// Capability number of CAP_PERFMON (Linux 5.8+)
+const CAP_PERFMON: u32 = 38;
+
+/// Check whether the current process may open system-wide perf events
+///
+/// Reads the effective capability set (`CapEff`) from `/proc/self/status`
+/// and returns true if it holds CAP_PERFMON, or CAP_SYS_ADMIN which grants
+/// the same on kernels before 5.8. Root normally has both.
+///
+/// Without either, opening the GPU PMU only works if `perf_event_paranoid`
+/// allows it, so this is a hint for a clear startup message rather than a
+/// guarantee that `IntelGpu::detect` fails. Returns false if the status
+/// file cannot be read.
+pub fn has_perfmon_capability() -> bool {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| parse_cap_eff(&status))
+        .is_some_and(grants_perfmon)
+}
+
+/// Parse the effective capability mask from `/proc/<pid>/status` contents
+fn parse_cap_eff(status: &str) -> Option<u64> {
+    let mask = status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))?;
+    u64::from_str_radix(mask.trim(), 16).ok()
+}
+
+/// Check whether a capability mask allows system-wide perf events
+fn grants_perfmon(caps: u64) -> bool {
+    [CAP_PERFMON, CAP_SYS_ADMIN]
+        .iter()
+        .any(|&cap| caps & (1 << cap) != 0)
+}
+
 /// A group of related perf events that can be read together
 #[derive(Debug)]
 pub struct PerfEventGroup {
@@ -349,6 +573,27 @@ impl PerfEventGroup {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_retry_on_eintr() {
+        let mut calls = 0;
+        let ret = retry_on_eintr(|| {
+            calls += 1;
+            if calls < 3 {
+                unsafe { *libc::__errno_location() = libc::EINTR };
+                -1
+            } else {
+                7
+            }
+        });
+        assert_eq!((ret, calls), (7, 3));
+
+        let ret = retry_on_eintr(|| {
+            unsafe { *libc::__errno_location() = libc::EBADF };
+            -1
+        });
+        assert_eq!(ret, -1);
+    }
+
     #[test]
     fn test_perf_event_attr_size() {
         // The structure should be properly sized for the syscall
@@ -362,5 +607,52 @@ mod tests {
         let attr = PerfEventAttr::new_i915(10, 0x30000);
         assert_eq!(attr.type_, 10);
         assert_eq!(attr.config, 0x30000);
+        assert_eq!(
+            attr.read_format,
+            read_format::TOTAL_TIME_ENABLED | read_format::TOTAL_TIME_RUNNING
+        );
+        assert_eq!(attr.flags, 0);
+        let attr = attr.with_flags(flags::DISABLED | flags::EXCLUDE_HV);
+        assert_eq!(attr.flags, 0b100_0001);
+        assert_eq!(attr.with_read_format(read_format::GROUP).read_format, 0);
+
+        let attr = PerfEventAttr::new_i915(10, 0).with_config(EventConfig {
+            config: 0x02,
+            config1: 0x3,
+            config2: 0x1,
+        });
+        assert_eq!((attr.config, attr.config1, attr.config2), (0x02, 0x3, 0x1));
+        assert_eq!(EventConfig::from(7).config1, 0);
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        assert_eq!(scale_factor(1000, 1000), 1.0);
+        assert_eq!(scale_factor(1000, 250), 4.0);
+        // Not scheduled yet
+        assert_eq!(scale_factor(1000, 0), 1.0);
+    }
+
+    #[test]
+    fn test_scale_value() {
+        // Past 2^53 an f64 round trip would lose the low bits
+        let large = (1u64 << 60) + 1;
+        assert_eq!(scale_value(large, 1000, 1000), large);
+        assert_eq!(scale_value(large, 1000, 0), large);
+        assert_eq!(scale_value(1_000_001, 1000, 250), 4_000_004);
+        assert_eq!(scale_value(10, 3, 2), 15);
+        assert_eq!(scale_value(u64::MAX, 2, 1), u64::MAX);
+    }
+
+    #[test]
+    fn test_perfmon_capability() {
+        let status = "Name:\tcat\nCapInh:\t0000000000000000\nCapEff:\t000001ffffffffff\n";
+        assert_eq!(parse_cap_eff(status), Some(0x1ff_ffff_ffff));
+        assert_eq!(parse_cap_eff("Name:\tcat\n"), None);
+
+        assert!(grants_perfmon(0x1ff_ffff_ffff));
+        assert!(grants_perfmon(1 << CAP_PERFMON));
+        assert!(grants_perfmon(1 << CAP_SYS_ADMIN));
+        assert!(!grants_perfmon(0));
     }
 }
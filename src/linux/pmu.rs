@@ -2,20 +2,82 @@
 //!
 //! Both i915 and xe drivers expose GPU performance counters via the Linux perf subsystem.
 //! This module handles discovering the PMU and its available events for both drivers.
+//!
+//! Engine utilization comes from one of two kinds of events:
+//!
+//! - Busy time: i915 `<engine>-busy` and xe `<class>-group-busy` events count
+//!   nanoseconds the engine was busy, so utilization is busy time over wall
+//!   time.
+//! - Tick ratio: some xe kernels only expose the per-engine
+//!   `engine-active-ticks` and `engine-total-ticks` events. The engine, GT and
+//!   event are selected through the PMU `format` fields, and utilization is
+//!   active ticks over total ticks. See [`PmuInfo::uses_engine_ticks`].
+//!
+//! GT-scoped frequency and RC6 events are named differently across drivers
+//! and kernel versions; [`GtEvent::names`] lists the known aliases. xe's
+//! frequency events are not used, see [`GtEvent::ActualFrequency`].
 
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::error::{Error, Result};
-use crate::types::{EngineClass, GpuDriver, GpuInfo};
+use crate::types::{is_pci_address, EngineClass, GpuDriver, GpuInfo};
+
+use super::drm;
+use super::paths;
+use super::perf::EventConfig;
+use super::rc6;
 
 /// Base path for PMU event sources
-const PMU_BASE_PATH: &str = "/sys/bus/event_source/devices";
+pub(crate) const PMU_BASE_PATH: &str = "/sys/bus/event_source/devices";
 
 /// Intel vendor ID
 pub const INTEL_VENDOR_ID: u16 = 0x8086;
 
+/// xe event counting ticks during which an engine was active
+pub const XE_ACTIVE_TICKS: &str = "engine-active-ticks";
+
+/// xe event counting all engine ticks, the denominator for [`XE_ACTIVE_TICKS`]
+pub const XE_TOTAL_TICKS: &str = "engine-total-ticks";
+
+/// A GT-scoped event read once per GT
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GtEvent {
+    /// Actual GPU frequency, counted in MHz × ns by i915
+    ///
+    /// xe's `gt-actual-frequency` instead adds the instantaneous MHz on
+    /// every read, so the count depends on how often it is read rather than
+    /// on time. xe has no name here and its frequency comes from sysfs.
+    ActualFrequency,
+    /// Requested GPU frequency, counted in MHz × ns by i915
+    ///
+    /// Not used on xe, like [`GtEvent::ActualFrequency`].
+    RequestedFrequency,
+    /// Time spent in RC6 (C6 on xe), in ns
+    Rc6Residency,
+}
+
+impl GtEvent {
+    /// Known names of the event on a driver, in the order they are tried
+    ///
+    /// Names are without the `-gtN` suffix of multi-GT parts.
+    pub fn names(self, driver: GpuDriver) -> &'static [&'static str] {
+        match (self, driver) {
+            (GtEvent::ActualFrequency, GpuDriver::I915) => &["actual-frequency"],
+            (GtEvent::RequestedFrequency, GpuDriver::I915) => &["requested-frequency"],
+            // Sampled MHz rather than MHz × ns, read from sysfs instead
+            (GtEvent::ActualFrequency | GtEvent::RequestedFrequency, GpuDriver::Xe) => &[],
+            (GtEvent::Rc6Residency, GpuDriver::I915) => &["rc6-residency"],
+            (GtEvent::Rc6Residency, GpuDriver::Xe) => {
+                &["gt-c6-residency", "c6-residency", "rc6-residency"]
+            }
+            // Passed-through GPUs have no host PMU
+            (_, GpuDriver::Vfio | GpuDriver::WindowsDch) => &[],
+        }
+    }
+}
+
 /// Intel GPU PMU information
 #[derive(Debug, Clone)]
 pub struct PmuInfo {
@@ -24,16 +86,36 @@ pub struct PmuInfo {
     /// Path to the PMU sysfs directory
     pub path: PathBuf,
     /// Available events and their configs
-    pub events: HashMap<String, u64>,
+    pub events: HashMap<String, EventConfig>,
     /// Card ID this PMU belongs to (e.g., "card0")
     pub card_id: String,
+    /// PCI address from the PMU name (e.g. "0000:03:00.0")
+    ///
+    /// `None` for the bare "i915" PMU, which belongs to the integrated GPU.
+    pub pci_address: Option<String>,
     /// Driver type (i915 or xe)
     pub driver: GpuDriver,
+    /// Config bit ranges of the PMU format fields (e.g. "engine_class" -> 20..=27)
+    pub format: HashMap<String, (u32, u32)>,
 }
 
 impl PmuInfo {
-    /// Get the config value for a named event
-    pub fn event_config(&self, name: &str) -> Option<u64> {
+    /// Check whether this PMU belongs to a GPU
+    ///
+    /// PMUs are matched on the PCI address in their name. The bare "i915"
+    /// PMU has none and belongs to the integrated GPU. Only when the GPU's
+    /// own PCI address is unknown is the card ID resolved from the PMU name
+    /// compared instead.
+    pub fn matches_gpu(&self, gpu: &GpuInfo) -> bool {
+        match (self.pci_address.as_deref(), gpu.pci_address()) {
+            (Some(pmu_address), Some(gpu_address)) => pmu_address == gpu_address,
+            (None, Some(_)) => self.driver == GpuDriver::I915 && gpu.is_integrated(),
+            (_, None) => self.card_id == gpu.id,
+        }
+    }
+
+    /// Get the config words of a named event
+    pub fn event_config(&self, name: &str) -> Option<EventConfig> {
         self.events.get(name).copied()
     }
 
@@ -44,10 +126,110 @@ impl PmuInfo {
         ((class as u64) << 16) | ((instance as u64) << 8) | (sample_type as u64)
     }
 
+    /// Nanoseconds per count of a GT's event, from the event's `.unit` file
+    ///
+    /// Like [`gt_event_config`](Self::gt_event_config), falls back to the
+    /// event without a GT suffix for GT 0. `None` if there is no unit file
+    /// or the unit is not a time.
+    pub fn gt_event_unit_ns(&self, name: &str, gt: u16) -> Option<f64> {
+        let unit = |event: &str| {
+            fs::read_to_string(self.path.join("events").join(format!("{}.unit", event))).ok()
+        };
+        unit(&format!("{}-gt{}", name, gt))
+            .or_else(|| (gt == 0).then(|| unit(name)).flatten())
+            .and_then(|unit| rc6::unit_ns(&unit))
+    }
+
     /// Check if a specific event is available
     pub fn has_event(&self, name: &str) -> bool {
         self.events.contains_key(name)
     }
+
+    /// Check if engine utilization must come from the xe tick events
+    ///
+    /// True for xe PMUs without `*-group-busy` events that expose the
+    /// [`XE_ACTIVE_TICKS`]/[`XE_TOTAL_TICKS`] pair instead.
+    pub fn uses_engine_ticks(&self) -> bool {
+        self.driver == GpuDriver::Xe
+            && !self
+                .events
+                .keys()
+                .any(|event| event.contains("-group-busy"))
+            && self.has_event(XE_ACTIVE_TICKS)
+            && self.has_event(XE_TOTAL_TICKS)
+    }
+
+    /// Build the config of an xe per-engine event
+    ///
+    /// Places the event id, engine class, engine instance and GT into the bit
+    /// ranges given by the PMU format. Returns `None` if the event or a
+    /// format field is missing, or a value does not fit its field.
+    pub fn xe_engine_config(
+        &self,
+        event: &str,
+        class: EngineClass,
+        instance: u16,
+        gt: u16,
+    ) -> Option<EventConfig> {
+        let mut config = self.event_config(event)?;
+        for (field, value) in [
+            ("engine_class", class as u64),
+            ("engine_instance", instance as u64),
+            ("gt", gt as u64),
+        ] {
+            let &(low, high) = self.format.get(field)?;
+            let width = high.checked_sub(low)? + 1;
+            if width < 64 && value >> width != 0 {
+                return None;
+            }
+            config.config |= value << low;
+        }
+        Some(config)
+    }
+
+    /// Get the config value for a GT-scoped event on a specific GT
+    ///
+    /// On multi-GT parts i915 suffixes every GT-scoped event with `-gtN`
+    /// (e.g. `actual-frequency-gt1`). Single-GT parts only expose the bare
+    /// name, which refers to gt0.
+    pub fn gt_event_config(&self, name: &str, gt: u16) -> Option<EventConfig> {
+        self.event_config(&format!("{}-gt{}", name, gt))
+            .or_else(|| (gt == 0).then(|| self.event_config(name)).flatten())
+    }
+
+    /// Find the name under which this PMU exposes a GT-scoped event
+    ///
+    /// Tries the driver's [`GtEvent::names`] in order, with or without a
+    /// `-gtN` suffix. Returns `None` if the PMU has none of them.
+    pub fn gt_event_name(&self, event: GtEvent) -> Option<&'static str> {
+        event
+            .names(self.driver)
+            .iter()
+            .copied()
+            .find(|name| !self.gt_ids(name).is_empty())
+    }
+
+    /// List the GT ids for which a GT-scoped event is available
+    pub fn gt_ids(&self, name: &str) -> Vec<u16> {
+        let mut gts: Vec<u16> = self
+            .events
+            .keys()
+            .filter_map(|event| parse_gt_suffix(event, name))
+            .collect();
+
+        if gts.is_empty() && self.has_event(name) {
+            gts.push(0);
+        }
+
+        gts.sort();
+        gts.dedup();
+        gts
+    }
+}
+
+/// Parse the GT id out of a `<name>-gtN` event name
+fn parse_gt_suffix(event: &str, name: &str) -> Option<u16> {
+    event.strip_prefix(name)?.strip_prefix("-gt")?.parse().ok()
 }
 
 /// Discover Intel GPU PMU devices (both i915 and xe)
@@ -56,21 +238,25 @@ pub fn discover_pmu() -> Result<Vec<PmuInfo>> {
 
     let pmu_base = Path::new(PMU_BASE_PATH);
     if !pmu_base.exists() {
-        return Err(Error::PmuNotAvailable);
+        return Err(Error::PmuNotAvailable {
+            reason: format!("{} does not exist", PMU_BASE_PATH),
+        });
     }
 
     let entries = fs::read_dir(pmu_base).map_err(|e| {
         if e.kind() == std::io::ErrorKind::PermissionDenied {
             Error::permission_denied(&e)
         } else {
-            Error::PmuNotAvailable
+            Error::PmuNotAvailable {
+                reason: format!("cannot read {}: {}", PMU_BASE_PATH, e),
+            }
         }
     })?;
 
     for entry in entries.flatten() {
         let name = entry.file_name().to_string_lossy().to_string();
 
-        // Check for i915 PMU: "i915" or "i915-0000:00:02.0"
+        // Check for i915 PMU: "i915", "i915_0000_03_00.0" or "i915-0000:03:00.0"
         if name.starts_with("i915") {
             if let Ok(pmu) = read_pmu_info(&entry.path(), &name, GpuDriver::I915) {
                 pmus.push(pmu);
@@ -85,12 +271,41 @@ pub fn discover_pmu() -> Result<Vec<PmuInfo>> {
     }
 
     if pmus.is_empty() {
-        return Err(Error::PmuNotAvailable);
+        return Err(Error::PmuNotAvailable {
+            reason: "no i915 or xe PMU found - ensure the i915 or xe driver is loaded".into(),
+        });
     }
 
     Ok(pmus)
 }
 
+/// Pick the PMU belonging to a GPU out of the discovered ones
+///
+/// See [`PmuInfo::matches_gpu`]. With no match this fails rather than
+/// guessing, since an unrelated PMU would silently report another GPU's load.
+pub fn find_pmu_for_gpu(pmus: Vec<PmuInfo>, gpu: &GpuInfo) -> Result<PmuInfo> {
+    let names: Vec<String> = pmus
+        .iter()
+        .map(|pmu| {
+            pmu.path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect();
+    pmus.into_iter()
+        .find(|pmu| pmu.matches_gpu(gpu))
+        .ok_or_else(|| Error::PmuNotAvailable {
+            reason: format!(
+                "no PMU matches {} ({}); found {}",
+                gpu.id,
+                gpu.pci_address().unwrap_or("unknown PCI address"),
+                names.join(", ")
+            ),
+        })
+}
+
 /// Read PMU information from sysfs
 fn read_pmu_info(path: &Path, name: &str, driver: GpuDriver) -> Result<PmuInfo> {
     // Read PMU type ID
@@ -103,57 +318,91 @@ fn read_pmu_info(path: &Path, name: &str, driver: GpuDriver) -> Result<PmuInfo>
         .map_err(|e| Error::sysfs_parse(&type_path, format!("invalid type id: {}", e)))?;
 
     // Parse card ID from PMU name
-    let card_id = parse_card_id(name, driver);
+    let pci_address = parse_pmu_pci_address(name, driver);
+    let card_id = parse_card_id(name, driver, |address| find_card_by_pci(address).ok());
 
     // Read available events
     let events = read_pmu_events(path)?;
+    let format = read_pmu_format(path);
 
     Ok(PmuInfo {
         type_id,
         path: path.to_path_buf(),
         events,
         card_id,
+        pci_address,
         driver,
+        format,
     })
 }
 
-/// Parse card ID from PMU name
-///
-/// PMU names can be:
-/// - "i915" (single GPU, i915 driver)
-/// - "i915-0000:00:02.0" (multi-GPU with PCI address, i915 driver)
-/// - "xe_0000_00_02.0" (xe driver, uses underscores in PCI address)
-fn parse_card_id(name: &str, driver: GpuDriver) -> String {
-    match driver {
-        GpuDriver::I915 => {
-            if name == "i915" {
-                return "card0".to_string();
-            }
-            // Try to find the card by PCI address: "i915-0000:00:02.0"
-            if let Some(pci_addr) = name.strip_prefix("i915-") {
-                if let Ok(card) = find_card_by_pci(pci_addr) {
-                    return card;
-                }
-            }
-        }
-        GpuDriver::Xe => {
-            // xe PMU names are like "xe_0000_00_02.0" (underscores instead of colons)
-            if let Some(pci_part) = name.strip_prefix("xe_") {
-                // Convert underscores to colons: "0000_00_02.0" -> "0000:00:02.0"
-                let pci_addr = pci_part.replacen('_', ":", 2);
-                if let Ok(card) = find_card_by_pci(&pci_addr) {
-                    return card;
-                }
-            }
+/// Read the PMU format fields (e.g. `format/engine_class` = "config:20-27")
+pub(crate) fn read_pmu_format(pmu_path: &Path) -> HashMap<String, (u32, u32)> {
+    let Ok(entries) = fs::read_dir(pmu_path.join("format")) else {
+        return HashMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let spec = fs::read_to_string(entry.path()).ok()?;
+            let range = parse_format_field(&spec)?;
+            Some((entry.file_name().to_string_lossy().to_string(), range))
+        })
+        .collect()
+}
+
+/// Parse a format field spec like "config:20-27" or "config:5" into a bit range
+fn parse_format_field(spec: &str) -> Option<(u32, u32)> {
+    let bits = spec.trim().strip_prefix("config:")?;
+    match bits.split_once('-') {
+        Some((low, high)) => Some((low.parse().ok()?, high.parse().ok()?)),
+        None => {
+            let bit = bits.parse().ok()?;
+            Some((bit, bit))
         }
     }
+}
+
+/// Parse the PCI address out of a PMU name
+///
+/// PMU names can be:
+/// - "i915" (integrated GPU, i915 driver): no address
+/// - "i915_0000_03_00.0" (other i915 GPUs, underscores instead of colons)
+/// - "i915-0000:03:00.0" (older kernels)
+/// - "xe_0000_00_02.0" (xe driver, underscores instead of colons)
+fn parse_pmu_pci_address(name: &str, driver: GpuDriver) -> Option<String> {
+    let suffix = match driver {
+        GpuDriver::I915 => name
+            .strip_prefix("i915_")
+            .or_else(|| name.strip_prefix("i915-"))?,
+        GpuDriver::Xe => name.strip_prefix("xe_")?,
+        // Never produced from a PMU name
+        GpuDriver::Vfio | GpuDriver::WindowsDch => return None,
+    };
+    // "0000_00_02.0" -> "0000:00:02.0"
+    let address = suffix.replacen('_', ":", 2);
+    is_pci_address(&address).then_some(address)
+}
 
-    "card0".to_string()
+/// Parse card ID from PMU name
+///
+/// The PCI address in the name (see [`parse_pmu_pci_address`]) is resolved
+/// to a card with `find_card`. The bare "i915" PMU and unresolvable names
+/// map to "card0".
+fn parse_card_id(
+    name: &str,
+    driver: GpuDriver,
+    find_card: impl Fn(&str) -> Option<String>,
+) -> String {
+    parse_pmu_pci_address(name, driver)
+        .and_then(|address| find_card(&address))
+        .unwrap_or_else(|| "card0".to_string())
 }
 
 /// Find card ID by PCI address
 fn find_card_by_pci(pci_addr: &str) -> Result<String> {
-    let drm_path = Path::new("/sys/class/drm");
+    let drm_path = paths::drm_sysfs_dir();
     if !drm_path.exists() {
         return Err(Error::NoGpuFound);
     }
@@ -181,7 +430,7 @@ fn find_card_by_pci(pci_addr: &str) -> Result<String> {
 }
 
 /// Read PMU events from sysfs
-fn read_pmu_events(pmu_path: &Path) -> Result<HashMap<String, u64>> {
+fn read_pmu_events(pmu_path: &Path) -> Result<HashMap<String, EventConfig>> {
     let events_path = pmu_path.join("events");
     let mut events = HashMap::new();
 
@@ -213,23 +462,40 @@ fn read_pmu_events(pmu_path: &Path) -> Result<HashMap<String, u64>> {
 /// Format examples:
 /// - "config=0x1"
 /// - "config=1"
-fn parse_event_config(config_str: &str) -> Option<u64> {
+/// - "event=0x02" (xe, the event id occupies the low config bits)
+/// - "config=0x10,config1=0x3" (parameters in the extension words)
+///
+/// Unknown terms are ignored; an invalid value for a known term rejects the
+/// whole event rather than opening it with a partial config.
+fn parse_event_config(config_str: &str) -> Option<EventConfig> {
     let config_str = config_str.trim();
+    let mut config = EventConfig::default();
+    let mut found = false;
 
-    // Look for "config=" or "config1=" etc.
     for part in config_str.split(',') {
-        let part = part.trim();
-        if let Some(value) = part.strip_prefix("config=") {
-            return parse_hex_or_dec(value);
-        }
+        let Some((term, value)) = part.trim().split_once('=') else {
+            continue;
+        };
+        let word = match term.trim() {
+            "config" | "event" => &mut config.config,
+            "config1" => &mut config.config1,
+            "config2" => &mut config.config2,
+            _ => continue,
+        };
+        *word = parse_hex_or_dec(value)?;
+        found = true;
     }
 
-    // If no "config=" found, try parsing the whole string
-    parse_hex_or_dec(config_str)
+    if found {
+        return Some(config);
+    }
+
+    // No known term, try parsing the whole string
+    parse_hex_or_dec(config_str).map(EventConfig::from)
 }
 
 /// Parse a hex (0x...) or decimal number
-fn parse_hex_or_dec(s: &str) -> Option<u64> {
+pub(crate) fn parse_hex_or_dec(s: &str) -> Option<u64> {
     let s = s.trim();
     if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
         u64::from_str_radix(hex, 16).ok()
@@ -239,21 +505,29 @@ fn parse_hex_or_dec(s: &str) -> Option<u64> {
 }
 
 /// Discover Intel GPUs in the system
+///
+/// Cards are enumerated from `/sys/class/drm`. If that directory is missing
+/// or unreadable, the render nodes in `/dev/dri` are used instead (see
+/// [`discover_render_nodes`]). Both can be overridden, see [`paths`].
 pub fn discover_gpus() -> Result<Vec<GpuInfo>> {
     let mut gpus = Vec::new();
-    let drm_path = Path::new("/sys/class/drm");
-
-    if !drm_path.exists() {
-        return Err(Error::NoGpuFound);
-    }
-
-    let entries = fs::read_dir(drm_path).map_err(|e| {
-        if e.kind() == std::io::ErrorKind::PermissionDenied {
-            Error::permission_denied(&e)
-        } else {
-            Error::NoGpuFound
+    let drm_path = paths::drm_sysfs_dir();
+
+    let entries = match fs::read_dir(&drm_path) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let mut gpus = discover_render_nodes(&paths::dri_dir());
+            if !gpus.is_empty() {
+                set_media_engines(&mut gpus);
+                return Ok(gpus);
+            }
+            return Err(if e.kind() == std::io::ErrorKind::PermissionDenied {
+                Error::permission_denied(&e)
+            } else {
+                Error::NoGpuFound
+            });
         }
-    })?;
+    };
 
     for entry in entries.flatten() {
         let name = entry.file_name().to_string_lossy().to_string();
@@ -274,9 +548,98 @@ pub fn discover_gpus() -> Result<Vec<GpuInfo>> {
         return Err(Error::NoGpuFound);
     }
 
+    set_media_engines(&mut gpus);
     Ok(gpus)
 }
 
+/// Fill in `GpuInfo::media_engines` from the engine instances of each GPU's PMU
+///
+/// GPUs whose PMU cannot be found, or exposes no media engines, are left
+/// unset so `media_engine_count` falls back to the device ID table.
+fn set_media_engines(gpus: &mut [GpuInfo]) {
+    let pmus = discover_pmu().unwrap_or_default();
+    for gpu in gpus {
+        gpu.media_engines = pmus
+            .iter()
+            .find(|pmu| pmu.card_id == gpu.id)
+            .map(|pmu| media_engine_count(&get_engine_instances(pmu)))
+            .filter(|&count| count > 0);
+    }
+}
+
+/// Discover Intel GPUs from their render nodes, without `/sys/class/drm`
+///
+/// For each `renderD<N>` node, the sysfs device is looked up through
+/// `/sys/dev/char` if that is mounted. Otherwise the driver is queried from
+/// the node itself and a minimal [`GpuInfo`] is built: vendor and device IDs
+/// are unknown then, so only nodes driven by i915 or xe are kept. Cards are
+/// numbered like the kernel does, `renderD128` being `card0`.
+pub fn discover_render_nodes(dev_dri: &Path) -> Vec<GpuInfo> {
+    let Ok(entries) = fs::read_dir(dev_dri) else {
+        return Vec::new();
+    };
+
+    let mut nodes: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| drm::parse_render_node(&entry.file_name().to_string_lossy()))
+        .collect();
+    nodes.sort_unstable();
+
+    nodes
+        .into_iter()
+        .filter_map(|minor| {
+            let card_id = format!("card{}", minor - drm::RENDER_MINOR_BASE);
+            let sys_path = PathBuf::from(format!("/sys/dev/char/{}:{}", drm::DRM_MAJOR, minor));
+            if sys_path.join("device").exists() {
+                return read_gpu_info(&sys_path, &card_id)
+                    .ok()
+                    .filter(GpuInfo::is_intel);
+            }
+
+            let render_node = dev_dri.join(format!("renderD{}", minor));
+            let driver = drm::driver_name(&render_node).and_then(|name| driver_from_name(&name))?;
+            let card_node = dev_dri.join(&card_id);
+            minimal_gpu_info(
+                card_id,
+                render_node.to_string_lossy().into_owned(),
+                card_node
+                    .exists()
+                    .then(|| card_node.to_string_lossy().into_owned()),
+                driver,
+            )
+        })
+        .collect()
+}
+
+/// Build a [`GpuInfo`] for a render node whose sysfs device is unavailable
+///
+/// Returns `None` for drivers that do not imply an Intel GPU.
+fn minimal_gpu_info(
+    card_id: String,
+    render_node: String,
+    card_node: Option<String>,
+    driver: GpuDriver,
+) -> Option<GpuInfo> {
+    // vfio-pci binds any vendor's devices, and never exposes DRM nodes anyway
+    if driver == GpuDriver::Vfio {
+        return None;
+    }
+    Some(GpuInfo {
+        id: card_id,
+        pci_path: String::new(),
+        device_name: None,
+        vendor_id: INTEL_VENDOR_ID,
+        device_id: 0,
+        render_node: Some(render_node),
+        card_node,
+        driver: Some(driver),
+        driver_name: Some(driver.name().to_string()),
+        pcie_link_speed: None,
+        pcie_link_width: None,
+        media_engines: None,
+    })
+}
+
 /// Read GPU information from sysfs
 fn read_gpu_info(card_path: &Path, card_id: &str) -> Result<GpuInfo> {
     let device_path = card_path.join("device");
@@ -304,16 +667,17 @@ fn read_gpu_info(card_path: &Path, card_id: &str) -> Result<GpuInfo> {
         .unwrap_or_default();
 
     // Detect driver in use
-    let driver = detect_gpu_driver(&device_path);
+    let driver_name = read_driver_name(&device_path);
+    let driver = driver_name.as_deref().and_then(driver_from_name);
 
     // Find render node
     let render_node = find_render_node(card_id);
 
     // Find card node
     let card_num = card_id.strip_prefix("card").unwrap_or("0");
-    let card_node = format!("/dev/dri/card{}", card_num);
-    let card_node = if Path::new(&card_node).exists() {
-        Some(card_node)
+    let card_node = paths::dri_dir().join(format!("card{}", card_num));
+    let card_node = if card_node.exists() {
+        Some(card_node.to_string_lossy().into_owned())
     } else {
         None
     };
@@ -321,6 +685,15 @@ fn read_gpu_info(card_path: &Path, card_id: &str) -> Result<GpuInfo> {
     // Try to get device name
     let device_name = get_device_name(device_id);
 
+    // PCIe link state (mostly meaningful for discrete GPUs)
+    let pcie_link_speed = fs::read_to_string(device_path.join("current_link_speed"))
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty());
+    let pcie_link_width = fs::read_to_string(device_path.join("current_link_width"))
+        .ok()
+        .and_then(|s| s.trim().parse().ok());
+
     Ok(GpuInfo {
         id: card_id.to_string(),
         pci_path,
@@ -330,26 +703,31 @@ fn read_gpu_info(card_path: &Path, card_id: &str) -> Result<GpuInfo> {
         render_node,
         card_node,
         driver,
+        driver_name,
+        pcie_link_speed,
+        pcie_link_width,
+        media_engines: None,
     })
 }
 
-/// Detect which kernel driver is in use for a GPU
-fn detect_gpu_driver(device_path: &Path) -> Option<GpuDriver> {
+/// Read the name of the kernel driver a GPU is bound to
+///
+/// Returns any driver name, including ones [`driver_from_name`] does not
+/// recognize, or `None` if the device is not bound to a driver.
+fn read_driver_name(device_path: &Path) -> Option<String> {
     // The driver symlink points to the kernel driver module
-    let driver_link = device_path.join("driver");
-    if let Ok(target) = fs::read_link(&driver_link) {
-        let driver_name = target
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_default();
-
-        return match driver_name.as_str() {
-            "i915" => Some(GpuDriver::I915),
-            "xe" => Some(GpuDriver::Xe),
-            _ => None,
-        };
+    let target = fs::read_link(device_path.join("driver")).ok()?;
+    target.file_name().map(|n| n.to_string_lossy().to_string())
+}
+
+/// Map a kernel driver name to the driver type
+fn driver_from_name(name: &str) -> Option<GpuDriver> {
+    match name {
+        "i915" => Some(GpuDriver::I915),
+        "xe" => Some(GpuDriver::Xe),
+        "vfio-pci" => Some(GpuDriver::Vfio),
+        _ => None,
     }
-    None
 }
 
 /// Find the render node for a card
@@ -358,10 +736,10 @@ fn find_render_node(card_id: &str) -> Option<String> {
 
     // Render nodes start at 128
     let render_num = 128 + card_num;
-    let render_path = format!("/dev/dri/renderD{}", render_num);
+    let render_path = paths::dri_dir().join(format!("renderD{}", render_num));
 
-    if Path::new(&render_path).exists() {
-        Some(render_path)
+    if render_path.exists() {
+        Some(render_path.to_string_lossy().into_owned())
     } else {
         None
     }
@@ -399,6 +777,15 @@ fn get_device_name(device_id: u16) -> Option<String> {
     Some(name.to_string())
 }
 
+/// Number of media engines (Video + VideoEnhance instances) among engine instances
+pub fn media_engine_count(engines: &HashMap<EngineClass, Vec<u16>>) -> u32 {
+    [EngineClass::Video, EngineClass::VideoEnhance]
+        .iter()
+        .filter_map(|class| engines.get(class))
+        .map(|instances| instances.len() as u32)
+        .sum()
+}
+
 /// Get available engine instances for a GPU
 pub fn get_engine_instances(pmu: &PmuInfo) -> HashMap<EngineClass, Vec<u16>> {
     let mut engines: HashMap<EngineClass, Vec<u16>> = HashMap::new();
@@ -469,6 +856,16 @@ pub fn get_engine_instances(pmu: &PmuInfo) -> HashMap<EngineClass, Vec<u16>> {
                     }
                 }
             }
+            // Passed-through GPUs have no PMU events on the host
+            GpuDriver::Vfio | GpuDriver::WindowsDch => {}
+        }
+    }
+
+    // Kernels without group-busy events: the tick events are per engine, so
+    // take the engine classes from sysfs
+    if pmu.uses_engine_ticks() {
+        for class in xe_engine_gts(&pmu.card_id).into_keys() {
+            engines.entry(class).or_default().push(0);
         }
     }
 
@@ -489,6 +886,55 @@ pub fn get_engine_instances(pmu: &PmuInfo) -> HashMap<EngineClass, Vec<u16>> {
     engines
 }
 
+/// Find the GT hosting each engine class of an xe GPU
+///
+/// xe lists engine classes per GT under
+/// `device/tile*/gt*/engines/{rcs,bcs,vcs,vecs,ccs}`. Media engines live on
+/// a separate GT on some platforms, so the GT is needed to address them.
+pub fn xe_engine_gts(card_id: &str) -> HashMap<EngineClass, u16> {
+    xe_engine_gts_in(&paths::card_sysfs_dir(card_id))
+}
+
+fn xe_engine_gts_in(card_path: &Path) -> HashMap<EngineClass, u16> {
+    let mut gts = HashMap::new();
+    let Ok(tiles) = fs::read_dir(card_path.join("device")) else {
+        return gts;
+    };
+
+    let mut gt_dirs: Vec<(u16, PathBuf)> = tiles
+        .flatten()
+        .filter(|tile| tile.file_name().to_string_lossy().starts_with("tile"))
+        .filter_map(|tile| fs::read_dir(tile.path()).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|gt| {
+            let id = gt
+                .file_name()
+                .to_string_lossy()
+                .strip_prefix("gt")?
+                .parse()
+                .ok()?;
+            Some((id, gt.path()))
+        })
+        .collect();
+    gt_dirs.sort();
+
+    for (gt, gt_path) in gt_dirs {
+        for (dir, class) in [
+            ("rcs", EngineClass::Render),
+            ("bcs", EngineClass::Copy),
+            ("vcs", EngineClass::Video),
+            ("vecs", EngineClass::VideoEnhance),
+            ("ccs", EngineClass::Compute),
+        ] {
+            if gt_path.join("engines").join(dir).exists() {
+                gts.entry(class).or_insert(gt);
+            }
+        }
+    }
+
+    gts
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -504,9 +950,97 @@ mod tests {
 
     #[test]
     fn test_parse_event_config() {
-        assert_eq!(parse_event_config("config=0x1"), Some(1));
-        assert_eq!(parse_event_config("config=1"), Some(1));
-        assert_eq!(parse_event_config("config=0x30000"), Some(0x30000));
+        let config = |config| Some(EventConfig::from(config));
+        assert_eq!(parse_event_config("config=0x1"), config(1));
+        assert_eq!(parse_event_config("config=1"), config(1));
+        assert_eq!(parse_event_config("config=0x30000\n"), config(0x30000));
+        assert_eq!(parse_event_config("event=0x02"), config(2));
+        assert_eq!(parse_event_config("0x5"), config(5));
+
+        assert_eq!(
+            parse_event_config("config=0x10,config1=0x3"),
+            Some(EventConfig {
+                config: 0x10,
+                config1: 0x3,
+                config2: 0,
+            })
+        );
+        assert_eq!(
+            parse_event_config("event=0x02, umask=0x1, config2=0x100"),
+            Some(EventConfig {
+                config: 0x02,
+                config1: 0,
+                config2: 0x100,
+            })
+        );
+        assert_eq!(parse_event_config("config=0x1,config1=bogus"), None);
+    }
+
+    #[test]
+    fn test_xe_engine_ticks() {
+        let events = [
+            (XE_ACTIVE_TICKS.to_string(), EventConfig::from(0x02)),
+            (XE_TOTAL_TICKS.to_string(), EventConfig::from(0x03)),
+        ];
+        let format = [
+            ("gt", "config:60-63"),
+            ("engine_class", "config:20-27"),
+            ("engine_instance", "config:12-19"),
+            ("event", "config:0-11"),
+        ];
+        let pmu = PmuInfo {
+            type_id: 0,
+            path: PathBuf::new(),
+            events: events.into_iter().collect(),
+            card_id: "card0".into(),
+            pci_address: None,
+            driver: GpuDriver::Xe,
+            format: format
+                .iter()
+                .map(|(name, spec)| (name.to_string(), parse_format_field(spec).unwrap()))
+                .collect(),
+        };
+
+        assert!(pmu.uses_engine_ticks());
+        assert_eq!(
+            pmu.xe_engine_config(XE_ACTIVE_TICKS, EngineClass::VideoEnhance, 1, 1),
+            Some(EventConfig::from((1 << 60) | (3 << 20) | (1 << 12) | 0x02))
+        );
+        assert_eq!(
+            pmu.xe_engine_config(XE_TOTAL_TICKS, EngineClass::Render, 0, 16),
+            None
+        );
+        assert_eq!(parse_format_field("config:5"), Some((5, 5)));
+
+        // group-busy events take precedence
+        let mut group = pmu.clone();
+        group
+            .events
+            .insert("render-group-busy-gt0".into(), EventConfig::from(0x10));
+        assert!(!group.uses_engine_ticks());
+    }
+
+    #[test]
+    fn test_xe_engine_gts() {
+        let card = std::env::temp_dir().join(format!("igs-xe-engines-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&card);
+        for dir in [
+            "device/tile0/gt0/engines/rcs",
+            "device/tile0/gt0/engines/bcs",
+            "device/tile0/gt0/engines/ccs",
+            "device/tile0/gt1/engines/vcs",
+            "device/tile0/gt1/engines/vecs",
+        ] {
+            fs::create_dir_all(card.join(dir)).unwrap();
+        }
+
+        let gts = xe_engine_gts_in(&card);
+        assert_eq!(gts.len(), 5);
+        assert_eq!(gts[&EngineClass::Render], 0);
+        assert_eq!(gts[&EngineClass::Video], 1);
+        assert_eq!(gts[&EngineClass::VideoEnhance], 1);
+
+        fs::remove_dir_all(&card).unwrap();
     }
 
     #[test]
@@ -526,4 +1060,185 @@ mod tests {
         // Video wait: class 2, instance 0, sample 1
         assert_eq!(PmuInfo::engine_config(EngineClass::Video, 0, 1), 0x20001);
     }
+
+    #[test]
+    fn test_gt_events() {
+        let pmu = |names: &[&str]| PmuInfo {
+            type_id: 0,
+            path: PathBuf::new(),
+            events: names
+                .iter()
+                .enumerate()
+                .map(|(config, name)| (name.to_string(), EventConfig::from(config as u64)))
+                .collect(),
+            card_id: "card0".into(),
+            pci_address: None,
+            driver: GpuDriver::I915,
+            format: HashMap::new(),
+        };
+
+        let single = pmu(&["actual-frequency", "rc6-residency"]);
+        assert_eq!(single.gt_ids("actual-frequency"), vec![0]);
+        assert_eq!(
+            single.gt_event_config("actual-frequency", 0),
+            Some(EventConfig::from(0))
+        );
+        assert_eq!(single.gt_event_config("actual-frequency", 1), None);
+
+        let multi = pmu(&[
+            "actual-frequency-gt0",
+            "actual-frequency-gt1",
+            "requested-frequency-gt0",
+        ]);
+        assert_eq!(multi.gt_ids("actual-frequency"), vec![0, 1]);
+        assert_eq!(multi.gt_ids("requested-frequency"), vec![0]);
+        assert_eq!(
+            multi.gt_event_config("actual-frequency", 1),
+            Some(EventConfig::from(1))
+        );
+        assert!(multi.gt_ids("rc6-residency").is_empty());
+        assert_eq!(
+            multi.gt_event_name(GtEvent::ActualFrequency),
+            Some("actual-frequency")
+        );
+        assert_eq!(multi.gt_event_name(GtEvent::Rc6Residency), None);
+
+        let xe = PmuInfo {
+            driver: GpuDriver::Xe,
+            ..pmu(&[
+                "gt-actual-frequency-gt0",
+                "gt-requested-frequency-gt0",
+                "gt-c6-residency",
+            ])
+        };
+        // xe's frequency events sum sampled MHz and would decode to ~0 MHz
+        // as MHz × ns, so sysfs is used
+        assert_eq!(xe.gt_event_name(GtEvent::ActualFrequency), None);
+        assert_eq!(xe.gt_event_name(GtEvent::RequestedFrequency), None);
+        assert_eq!(
+            xe.gt_event_name(GtEvent::Rc6Residency),
+            Some("gt-c6-residency")
+        );
+    }
+
+    #[test]
+    fn test_minimal_gpu_info() {
+        assert_eq!(driver_from_name("xe"), Some(GpuDriver::Xe));
+        assert_eq!(driver_from_name("amdgpu"), None);
+
+        let gpu = minimal_gpu_info(
+            "card1".into(),
+            "/dev/dri/renderD129".into(),
+            None,
+            GpuDriver::I915,
+        )
+        .unwrap();
+        assert!(gpu.is_intel());
+        assert_eq!(gpu.id, "card1");
+        assert_eq!(gpu.render_node.as_deref(), Some("/dev/dri/renderD129"));
+        assert_eq!(gpu.driver, Some(GpuDriver::I915));
+
+        let vfio = minimal_gpu_info("card0".into(), String::new(), None, GpuDriver::Vfio);
+        assert!(vfio.is_none());
+    }
+
+    #[test]
+    fn test_read_driver_name() {
+        let device = std::env::temp_dir().join(format!("igs-driver-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&device);
+        fs::create_dir_all(&device).unwrap();
+        assert_eq!(read_driver_name(&device), None);
+
+        std::os::unix::fs::symlink("../../../bus/pci/drivers/amdgpu", device.join("driver"))
+            .unwrap();
+        let name = read_driver_name(&device);
+        assert_eq!(name.as_deref(), Some("amdgpu"));
+        assert_eq!(name.as_deref().and_then(driver_from_name), None);
+
+        fs::remove_dir_all(&device).unwrap();
+    }
+
+    #[test]
+    fn test_parse_card_id() {
+        let find_card = |address: &str| match address {
+            "0000:00:02.0" => Some("card0".to_string()),
+            "0000:03:00.0" => Some("card1".to_string()),
+            _ => None,
+        };
+        let card = |name, driver| parse_card_id(name, driver, find_card);
+
+        assert_eq!(card("i915", GpuDriver::I915), "card0");
+        assert_eq!(card("i915_0000_03_00.0", GpuDriver::I915), "card1");
+        assert_eq!(card("i915-0000:03:00.0", GpuDriver::I915), "card1");
+        assert_eq!(card("xe_0000_03_00.0", GpuDriver::Xe), "card1");
+        assert_eq!(card("xe_0000_00_02.0", GpuDriver::Xe), "card0");
+        // Unknown address
+        assert_eq!(card("xe_0000_04_00.0", GpuDriver::Xe), "card0");
+
+        assert_eq!(
+            parse_pmu_pci_address("xe_0000_03_00.0", GpuDriver::Xe).as_deref(),
+            Some("0000:03:00.0")
+        );
+        assert_eq!(parse_pmu_pci_address("i915", GpuDriver::I915), None);
+        assert_eq!(parse_pmu_pci_address("xe_garbage", GpuDriver::Xe), None);
+    }
+
+    #[test]
+    fn test_find_pmu_for_gpu() {
+        let pmu = |name: &str, driver| PmuInfo {
+            type_id: 0,
+            path: PathBuf::from(name),
+            events: HashMap::new(),
+            card_id: "card0".into(),
+            pci_address: parse_pmu_pci_address(name, driver),
+            driver,
+            format: HashMap::new(),
+        };
+        let gpu = |id: &str, pci_path: &str| {
+            let mut gpu = minimal_gpu_info(id.into(), String::new(), None, GpuDriver::Xe).unwrap();
+            gpu.pci_path = pci_path.into();
+            gpu
+        };
+        let pmus = || {
+            vec![
+                pmu("i915", GpuDriver::I915),
+                pmu("xe_0000_03_00.0", GpuDriver::Xe),
+            ]
+        };
+
+        let igpu = gpu("card0", "../../../0000:00:02.0");
+        let arc = gpu("card1", "../../../0000:00:01.0/0000:03:00.0");
+        let path = |gpu| find_pmu_for_gpu(pmus(), gpu).map(|p| p.path);
+        assert_eq!(path(&igpu).unwrap(), PathBuf::from("i915"));
+        assert_eq!(path(&arc).unwrap(), PathBuf::from("xe_0000_03_00.0"));
+
+        // No PMU for this card: fail instead of picking the first one
+        let other = gpu("card2", "../../../0000:00:01.0/0000:04:00.0");
+        assert!(matches!(
+            find_pmu_for_gpu(pmus(), &other),
+            Err(Error::PmuNotAvailable { reason }) if reason.contains("card2")
+        ));
+    }
+
+    #[test]
+    fn test_media_engine_count() {
+        let mut engines = HashMap::new();
+        assert_eq!(media_engine_count(&engines), 0);
+        engines.insert(EngineClass::Render, vec![0]);
+        engines.insert(EngineClass::Video, vec![0, 1]);
+        engines.insert(EngineClass::VideoEnhance, vec![0]);
+        assert_eq!(media_engine_count(&engines), 3);
+    }
+
+    #[test]
+    fn test_discover_render_nodes_empty() {
+        let dir = std::env::temp_dir().join(format!("igs-dri-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        // Not render nodes, or not DRM devices
+        fs::write(dir.join("card0"), "").unwrap();
+        fs::write(dir.join("renderD250"), "").unwrap();
+        assert!(discover_render_nodes(&dir).is_empty());
+        assert!(discover_render_nodes(&dir.join("missing")).is_empty());
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -6,28 +6,120 @@
 //! The power data is typically found at:
 //! - /sys/class/powercap/intel-rapl:0/ (package power)
 //! - /sys/class/powercap/intel-rapl:0:2/ (uncore/GPU power, if available)
+//! - /sys/class/powercap/intel-rapl:1/ (psys platform power on some laptops)
 //!
-//! Some discrete GPUs also expose power via hwmon.
+//! All domains are read; GPU, package and platform (psys) power are the sums
+//! over the matching domains, and each domain is also reported individually.
+//! On some laptops psys is the only domain that tracks the whole SoC
+//! including the GPU.
+//!
+//! Some discrete GPUs also expose power via hwmon, either as a cumulative
+//! energy counter (`energy1_input`, preferred) or as an instantaneous power
+//! reading (`power1_input`).
+//!
+//! The GPU power limit is read from and written to hwmon `power1_max` when
+//! present, otherwise, for integrated GPUs only, the first constraint of a
+//! GPU RAPL domain (`constraint_0_power_limit_uw`). That domain belongs to
+//! the CPU package's graphics, so it is never used for a discrete GPU.
+//! Package limits are never touched, since they also cap the CPU.
+//!
+//! Energy and power files are kept open between reads (see
+//! [`sysfs`](super::sysfs)).
 
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use super::sysfs::SysfsFile;
+use crate::error::{Error, Result};
 use crate::types::PowerStats;
 
+/// GPU power interface exposed through hwmon
+#[derive(Debug)]
+enum HwmonPower {
+    /// Cumulative energy counter in microjoules (`energy1_input`)
+    Energy(SysfsFile),
+    /// Instantaneous power in microwatts (`power1_input`)
+    Power(SysfsFile),
+}
+
+/// What a RAPL domain measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DomainKind {
+    /// CPU package (`package-N`)
+    Package,
+    /// GPU or uncore (`uncore`, or any domain with "gpu" in its name)
+    Gpu,
+    /// Whole platform (`psys`)
+    Platform,
+    /// Anything else (`core`, `dram`, ...), only reported individually
+    Other,
+}
+
+impl DomainKind {
+    fn from_name(name: &str) -> Self {
+        if name.starts_with("package") {
+            DomainKind::Package
+        } else if name == "uncore" || name.contains("gpu") {
+            DomainKind::Gpu
+        } else if name == "psys" {
+            DomainKind::Platform
+        } else {
+            DomainKind::Other
+        }
+    }
+}
+
+/// A RAPL powercap domain and its energy counter state
+#[derive(Debug)]
+struct RaplDomain {
+    /// Domain name as reported by the kernel (e.g. "package-0", "uncore")
+    name: String,
+    kind: DomainKind,
+    /// Path to the `energy_uj` file
+    energy_path: PathBuf,
+    /// The open `energy_uj` file, `None` if it is not readable (root only
+    /// on most kernels)
+    energy: Option<SysfsFile>,
+    /// Wraparound range of the energy counter (microjoules)
+    range_uj: Option<u64>,
+    /// Last energy reading (microjoules)
+    last_uj: u64,
+}
+
+impl RaplDomain {
+    fn new(name: String, energy_path: PathBuf) -> Self {
+        let energy = SysfsFile::open(&energy_path);
+        Self {
+            kind: DomainKind::from_name(&name),
+            range_uj: read_energy_range_uj(&energy_path),
+            last_uj: energy.as_ref().and_then(SysfsFile::read_value).unwrap_or(0),
+            name,
+            energy_path,
+            energy,
+        }
+    }
+
+    /// Average power in watts since the previous read
+    fn read_watts(&mut self, elapsed_us: f64) -> Option<f64> {
+        let current_uj: u64 = self.energy.as_ref()?.read_value()?;
+        let delta = energy_delta_uj(self.last_uj, current_uj, self.range_uj);
+        self.last_uj = current_uj;
+        Some(delta as f64 / elapsed_us) // uJ/us = W
+    }
+}
+
 /// RAPL power reader
 #[derive(Debug)]
 pub struct RaplReader {
-    /// Path to package energy file
-    package_energy_path: Option<PathBuf>,
-    /// Path to GPU/uncore energy file (if available)
-    gpu_energy_path: Option<PathBuf>,
-    /// Path to hwmon power file (discrete GPUs)
-    hwmon_power_path: Option<PathBuf>,
-    /// Last package energy reading (microjoules)
-    last_package_uj: u64,
-    /// Last GPU energy reading (microjoules)
-    last_gpu_uj: u64,
+    /// All RAPL domains found under powercap
+    domains: Vec<RaplDomain>,
+    /// Hwmon power interface (discrete GPUs)
+    hwmon_power: Option<HwmonPower>,
+    /// Last hwmon energy reading (microjoules)
+    last_hwmon_uj: u64,
+    /// Writable GPU power limit file (microwatts)
+    power_limit_path: Option<PathBuf>,
     /// Last read timestamp
     last_timestamp: Instant,
 }
@@ -35,47 +127,70 @@ pub struct RaplReader {
 impl RaplReader {
     /// Create a new RAPL reader
     ///
-    /// Searches for available power measurement interfaces.
-    pub fn new(pci_path: &str) -> Self {
-        let (package_path, gpu_path) = find_rapl_paths();
-        let hwmon_path = find_hwmon_power(pci_path);
-
-        let mut reader = Self {
-            package_energy_path: package_path,
-            gpu_energy_path: gpu_path,
-            hwmon_power_path: hwmon_path,
-            last_package_uj: 0,
-            last_gpu_uj: 0,
-            last_timestamp: Instant::now(),
+    /// Searches for available power measurement interfaces. `integrated`
+    /// allows the RAPL power limit fallback, see the module docs.
+    pub fn new(pci_path: &str, integrated: bool) -> Self {
+        let domains: Vec<RaplDomain> = find_rapl_domains()
+            .into_iter()
+            .map(|(name, energy_path)| RaplDomain::new(name, energy_path))
+            .collect();
+        let hwmon_dir = find_hwmon_dir(pci_path);
+        let hwmon_power = hwmon_dir.as_deref().and_then(find_hwmon_power);
+        let power_limit_path = find_power_limit(hwmon_dir.as_deref(), &domains, integrated);
+        let last_hwmon_uj = match hwmon_power {
+            Some(HwmonPower::Energy(ref file)) => file.read_value().unwrap_or(0),
+            _ => 0,
         };
 
-        // Initialize with current readings
-        if let Some(ref path) = reader.package_energy_path {
-            reader.last_package_uj = read_energy_uj(path).unwrap_or(0);
-        }
-        if let Some(ref path) = reader.gpu_energy_path {
-            reader.last_gpu_uj = read_energy_uj(path).unwrap_or(0);
+        Self {
+            domains,
+            hwmon_power,
+            last_hwmon_uj,
+            power_limit_path,
+            last_timestamp: Instant::now(),
         }
-        reader.last_timestamp = Instant::now();
+    }
 
-        reader
+    /// Current GPU power limit in watts, if one is exposed
+    pub fn power_limit_watts(&self) -> Option<f64> {
+        let limit_uw = read_power_uw(self.power_limit_path.as_ref()?)?;
+        Some(limit_uw as f64 / 1_000_000.0)
+    }
+
+    /// Set the GPU power limit in watts
+    ///
+    /// Returns [`Error::FeatureNotAvailable`] if no power limit file was
+    /// found and [`Error::PermissionDenied`] if it is not writable, which is
+    /// the case without root privileges.
+    pub fn set_power_limit_watts(&self, watts: f64) -> Result<()> {
+        let limit_uw = watts_to_uw(watts)?;
+        let path = self
+            .power_limit_path
+            .as_ref()
+            .ok_or_else(|| Error::FeatureNotAvailable {
+                feature: "Power limit control".to_string(),
+            })?;
+
+        fs::write(path, limit_uw.to_string()).map_err(|e| Error::sysfs_write(path, e))
     }
 
     /// Check if any power monitoring is available
     pub fn is_available(&self) -> bool {
-        self.package_energy_path.is_some()
-            || self.gpu_energy_path.is_some()
-            || self.hwmon_power_path.is_some()
+        !self.domains.is_empty() || self.hwmon_power.is_some()
     }
 
     /// Check if GPU-specific power is available
     pub fn has_gpu_power(&self) -> bool {
-        self.gpu_energy_path.is_some() || self.hwmon_power_path.is_some()
+        self.hwmon_power.is_some() || self.domains.iter().any(|d| d.kind == DomainKind::Gpu)
     }
 
     /// Read current power consumption
     ///
     /// Returns power in watts calculated from energy delta since last read.
+    /// GPU power comes from hwmon when available, otherwise it is the sum of
+    /// all GPU/uncore RAPL domains. Package and platform power are the sums
+    /// of all package and psys domains. Every RAPL domain is also listed in
+    /// [`PowerStats::domains`].
     pub fn read(&mut self) -> Option<PowerStats> {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_timestamp);
@@ -86,155 +201,168 @@ impl RaplReader {
             return None;
         }
 
-        // First try hwmon direct power reading (discrete GPUs)
-        if let Some(ref path) = self.hwmon_power_path {
-            if let Some(power_uw) = read_power_uw(path) {
-                let gpu_watts = power_uw as f64 / 1_000_000.0;
-
-                // Also read package if available
-                let package_watts = self.read_package_watts(elapsed_us);
+        // Prefer hwmon (discrete GPUs), and its energy counter over power
+        // since it averages over the whole sample instead of a single instant
+        let hwmon_watts = match self.hwmon_power {
+            Some(HwmonPower::Energy(ref file)) => file.read_value().map(|current_uj| {
+                let delta = energy_delta_uj(self.last_hwmon_uj, current_uj, None);
+                self.last_hwmon_uj = current_uj;
+                delta as f64 / elapsed_us // uJ/us = W
+            }),
+            Some(HwmonPower::Power(ref file)) => file
+                .read_value::<u64>()
+                .map(|power_uw| power_uw as f64 / 1_000_000.0),
+            None => None,
+        };
 
-                self.last_timestamp = now;
-                return Some(PowerStats::new(gpu_watts, package_watts));
+        let mut package_watts = None;
+        let mut rapl_gpu_watts = None;
+        let mut platform_watts = None;
+        let mut domains = Vec::with_capacity(self.domains.len());
+        for domain in &mut self.domains {
+            let Some(watts) = domain.read_watts(elapsed_us) else {
+                continue;
+            };
+            match domain.kind {
+                DomainKind::Package => *package_watts.get_or_insert(0.0) += watts,
+                DomainKind::Gpu => *rapl_gpu_watts.get_or_insert(0.0) += watts,
+                DomainKind::Platform => *platform_watts.get_or_insert(0.0) += watts,
+                DomainKind::Other => {}
             }
+            domains.push((domain.name.clone(), watts));
         }
 
-        // Fall back to RAPL energy counters
-        let package_watts = self.read_package_watts(elapsed_us);
-
-        let gpu_watts = if let Some(ref path) = self.gpu_energy_path {
-            if let Some(current_uj) = read_energy_uj(path) {
-                let delta = current_uj.saturating_sub(self.last_gpu_uj);
-                self.last_gpu_uj = current_uj;
-                Some(delta as f64 / elapsed_us) // uJ/us = W
-            } else {
-                None
-            }
-        } else {
-            None
-        };
-
         self.last_timestamp = now;
 
         // Return stats if we have any power reading
-        if gpu_watts.is_some() || package_watts.is_some() {
-            Some(PowerStats::new(gpu_watts.unwrap_or(0.0), package_watts))
+        let gpu_watts = hwmon_watts.or(rapl_gpu_watts);
+        if gpu_watts.is_some() || package_watts.is_some() || platform_watts.is_some() {
+            let mut stats = PowerStats::new(gpu_watts.unwrap_or(0.0), package_watts);
+            stats.platform_watts = platform_watts;
+            stats.domains = domains;
+            Some(stats)
         } else {
             None
         }
     }
-
-    /// Read package power in watts
-    fn read_package_watts(&mut self, elapsed_us: f64) -> Option<f64> {
-        if let Some(ref path) = self.package_energy_path {
-            if let Some(current_uj) = read_energy_uj(path) {
-                let delta = current_uj.saturating_sub(self.last_package_uj);
-                self.last_package_uj = current_uj;
-                return Some(delta as f64 / elapsed_us); // uJ/us = W
-            }
-        }
-        None
-    }
 }
 
-/// Find RAPL sysfs paths
-fn find_rapl_paths() -> (Option<PathBuf>, Option<PathBuf>) {
+/// Find all RAPL domains as (name, energy file) pairs
+///
+/// `/sys/class/powercap` normally lists subdomains (e.g. `intel-rapl:0:2`)
+/// next to their parents, but they are also searched for inside each parent
+/// in case it does not. Each domain is returned once.
+fn find_rapl_domains() -> Vec<(String, PathBuf)> {
     let powercap_base = Path::new("/sys/class/powercap");
-    if !powercap_base.exists() {
-        return (None, None);
-    }
-
-    let mut package_path = None;
-    let mut gpu_path = None;
-
-    // Look for intel-rapl domains
     let entries = match fs::read_dir(powercap_base) {
         Ok(e) => e,
-        Err(_) => return (None, None),
+        Err(_) => return Vec::new(),
     };
 
+    let mut zone_paths = Vec::new();
     for entry in entries.flatten() {
-        let name = entry.file_name();
-        let name_str = name.to_string_lossy();
-
-        if name_str.starts_with("intel-rapl:") {
-            let domain_path = entry.path();
-
-            // Check what type of domain this is
-            let name_file = domain_path.join("name");
-            if let Ok(domain_name) = fs::read_to_string(&name_file) {
-                let domain_name = domain_name.trim();
-
-                if domain_name == "package-0" || domain_name.starts_with("package") {
-                    let energy_path = domain_path.join("energy_uj");
-                    if energy_path.exists() {
-                        package_path = Some(energy_path);
-                    }
-                }
-
-                // Look for GPU/uncore domain
-                if domain_name == "uncore" || domain_name.contains("gpu") {
-                    let energy_path = domain_path.join("energy_uj");
-                    if energy_path.exists() {
-                        gpu_path = Some(energy_path);
-                    }
-                }
-            }
+        if !entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("intel-rapl:")
+        {
+            continue;
+        }
+        let domain_path = entry.path();
+
+        if let Ok(subentries) = fs::read_dir(&domain_path) {
+            zone_paths.extend(
+                subentries
+                    .flatten()
+                    .filter(|sub| sub.file_name().to_string_lossy().starts_with("intel-rapl:"))
+                    .map(|sub| sub.path()),
+            );
+        }
+        zone_paths.push(domain_path);
+    }
 
-            // Also check subdirectories for uncore
-            if let Ok(subentries) = fs::read_dir(&domain_path) {
-                for subentry in subentries.flatten() {
-                    let subname = subentry.file_name();
-                    let subname_str = subname.to_string_lossy();
-
-                    if subname_str.contains("intel-rapl:") {
-                        let sub_path = subentry.path();
-                        let name_file = sub_path.join("name");
-
-                        if let Ok(sub_domain_name) = fs::read_to_string(&name_file) {
-                            if sub_domain_name.trim() == "uncore" {
-                                let energy_path = sub_path.join("energy_uj");
-                                if energy_path.exists() {
-                                    gpu_path = Some(energy_path);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    let mut seen = Vec::new();
+    let mut domains = Vec::new();
+    for zone_path in zone_paths {
+        let canonical = fs::canonicalize(&zone_path).unwrap_or_else(|_| zone_path.clone());
+        if seen.contains(&canonical) {
+            continue;
+        }
+        seen.push(canonical);
+
+        let Ok(name) = fs::read_to_string(zone_path.join("name")) else {
+            continue;
+        };
+        let energy_path = zone_path.join("energy_uj");
+        if energy_path.exists() {
+            domains.push((name.trim().to_string(), energy_path));
         }
     }
 
-    (package_path, gpu_path)
+    // Stable order regardless of directory iteration order
+    domains.sort_by(|a, b| a.1.cmp(&b.1));
+    domains
 }
 
-/// Find hwmon power interface for discrete GPUs
-fn find_hwmon_power(pci_path: &str) -> Option<PathBuf> {
-    let hwmon_base = Path::new("/sys/class/hwmon");
-    if !hwmon_base.exists() {
+/// Find the hwmon directory of a discrete GPU
+fn find_hwmon_dir(pci_path: &str) -> Option<PathBuf> {
+    if pci_path.is_empty() {
         return None;
     }
 
-    let entries = fs::read_dir(hwmon_base).ok()?;
+    let entries = fs::read_dir("/sys/class/hwmon").ok()?;
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|hwmon_path| {
+            // Check if this hwmon belongs to our GPU
+            fs::read_link(hwmon_path.join("device"))
+                .map(|target| target.to_string_lossy().contains(pci_path))
+                .unwrap_or(false)
+        })
+}
 
-    for entry in entries.flatten() {
-        let hwmon_path = entry.path();
-
-        // Check if this hwmon belongs to our GPU
-        let device_link = hwmon_path.join("device");
-        if let Ok(target) = fs::read_link(&device_link) {
-            let target_str = target.to_string_lossy();
-            if !pci_path.is_empty() && target_str.contains(pci_path) {
-                // Found the right hwmon, look for power
-                let power_path = hwmon_path.join("power1_input");
-                if power_path.exists() {
-                    return Some(power_path);
-                }
-            }
-        }
-    }
+/// Find the hwmon power interface, preferring the energy counter over power
+fn find_hwmon_power(hwmon_path: &Path) -> Option<HwmonPower> {
+    SysfsFile::open(&hwmon_path.join("energy1_input"))
+        .map(HwmonPower::Energy)
+        .or_else(|| SysfsFile::open(&hwmon_path.join("power1_input")).map(HwmonPower::Power))
+}
+
+/// Find the GPU power limit file: hwmon `power1_max`, else a GPU RAPL
+/// domain's if the GPU is integrated
+fn find_power_limit(
+    hwmon_path: Option<&Path>,
+    domains: &[RaplDomain],
+    integrated: bool,
+) -> Option<PathBuf> {
+    let hwmon_limit = hwmon_path.map(|path| path.join("power1_max"));
+    let rapl_limits = domains
+        .iter()
+        .filter(|domain| integrated && domain.kind == DomainKind::Gpu)
+        .map(|domain| {
+            domain
+                .energy_path
+                .with_file_name("constraint_0_power_limit_uw")
+        });
+    hwmon_limit
+        .into_iter()
+        .chain(rapl_limits)
+        .find(|path| path.exists())
+}
 
-    None
+/// Convert a power limit in watts to microwatts, rejecting nonsense values
+fn watts_to_uw(watts: f64) -> Result<u64> {
+    if !watts.is_finite() || watts <= 0.0 {
+        return Err(Error::InvalidConfig {
+            message: format!(
+                "power limit must be a positive number of watts, got {}",
+                watts
+            ),
+        });
+    }
+    Ok((watts * 1_000_000.0).round() as u64)
 }
 
 /// Read energy in microjoules from a RAPL energy file
@@ -244,6 +372,28 @@ fn read_energy_uj(path: &Path) -> Option<u64> {
         .and_then(|s| s.trim().parse().ok())
 }
 
+/// Read the wraparound range of a RAPL energy counter
+///
+/// RAPL exposes it as `max_energy_range_uj` next to `energy_uj`.
+fn read_energy_range_uj(energy_path: &Path) -> Option<u64> {
+    read_energy_uj(&energy_path.with_file_name("max_energy_range_uj"))
+}
+
+/// Energy consumed between two counter readings, in microjoules
+///
+/// Handles a single wraparound when the counter range is known. Without a
+/// range (e.g. 64-bit hwmon counters that never wrap in practice), a counter
+/// that went backwards yields zero rather than a bogus huge delta.
+fn energy_delta_uj(last_uj: u64, current_uj: u64, range_uj: Option<u64>) -> u64 {
+    if current_uj >= last_uj {
+        return current_uj - last_uj;
+    }
+    match range_uj {
+        Some(range) if last_uj <= range => range - last_uj + current_uj,
+        _ => 0,
+    }
+}
+
 /// Read power in microwatts from an hwmon power file
 fn read_power_uw(path: &Path) -> Option<u64> {
     fs::read_to_string(path)
@@ -262,10 +412,109 @@ mod tests {
         assert!((stats.package_watts.unwrap() - 45.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_energy_delta() {
+        assert_eq!(energy_delta_uj(1_000, 3_500, None), 2_500);
+        // Wraparound with a known counter range
+        assert_eq!(energy_delta_uj(9_000, 500, Some(10_000)), 1_500);
+        // Counter went backwards without a known range
+        assert_eq!(energy_delta_uj(9_000, 500, None), 0);
+    }
+
+    #[test]
+    fn test_power_limit() {
+        assert_eq!(watts_to_uw(25.5).unwrap(), 25_500_000);
+        assert!(matches!(watts_to_uw(0.0), Err(Error::InvalidConfig { .. })));
+        assert!(watts_to_uw(f64::NAN).is_err());
+
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-limit-{}", std::process::id()));
+        fs::create_dir_all(&hwmon).unwrap();
+        assert_eq!(find_power_limit(Some(&hwmon), &[], false), None);
+
+        fs::write(hwmon.join("power1_max"), "120000000\n").unwrap();
+        let reader = RaplReader {
+            domains: Vec::new(),
+            hwmon_power: None,
+            last_hwmon_uj: 0,
+            power_limit_path: find_power_limit(Some(&hwmon), &[], false),
+            last_timestamp: Instant::now(),
+        };
+        assert_eq!(reader.power_limit_watts(), Some(120.0));
+        reader.set_power_limit_watts(95.0).unwrap();
+        assert_eq!(reader.power_limit_watts(), Some(95.0));
+
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
+
+    #[test]
+    fn test_power_limit_rapl_fallback() {
+        let zone = std::env::temp_dir().join(format!("igs-rapl-uncore-{}", std::process::id()));
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("energy_uj"), "0\n").unwrap();
+        fs::write(zone.join("constraint_0_power_limit_uw"), "15000000\n").unwrap();
+        let domains = [RaplDomain::new("uncore".into(), zone.join("energy_uj"))];
+
+        assert_eq!(
+            find_power_limit(None, &domains, true),
+            Some(zone.join("constraint_0_power_limit_uw"))
+        );
+        // A discrete GPU without hwmon must not get the iGPU's limit
+        let reader = RaplReader {
+            domains: Vec::new(),
+            hwmon_power: None,
+            last_hwmon_uj: 0,
+            power_limit_path: find_power_limit(None, &domains, false),
+            last_timestamp: Instant::now(),
+        };
+        assert_eq!(reader.power_limit_watts(), None);
+        assert!(matches!(
+            reader.set_power_limit_watts(95.0),
+            Err(Error::FeatureNotAvailable { .. })
+        ));
+        assert_eq!(
+            fs::read_to_string(zone.join("constraint_0_power_limit_uw")).unwrap(),
+            "15000000\n"
+        );
+
+        fs::remove_dir_all(&zone).unwrap();
+    }
+
+    #[test]
+    fn test_hwmon_power() {
+        let hwmon = std::env::temp_dir().join(format!("igs-hwmon-power-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&hwmon);
+        fs::create_dir_all(&hwmon).unwrap();
+        assert!(find_hwmon_power(&hwmon).is_none());
+
+        fs::write(hwmon.join("power1_input"), "15000000\n").unwrap();
+        assert!(matches!(
+            find_hwmon_power(&hwmon),
+            Some(HwmonPower::Power(_))
+        ));
+
+        // The energy counter is preferred and read through the open file
+        fs::write(hwmon.join("energy1_input"), "1000\n").unwrap();
+        let Some(HwmonPower::Energy(energy)) = find_hwmon_power(&hwmon) else {
+            panic!("energy counter not found");
+        };
+        fs::write(hwmon.join("energy1_input"), "2500\n").unwrap();
+        assert_eq!(energy.read_value::<u64>(), Some(2500));
+
+        fs::remove_dir_all(&hwmon).unwrap();
+    }
+
+    #[test]
+    fn test_domain_kind() {
+        assert_eq!(DomainKind::from_name("package-0"), DomainKind::Package);
+        assert_eq!(DomainKind::from_name("uncore"), DomainKind::Gpu);
+        assert_eq!(DomainKind::from_name("psys"), DomainKind::Platform);
+        assert_eq!(DomainKind::from_name("core"), DomainKind::Other);
+    }
+
     #[test]
     fn test_rapl_reader_creation() {
         // Just test that creation doesn't panic
-        let reader = RaplReader::new("");
+        let reader = RaplReader::new("", false);
         // Can't test much without actual hardware
         let _ = reader.is_available();
     }
@@ -0,0 +1,135 @@
+//! RC6 residency counter units
+//!
+//! The PMU's RC6 event counts residency in the unit named by its
+//! `events/<name>.unit` file: nanoseconds for i915's `rc6-residency`,
+//! milliseconds for xe's `gt-c6-residency`. Some kernels get the unit
+//! wrong or omit it, which shows up as RC6 pinned at 100% or stuck near
+//! zero. The driver also exposes residency in sysfs with a fixed unit of
+//! milliseconds:
+//!
+//! i915:
+//! /sys/class/drm/card0/gt/gt0/rc6_residency_ms
+//! /sys/class/drm/card0/power/rc6_residency_ms (older kernels, gt0 only)
+//!
+//! xe:
+//! /sys/class/drm/card0/device/tile0/gt0/gtidle/idle_residency_ms
+//!
+//! Once enough residency has accumulated in sysfs, the PMU count is checked
+//! against it and the unit corrected (see [`ns_per_count_from_reference`]).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::paths;
+use crate::types::GpuDriver;
+
+/// Sysfs residency needed before the PMU unit is checked against it
+///
+/// The sysfs counter has millisecond granularity, so shorter spans would
+/// make the comparison too coarse.
+pub const CALIBRATION_MS: u64 = 100;
+
+/// Nanoseconds per unit of a perf event `.unit` string
+pub fn unit_ns(unit: &str) -> Option<f64> {
+    match unit.trim() {
+        "ns" => Some(1.0),
+        "us" => Some(1e3),
+        "ms" => Some(1e6),
+        "s" => Some(1e9),
+        _ => None,
+    }
+}
+
+/// Nanoseconds per PMU count, from the counts and sysfs residency over the
+/// same span
+///
+/// The ratio is rounded to a power of ten, since unit mistakes are off by
+/// whole decimal prefixes. `None` until at least [`CALIBRATION_MS`] of
+/// residency was seen, or if the PMU did not count at all.
+pub fn ns_per_count_from_reference(counts: u64, reference_ms: u64) -> Option<f64> {
+    if reference_ms < CALIBRATION_MS || counts == 0 {
+        return None;
+    }
+    let ns_per_count = reference_ms as f64 * 1e6 / counts as f64;
+    Some(10f64.powi(ns_per_count.log10().round() as i32))
+}
+
+/// Find the sysfs RC6 residency file (milliseconds) of a GT
+pub fn find_residency_ms(card_id: &str, driver: GpuDriver, gt: u16) -> Option<PathBuf> {
+    find_residency_ms_in(&paths::card_sysfs_dir(card_id), driver, gt)
+}
+
+fn find_residency_ms_in(card_path: &Path, driver: GpuDriver, gt: u16) -> Option<PathBuf> {
+    let candidates: Vec<PathBuf> = match driver {
+        GpuDriver::I915 => {
+            let mut candidates = vec![card_path.join(format!("gt/gt{}/rc6_residency_ms", gt))];
+            if gt == 0 {
+                candidates.push(card_path.join("power/rc6_residency_ms"));
+            }
+            candidates
+        }
+        GpuDriver::Xe => fs::read_dir(card_path.join("device"))
+            .map(|tiles| {
+                tiles
+                    .flatten()
+                    .filter(|tile| tile.file_name().to_string_lossy().starts_with("tile"))
+                    .map(|tile| {
+                        tile.path()
+                            .join(format!("gt{}/gtidle/idle_residency_ms", gt))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        GpuDriver::Vfio | GpuDriver::WindowsDch => Vec::new(),
+    };
+    candidates.into_iter().find(|path| path.exists())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_ns() {
+        assert_eq!(unit_ns("ns\n"), Some(1.0));
+        assert_eq!(unit_ns("ms"), Some(1e6));
+        assert_eq!(unit_ns("MiB"), None);
+    }
+
+    #[test]
+    fn test_ns_per_count_from_reference() {
+        // Nanosecond counter: 250 ms of residency is 250e6 counts
+        assert_eq!(ns_per_count_from_reference(250_000_000, 250), Some(1.0));
+        // Millisecond counter, off by a millisecond of sysfs granularity
+        assert_eq!(ns_per_count_from_reference(251, 250), Some(1e6));
+        // Microsecond counter
+        assert_eq!(ns_per_count_from_reference(249_000, 250), Some(1e3));
+        // Too little residency, or a counter that doesn't move
+        assert_eq!(ns_per_count_from_reference(50_000_000, 50), None);
+        assert_eq!(ns_per_count_from_reference(0, 250), None);
+    }
+
+    #[test]
+    fn test_find_residency_ms() {
+        let card = std::env::temp_dir().join(format!("igs-rc6-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&card);
+        fs::create_dir_all(card.join("power")).unwrap();
+        fs::write(card.join("power/rc6_residency_ms"), "1000\n").unwrap();
+        let gtidle = card.join("device/tile0/gt1/gtidle");
+        fs::create_dir_all(&gtidle).unwrap();
+        fs::write(gtidle.join("idle_residency_ms"), "500\n").unwrap();
+
+        assert_eq!(
+            find_residency_ms_in(&card, GpuDriver::I915, 0),
+            Some(card.join("power/rc6_residency_ms"))
+        );
+        assert_eq!(find_residency_ms_in(&card, GpuDriver::I915, 1), None);
+        assert_eq!(
+            find_residency_ms_in(&card, GpuDriver::Xe, 1),
+            Some(gtidle.join("idle_residency_ms"))
+        );
+        assert_eq!(find_residency_ms_in(&card, GpuDriver::Xe, 0), None);
+
+        fs::remove_dir_all(&card).unwrap();
+    }
+}
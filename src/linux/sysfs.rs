@@ -0,0 +1,69 @@
+//! Sysfs files kept open between samples
+//!
+//! Reading a sysfs attribute with `fs::read_to_string` opens, reads and
+//! closes the file every time. The readers that poll once per sample
+//! (throttle reasons, hwmon, RAPL, sysfs frequencies) instead open their
+//! files once and read them again from offset 0, which sysfs regenerates on
+//! every read. This saves two syscalls per file per sample.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Longest value read; sysfs numbers and flags are far shorter
+const MAX_VALUE_LEN: usize = 64;
+
+/// A sysfs attribute file held open for repeated reads
+#[derive(Debug)]
+pub struct SysfsFile {
+    file: File,
+}
+
+impl SysfsFile {
+    /// Open a sysfs file for reading, `None` if it is missing or unreadable
+    pub fn open(path: &Path) -> Option<Self> {
+        File::open(path).ok().map(|file| Self { file })
+    }
+
+    /// Read and parse the current value
+    ///
+    /// Each call reads the file again from the start, so it returns the
+    /// value at the time of the call. Surrounding whitespace is ignored.
+    /// Reads interrupted by a signal are retried.
+    pub fn read_value<T: FromStr>(&self) -> Option<T> {
+        let mut buf = [0u8; MAX_VALUE_LEN];
+        let len = loop {
+            match self.file.read_at(&mut buf, 0) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result.ok()?,
+            }
+        };
+        std::str::from_utf8(&buf[..len]).ok()?.trim().parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_sysfs_file() {
+        let path = std::env::temp_dir().join(format!("igs-sysfs-{}", std::process::id()));
+        fs::write(&path, "1200\n").unwrap();
+
+        let file = SysfsFile::open(&path).unwrap();
+        assert_eq!(file.read_value::<u32>(), Some(1200));
+        // The same handle sees later values
+        fs::write(&path, "350\n").unwrap();
+        assert_eq!(file.read_value::<u32>(), Some(350));
+        assert_eq!(file.read_value::<i64>(), Some(350));
+        fs::write(&path, "n/a\n").unwrap();
+        assert_eq!(file.read_value::<u32>(), None);
+
+        fs::remove_file(&path).unwrap();
+        assert!(SysfsFile::open(&path).is_none());
+    }
+}
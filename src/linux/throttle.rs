@@ -12,117 +12,217 @@
 //! - ratl: Running Average Thermal Limit
 //! - vr_thermalert: VR thermal alert
 //! - vr_tdc: VR Thermal Design Current
+//!
+//! Older i915 kernels have no `throttle_reason_*` files. There throttling is
+//! inferred instead: the GPU counts as thermally throttled while it runs
+//! below `gt_max_freq_mhz` at [`INFERRED_THROTTLE_CELSIUS`] or hotter. The
+//! temperature comes from the GPU's hwmon or, for integrated GPUs without
+//! one, the CPU package thermal zone. Inferred readings have
+//! [`ThrottleInfo::inferred`] set.
+//!
+//! [`ThrottleReader`] keeps the files it polls open between reads (see
+//! [`sysfs`](super::sysfs)).
 
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::hwmon::find_gpu_hwmon;
+use super::paths;
+use super::sysfs::SysfsFile;
 use crate::types::ThrottleInfo;
 
+/// Temperature at or above which a frequency below the maximum is inferred
+/// to be thermal throttling
+pub const INFERRED_THROTTLE_CELSIUS: f64 = 90.0;
+
 /// Find the GT (Graphics Tile) path for a card
 fn find_gt_path(card_id: &str) -> Option<PathBuf> {
+    let card_path = paths::card_sysfs_dir(card_id);
+
     // Try gt0 first (most common)
-    let gt0_path = format!("/sys/class/drm/{}/gt/gt0", card_id);
-    if Path::new(&gt0_path).exists() {
-        return Some(PathBuf::from(gt0_path));
+    let gt0_path = card_path.join("gt/gt0");
+    if gt0_path.exists() {
+        return Some(gt0_path);
     }
 
     // Try direct gt path (older kernels)
-    let gt_path = format!("/sys/class/drm/{}/gt", card_id);
-    if Path::new(&gt_path).exists() {
-        return Some(PathBuf::from(gt_path));
+    let gt_path = card_path.join("gt");
+    if gt_path.exists() {
+        return Some(gt_path);
     }
 
     // Try device path (some drivers)
-    let device_gt = format!("/sys/class/drm/{}/device/gt", card_id);
-    if Path::new(&device_gt).exists() {
-        return Some(PathBuf::from(device_gt));
+    let device_gt = card_path.join("device/gt");
+    if device_gt.exists() {
+        return Some(device_gt);
     }
 
     None
 }
 
-/// Read a throttle reason file (returns true if throttle is active)
-fn read_throttle_file(path: &Path) -> bool {
-    fs::read_to_string(path)
-        .map(|s| s.trim() == "1")
+/// Check whether a GT directory has any `throttle_reason_*` file
+fn has_reason_files(gt_path: &Path) -> bool {
+    fs::read_dir(gt_path)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("throttle_reason_")
+            })
+        })
         .unwrap_or(false)
 }
 
-/// Read throttle information from sysfs
-pub fn read_throttle_info(card_id: &str) -> Option<ThrottleInfo> {
-    let gt_path = find_gt_path(card_id)?;
+/// The [`ThrottleInfo`] flag a throttle reason file sets
+type ReasonFlag = fn(&mut ThrottleInfo) -> &mut bool;
 
-    let mut info = ThrottleInfo::new();
+/// Throttle reason files and the flag each one sets
+const REASON_FILES: [(&str, ReasonFlag); 7] = [
+    ("throttle_reason_status", |info| &mut info.status),
+    ("throttle_reason_pl1", |info| &mut info.power_limit),
+    ("throttle_reason_thermal", |info| &mut info.thermal),
+    ("throttle_reason_prochot", |info| &mut info.prochot),
+    ("throttle_reason_ratl", |info| &mut info.ratl),
+    ("throttle_reason_vr_thermalert", |info| &mut info.vr_thermal),
+    ("throttle_reason_vr_tdc", |info| &mut info.vr_tdc),
+];
 
-    // Read each throttle reason file
-    let status_path = gt_path.join("throttle_reason_status");
-    if status_path.exists() {
-        info.status = read_throttle_file(&status_path);
-    }
+/// Open the throttle reason files present in a GT directory
+fn open_reason_files(gt_path: &Path) -> Vec<(SysfsFile, ReasonFlag)> {
+    REASON_FILES
+        .iter()
+        .filter_map(|&(name, flag)| Some((SysfsFile::open(&gt_path.join(name))?, flag)))
+        .collect()
+}
 
-    let pl1_path = gt_path.join("throttle_reason_pl1");
-    if pl1_path.exists() {
-        info.power_limit = read_throttle_file(&pl1_path);
+/// Read throttle reasons from open reason files
+///
+/// A reason is active if its file reads 1; unreadable files count as
+/// inactive.
+fn read_reasons(files: &[(SysfsFile, ReasonFlag)]) -> ThrottleInfo {
+    let mut info = ThrottleInfo::new();
+    for (file, flag) in files {
+        *flag(&mut info) = file.read_value::<u8>() == Some(1);
     }
 
-    let thermal_path = gt_path.join("throttle_reason_thermal");
-    if thermal_path.exists() {
-        info.thermal = read_throttle_file(&thermal_path);
-    }
+    // Set overall throttled flag
+    info.is_throttled = info.any_throttling();
+    info
+}
 
-    let prochot_path = gt_path.join("throttle_reason_prochot");
-    if prochot_path.exists() {
-        info.prochot = read_throttle_file(&prochot_path);
-    }
+/// Read throttle information from sysfs
+///
+/// Opens the reason files on every call; [`ThrottleReader`] keeps them
+/// open for repeated reads.
+pub fn read_throttle_info(card_id: &str) -> Option<ThrottleInfo> {
+    let gt_path = find_gt_path(card_id)?;
+    Some(read_reasons(&open_reason_files(&gt_path)))
+}
 
-    let ratl_path = gt_path.join("throttle_reason_ratl");
-    if ratl_path.exists() {
-        info.ratl = read_throttle_file(&ratl_path);
+/// Infer throttling from the GPU frequency and temperature
+///
+/// Only `is_throttled` and `thermal` can be set; the cause is assumed to be
+/// heat since nothing else is observable.
+pub fn infer_throttle(actual_mhz: u32, max_mhz: u32, celsius: f64) -> ThrottleInfo {
+    let throttled = actual_mhz < max_mhz && celsius >= INFERRED_THROTTLE_CELSIUS;
+    ThrottleInfo {
+        is_throttled: throttled,
+        thermal: throttled,
+        inferred: true,
+        ..Default::default()
     }
+}
 
-    let vr_thermal_path = gt_path.join("throttle_reason_vr_thermalert");
-    if vr_thermal_path.exists() {
-        info.vr_thermal = read_throttle_file(&vr_thermal_path);
-    }
+/// Find the CPU package temperature, which integrated GPUs share
+fn find_package_temperature() -> Option<PathBuf> {
+    let entries = fs::read_dir("/sys/class/thermal").ok()?;
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .find_map(|zone| {
+            let kind = fs::read_to_string(zone.join("type")).ok()?;
+            (kind.trim() == "x86_pkg_temp").then(|| zone.join("temp"))
+        })
+}
 
-    let vr_tdc_path = gt_path.join("throttle_reason_vr_tdc");
-    if vr_tdc_path.exists() {
-        info.vr_tdc = read_throttle_file(&vr_tdc_path);
-    }
+/// Files used to infer throttling on kernels without throttle reasons
+#[derive(Debug)]
+struct InferenceSource {
+    /// `gt_act_freq_mhz`
+    actual: SysfsFile,
+    /// `gt_max_freq_mhz`
+    max: SysfsFile,
+    /// Temperature in millidegrees Celsius
+    temperature: SysfsFile,
+}
 
-    // Set overall throttled flag
-    info.is_throttled = info.any_throttling();
+impl InferenceSource {
+    fn find(card_id: &str, pci_path: &str) -> Option<Self> {
+        let card_path = paths::card_sysfs_dir(card_id);
+        let actual = SysfsFile::open(&card_path.join("gt_act_freq_mhz"))?;
+        let max = SysfsFile::open(&card_path.join("gt_max_freq_mhz"))?;
+        let temperature = find_gpu_hwmon(pci_path)
+            .and_then(|hwmon| SysfsFile::open(&hwmon.join("temp1_input")))
+            .or_else(|| SysfsFile::open(&find_package_temperature()?))?;
+        Some(Self {
+            actual,
+            max,
+            temperature,
+        })
+    }
 
-    Some(info)
+    fn read(&self) -> Option<ThrottleInfo> {
+        let actual = self.actual.read_value::<u32>()?;
+        let max = self.max.read_value::<u32>()?;
+        let celsius = self.temperature.read_value::<i64>()? as f64 / 1000.0;
+        Some(infer_throttle(actual, max, celsius))
+    }
 }
 
 /// Throttle reader for continuous monitoring
 #[derive(Debug)]
 pub struct ThrottleReader {
-    /// Card ID (e.g., "card0")
-    card_id: String,
-    /// Path to the GT directory
-    gt_path: Option<PathBuf>,
+    /// Open throttle reason files of the GT, empty if there are none
+    reasons: Vec<(SysfsFile, ReasonFlag)>,
+    /// Fallback used when there are no throttle reason files
+    inference: Option<InferenceSource>,
 }
 
 impl ThrottleReader {
     /// Create a new throttle reader for a card
-    pub fn new(card_id: &str) -> Self {
-        let gt_path = find_gt_path(card_id);
-        Self {
-            card_id: card_id.to_string(),
-            gt_path,
-        }
+    ///
+    /// `pci_path` locates the GPU's hwmon, used only if throttling has to be
+    /// inferred.
+    pub fn new(card_id: &str, pci_path: &str) -> Self {
+        let reasons = find_gt_path(card_id)
+            .filter(|path| has_reason_files(path))
+            .map(|path| open_reason_files(&path))
+            .unwrap_or_default();
+        let inference = if reasons.is_empty() {
+            InferenceSource::find(card_id, pci_path)
+        } else {
+            None
+        };
+        Self { reasons, inference }
     }
 
     /// Check if throttle monitoring is available
     pub fn is_available(&self) -> bool {
-        self.gt_path.is_some()
+        !self.reasons.is_empty() || self.inference.is_some()
+    }
+
+    /// Check if throttling is inferred rather than read from throttle reasons
+    pub fn is_inferred(&self) -> bool {
+        self.reasons.is_empty() && self.inference.is_some()
     }
 
     /// Read current throttle information
     pub fn read(&self) -> Option<ThrottleInfo> {
-        read_throttle_info(&self.card_id)
+        if !self.reasons.is_empty() {
+            return Some(read_reasons(&self.reasons));
+        }
+        self.inference.as_ref()?.read()
     }
 }
 
@@ -149,4 +249,40 @@ mod tests {
         info.power_limit = true;
         assert!(info.any_throttling());
     }
+
+    #[test]
+    fn test_reason_files() {
+        let gt = std::env::temp_dir().join(format!("igs-throttle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&gt);
+        fs::create_dir_all(&gt).unwrap();
+        fs::write(gt.join("throttle_reason_status"), "0\n").unwrap();
+        fs::write(gt.join("throttle_reason_thermal"), "0\n").unwrap();
+        assert!(has_reason_files(&gt));
+
+        let files = open_reason_files(&gt);
+        assert_eq!(files.len(), 2);
+        assert!(!read_reasons(&files).is_throttled);
+
+        // The open files see later values
+        fs::write(gt.join("throttle_reason_status"), "1\n").unwrap();
+        fs::write(gt.join("throttle_reason_thermal"), "1\n").unwrap();
+        let info = read_reasons(&files);
+        assert!(info.is_throttled && info.status && info.thermal);
+        assert!(!info.power_limit);
+
+        fs::remove_dir_all(&gt).unwrap();
+    }
+
+    #[test]
+    fn test_infer_throttle() {
+        let hot_and_slow = infer_throttle(350, 1150, 95.0);
+        assert!(hot_and_slow.is_throttled && hot_and_slow.thermal);
+        assert!(hot_and_slow.inferred);
+
+        // Running at the maximum, or below it while cool, is not throttling
+        assert!(!infer_throttle(1150, 1150, 95.0).is_throttled);
+        let cool = infer_throttle(350, 1150, 60.0);
+        assert!(!cool.any_throttling());
+        assert!(cool.inferred);
+    }
 }
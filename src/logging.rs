@@ -0,0 +1,98 @@
+//! Hook for the crate's diagnostic messages
+//!
+//! Problems the crate recovers from, such as an engine that could not be
+//! opened or an fdinfo key it does not know, are reported as log messages.
+//! By default warnings are printed to stderr and informational messages are
+//! dropped. [`set_log_hook`] routes both into the application's logging
+//! instead:
+//!
+//! ```
+//! use intel_gpu_stats::logging::{set_log_hook, LogLevel};
+//!
+//! set_log_hook(|level, message| match level {
+//!     LogLevel::Warn => eprintln!("gpu warning: {}", message),
+//!     LogLevel::Info => println!("gpu: {}", message),
+//! });
+//! ```
+
+use std::fmt;
+use std::sync::RwLock;
+
+/// Severity of a log message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogLevel {
+    /// Something did not work, but the crate carried on without it
+    Warn,
+    /// Worth knowing, e.g. a driver reporting something unrecognized
+    Info,
+}
+
+type LogHook = Box<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// Installed hook, `None` for the default stderr output
+static HOOK: RwLock<Option<LogHook>> = RwLock::new(None);
+
+/// Send the crate's log messages to `hook`
+///
+/// Replaces any previously installed hook. The hook may be called from the
+/// sampling threads, so it must be `Send + Sync`.
+pub fn set_log_hook(hook: impl Fn(LogLevel, &str) + Send + Sync + 'static) {
+    *HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(hook));
+}
+
+/// Remove the installed hook, printing warnings to stderr again
+pub fn reset_log_hook() {
+    *HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Log a message through the hook, or print warnings to stderr without one
+pub(crate) fn log(level: LogLevel, message: fmt::Arguments<'_>) {
+    let hook = HOOK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    match hook.as_ref() {
+        Some(hook) => hook(level, &message.to_string()),
+        None if level == LogLevel::Warn => eprintln!("Warning: {}", message),
+        None => {}
+    }
+}
+
+/// Log a warning
+pub(crate) fn warn(message: fmt::Arguments<'_>) {
+    log(LogLevel::Warn, message);
+}
+
+/// Log an informational message
+pub(crate) fn info(message: fmt::Arguments<'_>) {
+    log(LogLevel::Info, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_log_hook() {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let sink = messages.clone();
+        set_log_hook(move |level, message| {
+            sink.lock().unwrap().push((level, message.to_string()));
+        });
+        warn(format_args!("engine {} unavailable", 2));
+        info(format_args!("unknown key"));
+        reset_log_hook();
+        // Dropped without a hook
+        info(format_args!("not recorded"));
+
+        // Other tests may log while the hook is installed
+        let messages = messages.lock().unwrap();
+        assert!(messages.contains(&(LogLevel::Warn, "engine 2 unavailable".to_string())));
+        assert!(messages.contains(&(LogLevel::Info, "unknown key".to_string())));
+        assert!(!messages
+            .iter()
+            .any(|(_, message)| message == "not recorded"));
+    }
+}
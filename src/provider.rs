@@ -0,0 +1,112 @@
+//! Vendor-neutral interface for GPU statistics readers
+//!
+//! [`GpuStatsProvider`] covers the reads a dashboard needs from any GPU:
+//! a stats sample, the GPU's identity and what it can report. [`IntelGpu`]
+//! implements it, and readers for other vendors can implement it too, so
+//! monitoring code can be written once for all of them:
+//!
+//! ```rust,no_run
+//! use intel_gpu_stats::{GpuStatsProvider, IntelGpu};
+//!
+//! fn print_all(gpus: &mut [Box<dyn GpuStatsProvider>]) -> intel_gpu_stats::Result<()> {
+//!     for gpu in gpus {
+//!         let stats = gpu.read_stats()?;
+//!         println!("{}: {:.1}%", gpu.gpu_info().id, stats.engines.max_utilization());
+//!     }
+//!     Ok(())
+//! }
+//!
+//! let mut gpus: Vec<Box<dyn GpuStatsProvider>> = vec![Box::new(IntelGpu::detect()?)];
+//! print_all(&mut gpus)?;
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+//!
+//! [`IntelGpu`]: crate::IntelGpu
+
+use crate::error::Result;
+use crate::types::{Capabilities, GpuInfo, GpuStats};
+
+/// A source of [`GpuStats`] samples for one GPU
+///
+/// The trait is object safe, so readers of different types can be kept
+/// together as `Box<dyn GpuStatsProvider>`.
+pub trait GpuStatsProvider {
+    /// Read a stats sample covering the time since the previous read
+    fn read_stats(&mut self) -> Result<GpuStats>;
+
+    /// Identity of the GPU being read
+    fn gpu_info(&self) -> &GpuInfo;
+
+    /// What the reader can report for this GPU
+    fn capabilities(&self) -> Capabilities;
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl GpuStatsProvider for crate::IntelGpu {
+    fn read_stats(&mut self) -> Result<GpuStats> {
+        crate::IntelGpu::read_stats(self)
+    }
+
+    fn gpu_info(&self) -> &GpuInfo {
+        crate::IntelGpu::gpu_info(self)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        crate::IntelGpu::capabilities(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    /// A reader for another vendor's GPU
+    struct FakeGpu {
+        info: GpuInfo,
+        reads: u64,
+    }
+
+    impl GpuStatsProvider for FakeGpu {
+        fn read_stats(&mut self) -> Result<GpuStats> {
+            self.reads += 1;
+            Ok(GpuStats::new(Instant::now(), self.reads * 1_000))
+        }
+
+        fn gpu_info(&self) -> &GpuInfo {
+            &self.info
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                render: true,
+                ..Default::default()
+            }
+        }
+    }
+
+    #[test]
+    fn test_dyn_provider() {
+        let info = GpuInfo {
+            id: "nv0".into(),
+            pci_path: String::new(),
+            device_name: None,
+            vendor_id: 0x10de,
+            device_id: 0x2684,
+            render_node: None,
+            card_node: None,
+            driver: None,
+            driver_name: None,
+            pcie_link_speed: None,
+            pcie_link_width: None,
+            media_engines: None,
+        };
+        let mut gpus: Vec<Box<dyn GpuStatsProvider>> = vec![Box::new(FakeGpu { info, reads: 0 })];
+        for gpu in &mut gpus {
+            assert!(!gpu.gpu_info().is_intel());
+            assert!(gpu.capabilities().render);
+            gpu.read_stats().unwrap();
+            assert_eq!(gpu.read_stats().unwrap().sample_duration_ns, 2_000);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//! High-level Quick Sync load monitor
+//!
+//! [`QuickSyncMonitor`] wraps an `IntelGpu` and its sampling thread for the
+//! common "show encoder/decoder load" case: start it once, then read the
+//! latest figures from any thread.
+//!
+//! ```rust,no_run
+//! use intel_gpu_stats::QuickSyncMonitor;
+//!
+//! let monitor = QuickSyncMonitor::detect()?;
+//! // ... later, e.g. from a UI thread ...
+//! println!("Encode: {:.1}%", monitor.encode_percent());
+//! println!("Decode: {:.1}%", monitor.decode_percent());
+//! for client in monitor.active_clients() {
+//!     println!("{} (PID {})", client.name, client.pid);
+//! }
+//! if monitor.is_saturated() {
+//!     println!("Quick Sync is saturated");
+//! }
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::sampling::SamplingHandle;
+use crate::types::{DrmClient, GpuStats};
+use crate::IntelGpu;
+
+/// Default sampling interval of a [`QuickSyncMonitor`]
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default Quick Sync utilization at or above which the monitor is saturated
+pub const DEFAULT_SATURATION_PERCENT: f64 = 90.0;
+
+/// Latest figures published by the sampling thread
+#[derive(Debug, Default)]
+struct Shared {
+    stats: Option<GpuStats>,
+    clients: Vec<DrmClient>,
+}
+
+/// Background monitor of Quick Sync (video and video enhance) load
+///
+/// Samples the GPU and its DRM clients every interval on a background
+/// thread. The getters return the most recent sample and never block on the
+/// GPU; before the first sample completes they report zero load and no
+/// clients. Sampling stops when the monitor is dropped.
+pub struct QuickSyncMonitor {
+    shared: Arc<Mutex<Shared>>,
+    handle: SamplingHandle,
+    saturation_percent: f64,
+}
+
+impl QuickSyncMonitor {
+    /// Detect the first Intel GPU and monitor it at [`DEFAULT_INTERVAL`]
+    pub fn detect() -> Result<Self> {
+        Self::start(IntelGpu::detect()?, DEFAULT_INTERVAL)
+    }
+
+    /// Monitor an opened GPU, sampling every `interval`
+    ///
+    /// Each sample also scans the system's DRM clients, so very short
+    /// intervals cost noticeably more than plain `start_sampling`. Only
+    /// clients of this GPU are kept, see [`active_clients`](Self::active_clients).
+    pub fn start(gpu: IntelGpu, interval: Duration) -> Result<Self> {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let thread_shared = shared.clone();
+        let mut previous_clients = HashMap::new();
+        let card_id = gpu.gpu_info().id.clone();
+
+        let handle = gpu.start_sampling(interval, move |stats| {
+            let clients = clients_on_card(IntelGpu::list_drm_clients(), &card_id);
+            let clients = active_quicksync_clients(&mut previous_clients, clients);
+            let mut shared = lock(&thread_shared);
+            shared.stats = Some(stats);
+            shared.clients = clients;
+        })?;
+
+        Ok(Self {
+            shared,
+            handle,
+            saturation_percent: DEFAULT_SATURATION_PERCENT,
+        })
+    }
+
+    /// Set the utilization at or above which [`is_saturated`](Self::is_saturated) reports true
+    ///
+    /// Defaults to [`DEFAULT_SATURATION_PERCENT`].
+    pub fn with_saturation_percent(mut self, percent: f64) -> Self {
+        self.saturation_percent = percent;
+        self
+    }
+
+    /// Encoder (video enhance engine) busy percentage of the latest sample
+    pub fn encode_percent(&self) -> f64 {
+        self.read(|stats| stats.engines.video_enhance.busy_percent)
+    }
+
+    /// Decoder (video engine) busy percentage of the latest sample
+    pub fn decode_percent(&self) -> f64 {
+        self.read(|stats| stats.engines.video.busy_percent)
+    }
+
+    /// Combined Quick Sync utilization of the latest sample
+    ///
+    /// See [`EngineStats::quicksync_utilization`](crate::EngineStats::quicksync_utilization).
+    pub fn quicksync_percent(&self) -> f64 {
+        self.read(|stats| stats.engines.quicksync_utilization())
+    }
+
+    /// Processes that used the video or video enhance engines during the latest sample
+    ///
+    /// Engine times are the usage within that sample, not since the process
+    /// started. Clients of other GPUs are left out; on Windows, where usage
+    /// is not split by adapter, clients of all GPUs are listed.
+    pub fn active_clients(&self) -> Vec<DrmClient> {
+        lock(&self.shared).clients.clone()
+    }
+
+    /// Whether Quick Sync utilization reached the saturation threshold
+    pub fn is_saturated(&self) -> bool {
+        self.quicksync_percent() >= self.saturation_percent
+    }
+
+    /// The full stats of the latest sample, if one completed yet
+    pub fn latest_stats(&self) -> Option<GpuStats> {
+        lock(&self.shared).stats.clone()
+    }
+
+    /// Check if the sampling thread is still running
+    pub fn is_running(&self) -> bool {
+        self.handle.is_running()
+    }
+
+    /// Stop monitoring
+    ///
+    /// Equivalent to dropping the monitor.
+    pub fn stop(self) {
+        self.handle.stop();
+    }
+
+    fn read(&self, value: impl Fn(&GpuStats) -> f64) -> f64 {
+        lock(&self.shared).stats.as_ref().map_or(0.0, value)
+    }
+}
+
+/// Lock the shared figures, recovering them if a holder panicked
+fn lock(shared: &Mutex<Shared>) -> MutexGuard<'_, Shared> {
+    shared
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Keep the clients of one card, and those whose card is unknown
+///
+/// Windows does not attribute clients to an adapter (`card_id` is `None`),
+/// so they are all kept.
+fn clients_on_card(clients: Vec<DrmClient>, card_id: &str) -> Vec<DrmClient> {
+    clients
+        .into_iter()
+        .filter(|client| client.card_id.as_deref().is_none_or(|id| id == card_id))
+        .collect()
+}
+
+/// Clients that used Quick Sync since the previous scan
+///
+/// `previous` holds the cumulative counters of the last scan by PID and card
+/// and is replaced with `clients`. Clients first seen in this scan are
+/// compared against zero.
+fn active_quicksync_clients(
+    previous: &mut HashMap<(u32, Option<String>), DrmClient>,
+    clients: Vec<DrmClient>,
+) -> Vec<DrmClient> {
+    let active = clients
+        .iter()
+        .map(
+            |client| match previous.get(&(client.pid, client.card_id.clone())) {
+                Some(earlier) => client.since(earlier),
+                None => client.clone(),
+            },
+        )
+        .filter(DrmClient::is_using_quicksync)
+        .collect();
+    *previous = clients
+        .into_iter()
+        .map(|client| ((client.pid, client.card_id.clone()), client))
+        .collect();
+    active
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(pid: u32, video_ns: u64) -> DrmClient {
+        let mut client = DrmClient::new(pid, format!("proc{}", pid));
+        client.video_ns = video_ns;
+        client
+    }
+
+    #[test]
+    fn test_clients_on_card() {
+        let on = |pid, card_id: Option<&str>| DrmClient {
+            card_id: card_id.map(String::from),
+            ..client(pid, 100)
+        };
+        let clients = clients_on_card(
+            vec![on(1, Some("card0")), on(2, Some("card1")), on(3, None)],
+            "card1",
+        );
+        let pids: Vec<u32> = clients.iter().map(|client| client.pid).collect();
+        assert_eq!(pids, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_active_quicksync_clients() {
+        let mut previous = HashMap::new();
+
+        let active = active_quicksync_clients(&mut previous, vec![client(1, 100), client(2, 0)]);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].pid, 1);
+
+        // pid 1 went idle, pid 2 started decoding
+        let active = active_quicksync_clients(&mut previous, vec![client(1, 100), client(2, 50)]);
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].pid, 2);
+        assert_eq!(active[0].video_ns, 50);
+    }
+}
@@ -0,0 +1,320 @@
+//! Recording and replay of sampling sessions
+//!
+//! A [`Recorder`] writes timestamped [`GpuStats`] samples as JSON Lines, one
+//! sample per line, and a [`Player`] reads such a file back and replays it
+//! into a callback with the same signature as `IntelGpu::start_sampling`.
+//!
+//! This is useful for sharing a problematic run for bug reports and for
+//! testing UIs against real traces without an Intel GPU present.
+//!
+//! For log ingestion (Loki, Vector, `tail -f`), a [`JsonLinesWriter`] writes
+//! the plain [`GpuStats`] of each sample as one compact JSON object per line.
+//!
+//! Requires the `json` feature.
+//!
+//! ```rust,no_run
+//! use intel_gpu_stats::record::{Player, Recorder};
+//! use intel_gpu_stats::IntelGpu;
+//! use std::fs::File;
+//! use std::time::Duration;
+//!
+//! // Record ten seconds of samples
+//! let gpu = IntelGpu::detect()?;
+//! let file = File::create("session.jsonl")?;
+//! let handle = Recorder::start(gpu, Duration::from_millis(100), file, |_| {})?;
+//! std::thread::sleep(Duration::from_secs(10));
+//! handle.stop();
+//!
+//! // Replay it at 4x speed
+//! let player = Player::open("session.jsonl")?.with_speed(4.0)?;
+//! player.play(|stats| println!("Video: {:.1}%", stats.engines.video.busy_percent));
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::types::GpuStats;
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::{IntelGpu, SamplingHandle};
+
+/// A single recorded sample
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedSample {
+    /// Time since the start of the recording, in nanoseconds
+    pub offset_ns: u64,
+    /// The recorded statistics
+    pub stats: GpuStats,
+}
+
+/// Writes [`GpuStats`] samples to a JSON Lines stream
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    writer: W,
+    started: Option<Instant>,
+}
+
+impl<W: Write> Recorder<W> {
+    /// Create a recorder writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            started: None,
+        }
+    }
+
+    /// Append a sample to the recording
+    ///
+    /// The first recorded sample defines offset zero. Each sample is flushed
+    /// immediately so a recording survives the process being killed.
+    pub fn record(&mut self, stats: &GpuStats) -> Result<()> {
+        let started = *self.started.get_or_insert(stats.timestamp);
+        let offset = stats.timestamp.saturating_duration_since(started);
+
+        let sample = RecordedSample {
+            offset_ns: offset.as_nanos() as u64,
+            stats: stats.clone(),
+        };
+
+        serde_json::to_writer(&mut self.writer, &sample).map_err(|e| Error::Io {
+            context: "Failed to write recorded sample".into(),
+            source: e.into(),
+        })?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Consume the recorder and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl<W: Write + Send + 'static> Recorder<W> {
+    /// Start a sampling session that records every sample to `writer`
+    ///
+    /// Each sample is also passed to `callback`, so recording can run
+    /// alongside normal live processing.
+    pub fn start<F>(
+        gpu: IntelGpu,
+        interval: Duration,
+        writer: W,
+        mut callback: F,
+    ) -> Result<SamplingHandle>
+    where
+        F: FnMut(GpuStats) + Send + 'static,
+    {
+        let mut recorder = Recorder::new(writer);
+        gpu.start_sampling(interval, move |stats| {
+            if let Err(e) = recorder.record(&stats) {
+                crate::logging::warn(format_args!("Error recording GPU stats: {}", e));
+            }
+            callback(stats);
+        })
+    }
+}
+
+/// Writes [`GpuStats`] samples as newline-delimited JSON (JSON Lines)
+///
+/// Each sample becomes one compact JSON object on its own line and is
+/// flushed immediately, so the output can be tailed while it is written.
+/// Unlike [`Recorder`], no recording offset is added around the stats.
+///
+/// ```rust,no_run
+/// use intel_gpu_stats::record::JsonLinesWriter;
+/// use intel_gpu_stats::IntelGpu;
+/// use std::time::Duration;
+///
+/// let gpu = IntelGpu::detect()?;
+/// let mut writer = JsonLinesWriter::new(std::io::stdout());
+/// let handle = gpu.start_sampling(Duration::from_secs(1), move |stats| {
+///     if let Err(e) = writer.write(&stats) {
+///         eprintln!("Error writing GPU stats: {}", e);
+///     }
+/// })?;
+/// # handle.stop();
+/// # Ok::<(), intel_gpu_stats::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Create a writer writing to `writer`
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write one sample as a single line and flush it
+    pub fn write(&mut self, stats: &GpuStats) -> Result<()> {
+        serde_json::to_writer(&mut self.writer, stats).map_err(|e| Error::Io {
+            context: "Failed to write GPU stats".into(),
+            source: e.into(),
+        })?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Consume the writer and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Replays a recorded session
+#[derive(Debug, Clone)]
+pub struct Player {
+    samples: Vec<RecordedSample>,
+    speed: f64,
+}
+
+impl Player {
+    /// Load a recording from a file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| Error::Io {
+            context: format!("Failed to open recording {}", path.display()),
+            source: e,
+        })?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// Load a recording from any buffered reader
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self> {
+        let mut samples = Vec::new();
+
+        for (line_no, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let sample: RecordedSample =
+                serde_json::from_str(&line).map_err(|e| Error::Decode {
+                    message: format!("line {}: {}", line_no + 1, e),
+                })?;
+            samples.push(sample);
+        }
+
+        Ok(Self {
+            samples,
+            speed: 1.0,
+        })
+    }
+
+    /// Set the playback speed (1.0 = original timing, 2.0 = twice as fast)
+    pub fn with_speed(mut self, speed: f64) -> Result<Self> {
+        if !(speed.is_finite() && speed > 0.0) {
+            return Err(Error::InvalidConfig {
+                message: format!("playback speed must be positive, got {}", speed),
+            });
+        }
+        self.speed = speed;
+        Ok(self)
+    }
+
+    /// Get the recorded samples
+    pub fn samples(&self) -> &[RecordedSample] {
+        &self.samples
+    }
+
+    /// Total duration of the recording at original speed
+    pub fn duration(&self) -> Duration {
+        self.samples
+            .last()
+            .map(|s| Duration::from_nanos(s.offset_ns))
+            .unwrap_or_default()
+    }
+
+    /// Replay all samples into `callback`, blocking until done
+    ///
+    /// Samples are delivered with their original spacing divided by the
+    /// playback speed. Each delivered sample is re-stamped with the current
+    /// time so consumers see a live-looking stream.
+    pub fn play<F>(&self, mut callback: F)
+    where
+        F: FnMut(GpuStats),
+    {
+        let start = Instant::now();
+
+        for sample in &self.samples {
+            let due = Duration::from_nanos(sample.offset_ns).div_f64(self.speed);
+            let elapsed = start.elapsed();
+            if due > elapsed {
+                thread::sleep(due - elapsed);
+            }
+
+            let mut stats = sample.stats.clone();
+            stats.timestamp = Instant::now();
+            callback(stats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_replay_roundtrip() {
+        let base = Instant::now();
+        let mut recorder = Recorder::new(Vec::new());
+
+        for i in 0..3u64 {
+            let mut stats = GpuStats::new(base + Duration::from_millis(i * 10), 10_000_000);
+            stats.engines.video.busy_percent = i as f64 * 10.0;
+            recorder.record(&stats).unwrap();
+        }
+
+        let data = recorder.into_inner();
+        assert_eq!(data.iter().filter(|&&b| b == b'\n').count(), 3);
+
+        let player = Player::from_reader(&data[..])
+            .unwrap()
+            .with_speed(100.0)
+            .unwrap();
+        assert_eq!(player.samples().len(), 3);
+        assert_eq!(player.duration(), Duration::from_millis(20));
+
+        let mut replayed = Vec::new();
+        player.play(|stats| replayed.push(stats.engines.video.busy_percent));
+        assert_eq!(replayed, vec![0.0, 10.0, 20.0]);
+    }
+
+    #[test]
+    fn test_json_lines_writer() {
+        let mut writer = JsonLinesWriter::new(Vec::new());
+        for i in 0..2u64 {
+            let mut stats = GpuStats::new(Instant::now(), 10_000_000);
+            stats.engines.video.busy_percent = i as f64 * 10.0;
+            writer.write(&stats).unwrap();
+        }
+
+        let data = String::from_utf8(writer.into_inner()).unwrap();
+        let lines: Vec<&str> = data.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (i, line) in lines.iter().enumerate() {
+            let stats: GpuStats = serde_json::from_str(line).unwrap();
+            assert_eq!(stats.engines.video.busy_percent, i as f64 * 10.0);
+        }
+    }
+
+    #[test]
+    fn test_player_rejects_invalid_input() {
+        assert!(Player::from_reader(&b"not json\n"[..]).is_err());
+
+        let player = Player::from_reader(&b""[..]).unwrap();
+        assert!(player.clone().with_speed(0.0).is_err());
+        assert!(player.with_speed(f64::NAN).is_err());
+    }
+}
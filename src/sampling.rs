@@ -0,0 +1,733 @@
+//! Background sampling shared by the platform implementations
+//!
+//! [`SamplingOptions`] configures `IntelGpu::start_sampling_with`, and
+//! [`SamplingHandle`] controls the resulting sampling thread and reports how
+//! well it keeps up ([`SamplingTimingStats`]). Besides the callback, any
+//! number of consumers can receive the samples through
+//! [`SamplingHandle::subscribe`], so the GPU is read once for all of them.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::logging;
+use crate::throttle_history::{lock_shared, ThrottleTracker};
+use crate::types::*;
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+use crate::IntelGpu;
+
+/// How often `IntelGpu::wait_until_busy` reads the GPU
+pub const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Time a sampling thread gets to exit beyond one sampling interval
+///
+/// The thread only checks for a stop request between samples, so stopping
+/// waits for the current interval plus this grace period by default. See
+/// [`SamplingHandle::with_stop_timeout`].
+pub const DEFAULT_STOP_GRACE: Duration = Duration::from_secs(1);
+
+/// How often a stopping `SamplingHandle` checks whether its thread exited
+const JOIN_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Samples a subscriber may fall behind before it misses samples
+///
+/// See [`SamplingHandle::subscribe`].
+pub const SUBSCRIBER_BUFFER: usize = 64;
+
+/// Options for background sampling
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SamplingOptions {
+    /// How often stats are delivered to the callback
+    pub interval: Duration,
+    /// Optional faster polling rate within each interval
+    ///
+    /// When set, the GPU is read every `inner_interval` and the delivered
+    /// stats are aggregated over the inner samples: utilization is the mean
+    /// over the interval and [`EngineUtilization::peak_busy_percent`] holds the
+    /// highest inner reading, which catches short bursts a long average
+    /// smooths away.
+    pub inner_interval: Option<Duration>,
+}
+
+impl SamplingOptions {
+    /// Sample at the given interval without inner polling
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            inner_interval: None,
+        }
+    }
+
+    /// Poll at `inner_interval` within each interval to track peaks
+    pub fn with_inner_interval(mut self, inner_interval: Duration) -> Self {
+        self.inner_interval = Some(inner_interval);
+        self
+    }
+
+    /// Check that the options are consistent
+    pub(crate) fn validate(&self) -> Result<()> {
+        if let Some(inner) = self.inner_interval {
+            if inner.is_zero() || inner > self.interval {
+                return Err(Error::InvalidConfig {
+                    message: format!(
+                        "inner interval {:?} must be non-zero and no longer than the interval {:?}",
+                        inner, self.interval
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How well a sampling thread keeps up with its interval
+///
+/// Intervals are measured between consecutive deliveries to the callback,
+/// so they include the time the callback itself takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SamplingTimingStats {
+    /// Number of samples delivered so far
+    pub samples: u64,
+    /// Mean time between deliveries (the first one is timed from the start)
+    pub mean_interval: Duration,
+    /// Longest time between deliveries
+    pub max_interval: Duration,
+    /// Whole intervals that passed without a delivery
+    pub missed_ticks: u64,
+}
+
+/// Delivery timing shared between a sampling thread and its handle
+pub(crate) struct SamplingTimer {
+    interval: Duration,
+    state: Mutex<TimerState>,
+}
+
+struct TimerState {
+    last_delivery: Instant,
+    total: Duration,
+    stats: SamplingTimingStats,
+}
+
+impl SamplingTimer {
+    /// Start timing deliveries expected every `interval`
+    pub(crate) fn new(interval: Duration) -> Arc<Self> {
+        Arc::new(Self {
+            interval,
+            state: Mutex::new(TimerState {
+                last_delivery: Instant::now(),
+                total: Duration::ZERO,
+                stats: SamplingTimingStats::default(),
+            }),
+        })
+    }
+
+    /// Record a delivery at `now`
+    pub(crate) fn record(&self, now: Instant) {
+        let mut state = self.lock();
+        let elapsed = now.saturating_duration_since(state.last_delivery);
+        state.last_delivery = now;
+        state.total += elapsed;
+        let total = state.total;
+
+        let stats = &mut state.stats;
+        stats.samples += 1;
+        stats.mean_interval =
+            Duration::from_nanos((total.as_nanos() / stats.samples as u128) as u64);
+        stats.max_interval = stats.max_interval.max(elapsed);
+        stats.missed_ticks += missed_ticks(elapsed, self.interval);
+    }
+
+    fn stats(&self) -> SamplingTimingStats {
+        self.lock().stats
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TimerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Number of whole intervals that fit in a gap beyond the expected one
+fn missed_ticks(elapsed: Duration, interval: Duration) -> u64 {
+    if interval.is_zero() {
+        return 0;
+    }
+    (elapsed.as_nanos() / interval.as_nanos()).saturating_sub(1) as u64
+}
+
+/// Channels receiving every sample of a sampling thread
+///
+/// `None` once the thread has exited, or for threads that deliver no
+/// stats, so new subscribers see a disconnected channel right away.
+pub(crate) struct Subscribers {
+    senders: Mutex<Option<Vec<SyncSender<Arc<GpuStats>>>>>,
+}
+
+impl Subscribers {
+    /// Subscribers of a thread that delivers stats
+    pub(crate) fn new() -> Arc<Self> {
+        Arc::new(Self {
+            senders: Mutex::new(Some(Vec::new())),
+        })
+    }
+
+    /// Subscribers of a thread that delivers no stats
+    fn closed() -> Arc<Self> {
+        Arc::new(Self {
+            senders: Mutex::new(None),
+        })
+    }
+
+    fn subscribe(&self) -> Receiver<Arc<GpuStats>> {
+        let (sender, receiver) = mpsc::sync_channel(SUBSCRIBER_BUFFER);
+        if let Some(senders) = self.lock().as_mut() {
+            senders.push(sender);
+        }
+        receiver
+    }
+
+    /// Send a sample to every subscriber
+    ///
+    /// The stats are only cloned if someone subscribed. Subscribers whose
+    /// buffer is full miss this sample; those that hung up are removed.
+    pub(crate) fn publish(&self, stats: &GpuStats) {
+        let mut senders = self.lock();
+        let Some(senders) = senders.as_mut().filter(|senders| !senders.is_empty()) else {
+            return;
+        };
+        let stats = Arc::new(stats.clone());
+        senders.retain(|sender| {
+            !matches!(
+                sender.try_send(stats.clone()),
+                Err(TrySendError::Disconnected(_))
+            )
+        });
+    }
+
+    /// Disconnect all subscribers once the thread exits
+    pub(crate) fn close(&self) {
+        *self.lock() = None;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Vec<SyncSender<Arc<GpuStats>>>>> {
+        self.senders
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Handle for controlling background sampling
+///
+/// Stopping (explicitly or by dropping the handle) waits a bounded time for
+/// the sampling thread to exit. A thread still running after that, e.g.
+/// because the callback is blocked, is detached so shutdown never hangs.
+pub struct SamplingHandle {
+    stop_flag: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    throttle_history: Arc<Mutex<ThrottleTracker>>,
+    timer: Arc<SamplingTimer>,
+    subscribers: Arc<Subscribers>,
+    stop_timeout: Duration,
+}
+
+impl SamplingHandle {
+    /// The timer's interval is how long the thread may go without checking
+    /// the stop flag
+    pub(crate) fn new(
+        stop_flag: Arc<AtomicBool>,
+        thread: JoinHandle<()>,
+        throttle_history: Arc<Mutex<ThrottleTracker>>,
+        timer: Arc<SamplingTimer>,
+    ) -> Self {
+        Self {
+            stop_flag,
+            thread: Some(thread),
+            throttle_history,
+            stop_timeout: timer.interval.saturating_add(DEFAULT_STOP_GRACE),
+            timer,
+            subscribers: Subscribers::closed(),
+        }
+    }
+
+    /// Also deliver the thread's samples to `subscribers`
+    pub(crate) fn with_subscribers(mut self, subscribers: Arc<Subscribers>) -> Self {
+        self.subscribers = subscribers;
+        self
+    }
+
+    /// Set how long stopping waits for the sampling thread to exit
+    ///
+    /// Defaults to one sampling interval plus [`DEFAULT_STOP_GRACE`]. If the
+    /// thread has not exited by then, it is detached with a warning and will
+    /// exit on its own once the callback returns.
+    pub fn with_stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+
+    /// Percentage of the last `window` each throttle reason was active
+    ///
+    /// See `IntelGpu::throttle_residency`.
+    pub fn throttle_residency(&self, window: Duration) -> HashMap<ThrottleReason, f64> {
+        lock_shared(&self.throttle_history).residency(window)
+    }
+
+    /// Receive every sample from now on, alongside the callback
+    ///
+    /// Can be called any number of times, e.g. once for a logger and once
+    /// for a UI; the GPU is still read only once per interval. Each
+    /// subscriber buffers up to [`SUBSCRIBER_BUFFER`] samples and misses
+    /// samples while its buffer is full rather than growing without bound.
+    /// The channel disconnects when sampling stops. Handles from
+    /// `watch_throttle` deliver no stats, so their channels are
+    /// disconnected from the start.
+    ///
+    /// ```rust,no_run
+    /// use intel_gpu_stats::IntelGpu;
+    /// use std::time::Duration;
+    ///
+    /// let gpu = IntelGpu::detect()?;
+    /// let handle = gpu.start_sampling(Duration::from_secs(1), |_| {})?;
+    /// let logger = handle.subscribe();
+    /// let ui = handle.subscribe();
+    /// std::thread::spawn(move || {
+    ///     for stats in logger {
+    ///         println!("Render: {:.1}%", stats.engines.render.busy_percent);
+    ///     }
+    /// });
+    /// let first = ui.recv().unwrap();
+    /// # Ok::<(), intel_gpu_stats::Error>(())
+    /// ```
+    pub fn subscribe(&self) -> Receiver<Arc<GpuStats>> {
+        self.subscribers.subscribe()
+    }
+
+    /// Timing of the samples delivered so far
+    ///
+    /// A mean interval well above the requested one, or missed ticks, mean
+    /// the callback is too slow or the system is overloaded.
+    pub fn timing_stats(&self) -> SamplingTimingStats {
+        self.timer.stats()
+    }
+
+    /// Stop the background sampling
+    ///
+    /// Waits at most the stop timeout for the thread to exit; see
+    /// [`with_stop_timeout`](Self::with_stop_timeout).
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    /// Check if sampling is still running
+    pub fn is_running(&self) -> bool {
+        !self.stop_flag.load(Ordering::SeqCst)
+    }
+}
+
+impl SamplingHandle {
+    fn shutdown(&mut self) {
+        self.stop_flag.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            if !join_within(thread, self.stop_timeout) {
+                logging::warn(format_args!(
+                    "Sampling thread did not stop within {:?}, detaching it",
+                    self.stop_timeout
+                ));
+            }
+        }
+    }
+}
+
+impl Drop for SamplingHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// Join a thread if it exits within `timeout`, otherwise detach it
+///
+/// Returns whether the thread was joined.
+fn join_within(thread: JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !thread.is_finished() {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            // Dropping the handle detaches the thread
+            return false;
+        }
+        thread::sleep(remaining.min(JOIN_POLL_INTERVAL));
+    }
+    let _ = thread.join();
+    true
+}
+
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+impl IntelGpu {
+    /// Block until an engine's busy percentage exceeds `threshold`
+    ///
+    /// The GPU is read every [`WAIT_POLL_INTERVAL`] until the primary engine
+    /// of `engine` is busier than `threshold` percent or `timeout` elapses.
+    /// Returns `Ok(true)` if the engine became busy and `Ok(false)` on
+    /// timeout. Always `Ok(false)` if the GPU has no engine of that class.
+    ///
+    /// Useful for synchronizing a capture to the start of a workload.
+    pub fn wait_until_busy(
+        &mut self,
+        engine: EngineClass,
+        threshold: f64,
+        timeout: Duration,
+    ) -> Result<bool> {
+        // Start a fresh measurement window so activity from before the call
+        // does not count
+        self.read_stats()?;
+        poll_until_busy(engine, threshold, timeout, WAIT_POLL_INTERVAL, || {
+            self.read_stats()
+        })
+    }
+}
+
+/// Read stats every `poll` until `engine` exceeds `threshold` or `timeout` elapses
+fn poll_until_busy(
+    engine: EngineClass,
+    threshold: f64,
+    timeout: Duration,
+    poll: Duration,
+    mut read: impl FnMut() -> Result<GpuStats>,
+) -> Result<bool> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+        thread::sleep(remaining.min(poll));
+        let stats = read()?;
+        if stats
+            .engines
+            .get(engine)
+            .is_some_and(|e| e.busy_percent > threshold)
+        {
+            return Ok(true);
+        }
+    }
+}
+
+/// Read stats every `inner` until `interval` has passed and aggregate them
+pub(crate) fn read_window(
+    interval: Duration,
+    inner: Duration,
+    mut read: impl FnMut() -> Result<GpuStats>,
+) -> Result<GpuStats> {
+    let deadline = Instant::now() + interval;
+    let mut samples = Vec::new();
+    loop {
+        thread::sleep(
+            deadline
+                .saturating_duration_since(Instant::now())
+                .min(inner),
+        );
+        samples.push(read()?);
+        if Instant::now() >= deadline {
+            break;
+        }
+    }
+    Ok(aggregate(samples))
+}
+
+/// Combine consecutive samples into one covering their whole duration
+///
+/// Counter-derived values (engines, frequency, RC6, power, memory
+/// bandwidth) are averaged weighted by sample duration; sensor readings
+/// (temperature, throttle, memory, performance state) come from the latest
+/// sample. `samples` must not be empty.
+fn aggregate(samples: Vec<GpuStats>) -> GpuStats {
+    let weights: Vec<f64> = samples
+        .iter()
+        .map(|s| s.sample_duration_ns.max(1) as f64)
+        .collect();
+    let mean = |value: &dyn Fn(&GpuStats) -> f64| -> f64 {
+        let total: f64 = weights.iter().sum();
+        samples
+            .iter()
+            .zip(&weights)
+            .map(|(s, w)| value(s) * w)
+            .sum::<f64>()
+            / total
+    };
+    let all = |present: &dyn Fn(&GpuStats) -> bool| samples.iter().all(present);
+
+    let last = samples.last().expect("at least one sample");
+    let mut stats = last.clone();
+    stats.sample_duration_ns = samples.iter().map(|s| s.sample_duration_ns).sum();
+
+    let engines: Vec<&EngineStats> = samples.iter().map(|s| &s.engines).collect();
+    stats.engines.render = mean_engine(&engines, &weights, |e| Some(&e.render));
+    stats.engines.video = mean_engine(&engines, &weights, |e| Some(&e.video));
+    stats.engines.video_enhance = mean_engine(&engines, &weights, |e| Some(&e.video_enhance));
+    stats.engines.blitter = mean_engine(&engines, &weights, |e| Some(&e.blitter));
+    if all(&|s| s.engines.compute.is_some()) {
+        stats.engines.compute = Some(mean_engine(&engines, &weights, |e| e.compute.as_ref()));
+    }
+    let video_count = last.engines.video_instances.len();
+    if all(&|s| s.engines.video_instances.len() == video_count) {
+        stats.engines.video_instances = (0..video_count)
+            .map(|i| mean_engine(&engines, &weights, |e| e.video_instances.get(i)))
+            .collect();
+    }
+    let enhance_count = last.engines.video_enhance_instances.len();
+    if all(&|s| s.engines.video_enhance_instances.len() == enhance_count) {
+        stats.engines.video_enhance_instances = (0..enhance_count)
+            .map(|i| mean_engine(&engines, &weights, |e| e.video_enhance_instances.get(i)))
+            .collect();
+    }
+    let compute_count = last.engines.compute_instances.len();
+    if all(&|s| s.engines.compute_instances.len() == compute_count) {
+        stats.engines.compute_instances = (0..compute_count)
+            .map(|i| mean_engine(&engines, &weights, |e| e.compute_instances.get(i)))
+            .collect();
+    }
+
+    stats.frequency = FrequencyStats::new(
+        mean(&|s| s.frequency.actual_mhz as f64).round() as u32,
+        mean(&|s| s.frequency.requested_mhz as f64).round() as u32,
+    )
+    .with_min_mhz(last.frequency.min_mhz)
+    .with_media_freq_factor(last.frequency.media_freq_factor);
+    if all(&|s| s.frequency.media_mhz.is_some()) {
+        stats.frequency.media_mhz =
+            Some(mean(&|s| s.frequency.media_mhz.unwrap_or(0) as f64).round() as u32);
+    }
+    if all(&|s| s.rc6.is_some()) {
+        stats.rc6 = Some(Rc6Stats::new(mean(&|s| {
+            s.rc6.as_ref().map_or(0.0, |rc6| rc6.residency_percent)
+        })));
+    }
+    if all(&|s| s.system_memory_bandwidth_gbps.is_some()) {
+        stats.system_memory_bandwidth_gbps =
+            Some(mean(&|s| s.system_memory_bandwidth_gbps.unwrap_or(0.0)));
+    }
+    if all(&|s| s.power.is_some()) {
+        // Averaged only if every sample has the reading
+        let optional_mean = |select: &dyn Fn(&PowerStats) -> Option<f64>| {
+            all(&|s| s.power.as_ref().and_then(select).is_some())
+                .then(|| mean(&|s| s.power.as_ref().and_then(select).unwrap_or(0.0)))
+        };
+        let mut power = PowerStats::new(
+            mean(&|s| s.power.as_ref().map_or(0.0, |p| p.gpu_watts)),
+            optional_mean(&|p| p.package_watts),
+        );
+        power.platform_watts = optional_mean(&|p| p.platform_watts);
+        let last_domains = &last.power.as_ref().expect("power present").domains;
+        power.domains = last_domains.clone();
+        let same_domains = |s: &GpuStats| {
+            s.power.as_ref().is_some_and(|p| {
+                p.domains.len() == last_domains.len()
+                    && p.domains.iter().zip(last_domains).all(|(a, b)| a.0 == b.0)
+            })
+        };
+        if all(&same_domains) {
+            for (i, domain) in power.domains.iter_mut().enumerate() {
+                domain.1 = mean(&|s| s.power.as_ref().map_or(0.0, |p| p.domains[i].1));
+            }
+        }
+        stats.power = Some(power);
+    }
+
+    stats
+}
+
+/// Duration-weighted mean of one engine across samples, keeping the peak
+///
+/// Busy nanoseconds and context switches are summed, since they cover
+/// consecutive windows. Wait and semaphore percentages are only averaged if
+/// every sample measured them.
+fn mean_engine<'a>(
+    engines: &[&'a EngineStats],
+    weights: &[f64],
+    select: impl Fn(&'a EngineStats) -> Option<&'a EngineUtilization>,
+) -> EngineUtilization {
+    let mut total = EngineUtilization::new(0.0, 0.0, 0.0);
+    let mut total_weight = 0.0;
+    for (engine, weight) in engines.iter().zip(weights) {
+        if let Some(engine) = select(engine) {
+            total.busy_percent += engine.busy_percent * weight;
+            total.wait_percent = total
+                .wait_percent
+                .zip(engine.wait_percent)
+                .map(|(total, wait)| total + wait * weight);
+            total.sema_percent = total
+                .sema_percent
+                .zip(engine.sema_percent)
+                .map(|(total, sema)| total + sema * weight);
+            total.peak_busy_percent = total.peak_busy_percent.max(engine.peak_busy_percent);
+            total.busy_ns += engine.busy_ns;
+            if let Some(switches) = engine.context_switches {
+                *total.context_switches.get_or_insert(0) += switches;
+            }
+            total_weight += weight;
+        }
+    }
+
+    if total_weight > 0.0 {
+        total.busy_percent = clamp_percent(total.busy_percent / total_weight);
+        total.wait_percent = total
+            .wait_percent
+            .map(|wait| clamp_percent(wait / total_weight));
+        total.sema_percent = total
+            .sema_percent
+            .map(|sema| clamp_percent(sema / total_weight));
+    } else {
+        total = EngineUtilization::default();
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(duration_ms: u64, video_busy: f64, mhz: u32) -> GpuStats {
+        let mut stats = GpuStats::new(Instant::now(), duration_ms * 1_000_000);
+        let busy_ns = (video_busy / 100.0 * (duration_ms * 1_000_000) as f64) as u64;
+        stats.engines.video = EngineUtilization::new(video_busy, 0.0, 0.0).with_busy_ns(busy_ns);
+        stats.frequency = FrequencyStats::new(mhz, mhz);
+        stats
+    }
+
+    #[test]
+    fn test_aggregate_mean_and_peak() {
+        let with_power = |mut stats: GpuStats, platform_watts: f64| {
+            stats.power = Some(PowerStats {
+                platform_watts: Some(platform_watts),
+                ..PowerStats::new(5.0, None)
+            });
+            stats
+        };
+        let stats = aggregate(vec![
+            with_power(sample(10, 0.0, 300), 10.0),
+            with_power(sample(10, 100.0, 1300), 10.0),
+            with_power(sample(20, 10.0, 300), 20.0),
+        ]);
+
+        assert_eq!(stats.sample_duration_ns, 40_000_000);
+        assert!((stats.engines.video.busy_percent - 30.0).abs() < 1e-9);
+        assert_eq!(stats.engines.video.peak_busy_percent, 100.0);
+        assert_eq!(stats.engines.video.busy_ns, 12_000_000);
+        assert_eq!(stats.engines.render.peak_busy_percent, 0.0);
+        assert_eq!(stats.frequency.actual_mhz, 550);
+        let power = stats.power.unwrap();
+        assert_eq!(power.gpu_watts, 5.0);
+        assert_eq!(power.package_watts, None);
+        assert_eq!(power.platform_watts, Some(15.0));
+    }
+
+    #[test]
+    fn test_sampling_timer() {
+        let interval = Duration::from_millis(100);
+        let timer = SamplingTimer::new(interval);
+        let start = timer.lock().last_delivery;
+
+        timer.record(start + Duration::from_millis(100));
+        timer.record(start + Duration::from_millis(210));
+        // A slow callback delays the next delivery by more than two intervals
+        timer.record(start + Duration::from_millis(540));
+
+        let stats = timer.stats();
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.mean_interval, Duration::from_millis(180));
+        assert_eq!(stats.max_interval, Duration::from_millis(330));
+        assert_eq!(stats.missed_ticks, 2);
+
+        assert_eq!(missed_ticks(Duration::from_millis(199), interval), 0);
+        assert_eq!(missed_ticks(Duration::from_secs(1), Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn test_join_within() {
+        let finished = thread::spawn(|| {});
+        assert!(join_within(finished, Duration::from_secs(5)));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_clone = stop.clone();
+        let stuck = thread::spawn(move || {
+            while !stop_clone.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+        });
+        assert!(!join_within(stuck, Duration::from_millis(20)));
+        stop.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn test_subscribers() {
+        let subscribers = Subscribers::new();
+        let logger = subscribers.subscribe();
+        let ui = subscribers.subscribe();
+
+        let stats = GpuStats::new(Instant::now(), 1_000);
+        subscribers.publish(&stats);
+        assert_eq!(logger.recv().unwrap().sample_duration_ns, 1_000);
+        assert_eq!(ui.recv().unwrap().sample_duration_ns, 1_000);
+
+        // A full buffer drops samples without disconnecting
+        for _ in 0..SUBSCRIBER_BUFFER + 1 {
+            subscribers.publish(&stats);
+        }
+        assert_eq!(ui.try_iter().count(), SUBSCRIBER_BUFFER);
+
+        drop(logger);
+        subscribers.publish(&stats);
+        assert_eq!(subscribers.lock().as_ref().unwrap().len(), 1);
+
+        subscribers.close();
+        assert!(ui.recv().is_ok());
+        assert!(ui.recv().is_err());
+        assert!(subscribers.subscribe().recv().is_err());
+    }
+
+    #[test]
+    fn test_poll_until_busy() {
+        let poll = Duration::from_millis(1);
+        let mut busy = [0.0, 5.0, 60.0].into_iter();
+        let became_busy = poll_until_busy(
+            EngineClass::Video,
+            50.0,
+            Duration::from_secs(5),
+            poll,
+            || Ok(sample(1, busy.next().unwrap(), 300)),
+        )
+        .unwrap();
+        assert!(became_busy);
+        assert_eq!(busy.next(), None);
+
+        // Times out while idle, and never matches a missing compute engine
+        let timeout = Duration::from_millis(5);
+        let idle = || Ok(sample(1, 0.0, 300));
+        assert!(!poll_until_busy(EngineClass::Video, 50.0, timeout, poll, idle).unwrap());
+        let busy = || Ok(sample(1, 100.0, 300));
+        assert!(!poll_until_busy(EngineClass::Compute, 50.0, timeout, poll, busy).unwrap());
+    }
+
+    #[test]
+    fn test_options_validate() {
+        let interval = Duration::from_millis(500);
+        assert!(SamplingOptions::new(interval).validate().is_ok());
+        assert!(SamplingOptions::new(interval)
+            .with_inner_interval(Duration::from_millis(10))
+            .validate()
+            .is_ok());
+        assert!(SamplingOptions::new(interval)
+            .with_inner_interval(Duration::ZERO)
+            .validate()
+            .is_err());
+        assert!(SamplingOptions::new(interval)
+            .with_inner_interval(Duration::from_secs(1))
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Percentile summaries over a monitoring session
+//!
+//! [`StatsAccumulator`] ingests samples as they arrive and produces a
+//! [`SessionReport`] with min, max, mean and p50/p95/p99 of each engine's
+//! utilization, the GPU frequency, power and temperature, e.g. to attach to
+//! a test run:
+//!
+//! ```rust,no_run
+//! use intel_gpu_stats::{IntelGpu, StatsAccumulator};
+//! use std::time::Duration;
+//!
+//! let mut gpu = IntelGpu::detect()?;
+//! let mut accumulator = StatsAccumulator::new();
+//! for _ in 0..100 {
+//!     std::thread::sleep(Duration::from_millis(100));
+//!     accumulator.add(&gpu.read_stats()?);
+//! }
+//!
+//! let report = accumulator.report();
+//! if let Some(video) = report.video {
+//!     println!("Video p95: {:.1}%", video.p95);
+//! }
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+//!
+//! Memory is bounded: each metric keeps at most `capacity` values. Up to
+//! that many samples the percentiles are exact; beyond it they are computed
+//! from a uniform random sample of the stream (reservoir sampling), while
+//! count, min, max and mean stay exact.
+
+use crate::types::GpuStats;
+
+/// Default number of values kept per metric
+///
+/// At one sample per 100ms this keeps percentiles exact for over 15 minutes.
+pub const DEFAULT_CAPACITY: usize = 10_000;
+
+/// Distribution of one metric over a session
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Percentiles {
+    /// Number of samples
+    pub count: u64,
+    /// Smallest value
+    pub min: f64,
+    /// Largest value
+    pub max: f64,
+    /// Mean value
+    pub mean: f64,
+    /// Median
+    pub p50: f64,
+    /// 95th percentile
+    pub p95: f64,
+    /// 99th percentile
+    pub p99: f64,
+    /// Whether the percentiles are exact, i.e. every sample was kept
+    pub exact: bool,
+}
+
+/// Summary of a session, returned by [`StatsAccumulator::report`]
+///
+/// Engine values are busy percentages. A metric is `None` if no sample
+/// carried it, e.g. `compute` on GPUs without a compute engine.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionReport {
+    /// Number of samples ingested
+    pub samples: u64,
+    /// Total time covered by the samples in nanoseconds
+    pub duration_ns: u64,
+    /// Render/3D engine busy percentage
+    pub render: Option<Percentiles>,
+    /// Video (decode) engine busy percentage
+    pub video: Option<Percentiles>,
+    /// Video enhance (encode) engine busy percentage
+    pub video_enhance: Option<Percentiles>,
+    /// Blitter engine busy percentage
+    pub blitter: Option<Percentiles>,
+    /// Compute engine busy percentage
+    pub compute: Option<Percentiles>,
+    /// Actual GPU frequency in MHz
+    pub frequency_mhz: Option<Percentiles>,
+    /// GPU power in Watts
+    pub gpu_watts: Option<Percentiles>,
+    /// GPU temperature in degrees Celsius
+    pub temperature_celsius: Option<Percentiles>,
+}
+
+/// Bounded sample of one metric, with exact running aggregates
+#[derive(Debug, Clone)]
+struct Reservoir {
+    capacity: usize,
+    values: Vec<f64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    /// xorshift64 state choosing which values to replace once full
+    rng: u64,
+}
+
+impl Reservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            values: Vec::new(),
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            rng: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+
+        if self.values.len() < self.capacity {
+            self.values.push(value);
+        } else {
+            // Keep each of the `count` values seen with equal probability
+            let slot = self.next_random() % self.count;
+            if let Some(kept) = self.values.get_mut(slot as usize) {
+                *kept = value;
+            }
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    fn percentiles(&self) -> Option<Percentiles> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let mut sorted = self.values.clone();
+        sorted.sort_by(f64::total_cmp);
+        // Nearest-rank method: the smallest value with at least p% of the
+        // samples at or below it
+        let rank = |p: f64| {
+            let index = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+            sorted[index.clamp(1, sorted.len()) - 1]
+        };
+        Some(Percentiles {
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: self.sum / self.count as f64,
+            p50: rank(50.0),
+            p95: rank(95.0),
+            p99: rank(99.0),
+            exact: self.count == sorted.len() as u64,
+        })
+    }
+}
+
+/// Accumulates samples into a [`SessionReport`]
+#[derive(Debug, Clone)]
+pub struct StatsAccumulator {
+    samples: u64,
+    duration_ns: u64,
+    render: Reservoir,
+    video: Reservoir,
+    video_enhance: Reservoir,
+    blitter: Reservoir,
+    compute: Reservoir,
+    frequency_mhz: Reservoir,
+    gpu_watts: Reservoir,
+    temperature_celsius: Reservoir,
+}
+
+impl StatsAccumulator {
+    /// Create an accumulator keeping [`DEFAULT_CAPACITY`] values per metric
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Create an accumulator keeping at most `capacity` values per metric
+    ///
+    /// Percentiles are exact until a metric has more samples than this.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let reservoir = Reservoir::new(capacity.max(1));
+        Self {
+            samples: 0,
+            duration_ns: 0,
+            render: reservoir.clone(),
+            video: reservoir.clone(),
+            video_enhance: reservoir.clone(),
+            blitter: reservoir.clone(),
+            compute: reservoir.clone(),
+            frequency_mhz: reservoir.clone(),
+            gpu_watts: reservoir.clone(),
+            temperature_celsius: reservoir,
+        }
+    }
+
+    /// Ingest one sample
+    pub fn add(&mut self, stats: &GpuStats) {
+        self.samples += 1;
+        self.duration_ns = self.duration_ns.saturating_add(stats.sample_duration_ns);
+
+        let engines = &stats.engines;
+        self.render.push(engines.render.busy_percent);
+        self.video.push(engines.video.busy_percent);
+        self.video_enhance.push(engines.video_enhance.busy_percent);
+        self.blitter.push(engines.blitter.busy_percent);
+        if let Some(compute) = &engines.compute {
+            self.compute.push(compute.busy_percent);
+        }
+        self.frequency_mhz.push(stats.frequency.actual_mhz as f64);
+        if let Some(power) = &stats.power {
+            self.gpu_watts.push(power.gpu_watts);
+        }
+        if let Some(temperature) = &stats.temperature {
+            self.temperature_celsius.push(temperature.gpu_celsius);
+        }
+    }
+
+    /// Number of samples ingested so far
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// Summarize the samples ingested so far
+    pub fn report(&self) -> SessionReport {
+        SessionReport {
+            samples: self.samples,
+            duration_ns: self.duration_ns,
+            render: self.render.percentiles(),
+            video: self.video.percentiles(),
+            video_enhance: self.video_enhance.percentiles(),
+            blitter: self.blitter.percentiles(),
+            compute: self.compute.percentiles(),
+            frequency_mhz: self.frequency_mhz.percentiles(),
+            gpu_watts: self.gpu_watts.percentiles(),
+            temperature_celsius: self.temperature_celsius.percentiles(),
+        }
+    }
+}
+
+impl Default for StatsAccumulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EngineUtilization;
+    use std::time::Instant;
+
+    #[test]
+    fn test_percentiles() {
+        let mut accumulator = StatsAccumulator::new();
+        for busy in 1..=100 {
+            let mut stats = GpuStats::new(Instant::now(), 1_000_000);
+            stats.engines.video = EngineUtilization::new(busy as f64, 0.0, 0.0);
+            accumulator.add(&stats);
+        }
+
+        let report = accumulator.report();
+        assert_eq!(report.samples, 100);
+        assert_eq!(report.duration_ns, 100_000_000);
+        let video = report.video.unwrap();
+        assert_eq!((video.min, video.max, video.mean), (1.0, 100.0, 50.5));
+        assert_eq!((video.p50, video.p95, video.p99), (50.0, 95.0, 99.0));
+        assert!(video.exact);
+        assert!(report.compute.is_none());
+        assert!(report.gpu_watts.is_none());
+    }
+
+    #[test]
+    fn test_bounded_memory() {
+        let mut reservoir = Reservoir::new(100);
+        for value in 0..10_000 {
+            reservoir.push(value as f64);
+        }
+        assert_eq!(reservoir.values.len(), 100);
+
+        let percentiles = reservoir.percentiles().unwrap();
+        assert!(!percentiles.exact);
+        assert_eq!(percentiles.count, 10_000);
+        assert_eq!((percentiles.min, percentiles.max), (0.0, 9999.0));
+        // A uniform sample of 0..10000 has its median near the middle
+        assert!((2_000.0..8_000.0).contains(&percentiles.p50));
+    }
+}
@@ -0,0 +1,432 @@
+//! Raw counter snapshots for measuring arbitrary windows
+//!
+//! `read_stats` computes utilization since the previous read, so every call
+//! moves the reader's window. A [`CounterSnapshot`] instead captures the
+//! cumulative counters without touching that state, and
+//! [`GpuStats::from_snapshots`] computes the stats between any two of them,
+//! e.g. taken at the start and end of a job:
+//!
+//! ```rust,no_run
+//! use intel_gpu_stats::{GpuStats, IntelGpu};
+//!
+//! let mut gpu = IntelGpu::detect()?;
+//! let start = gpu.snapshot_counters()?;
+//! // ... run the job, calling read_stats() as often as you like ...
+//! let end = gpu.snapshot_counters()?;
+//!
+//! let job = GpuStats::from_snapshots(&start, &end);
+//! println!("Video: {:.1}%", job.engines.video.busy_percent);
+//! # Ok::<(), intel_gpu_stats::Error>(())
+//! ```
+//!
+//! Only counter-derived values (engines, PMU frequency, RC6) are computed;
+//! sensor readings such as temperature, power and throttle are left unset.
+
+use std::time::{Duration, Instant};
+
+use crate::types::*;
+
+/// Window over which `IntelGpu::is_in_use` watches the busy counters
+pub(crate) const IN_USE_WINDOW: Duration = Duration::from_millis(50);
+
+/// Cumulative counters of one engine instance
+#[derive(Debug, Clone)]
+pub struct EngineSnapshot {
+    /// Engine class
+    pub class: EngineClass,
+    /// Engine instance within the class
+    pub instance: u16,
+    /// Whether this is the instance reported as e.g. `engines.render`
+    pub primary: bool,
+    /// Busy nanoseconds, or active ticks when `total_ticks` is set
+    pub busy: u64,
+    /// Nanoseconds spent waiting for memory, if counted
+    pub wait: Option<u64>,
+    /// Nanoseconds spent waiting on semaphores, if counted
+    pub sema: Option<u64>,
+    /// Total engine ticks (xe kernels with only tick counters)
+    pub total_ticks: Option<u64>,
+    /// Context switches, wrapping at `u32::MAX` (Windows only)
+    pub context_switches: Option<u32>,
+}
+
+/// Cumulative RC6 residency counter of one GT
+#[derive(Debug, Clone, Copy)]
+pub struct Rc6Snapshot {
+    /// Raw counter value
+    pub count: u64,
+    /// Nanoseconds per count, as known when the snapshot was taken
+    ///
+    /// On Linux the unit is checked against sysfs once enough residency has
+    /// accumulated, and may change then. A difference is converted with the
+    /// later snapshot's unit, so both ends are in the same one.
+    pub ns_per_count: f64,
+}
+
+/// Cumulative GPU counters at one instant
+///
+/// Taken with `IntelGpu::snapshot_counters`. The values are raw and only
+/// meaningful as a difference between two snapshots of the same GPU.
+#[derive(Debug, Clone)]
+pub struct CounterSnapshot {
+    /// When the counters were read
+    pub timestamp: Instant,
+    /// Counters of every opened engine instance
+    pub engines: Vec<EngineSnapshot>,
+    /// Actual frequency counter of gt0 in MHz × ns, if available
+    pub actual_frequency: Option<u64>,
+    /// Requested frequency counter of gt0 in MHz × ns, if available
+    pub requested_frequency: Option<u64>,
+    /// RC6 residency counters, one entry per GT
+    pub rc6: Vec<Rc6Snapshot>,
+}
+
+impl CounterSnapshot {
+    /// Check whether any engine did work since an earlier snapshot
+    ///
+    /// Engines are matched by class and instance; engines missing from
+    /// `earlier` are ignored.
+    pub fn is_busy_since(&self, earlier: &CounterSnapshot) -> bool {
+        self.engines.iter().any(|engine| {
+            earlier
+                .engines
+                .iter()
+                .find(|e| e.class == engine.class && e.instance == engine.instance)
+                .is_some_and(|e| engine.busy > e.busy)
+        })
+    }
+}
+
+/// Raw cumulative counters for one-shot tools
+///
+/// Returned by `IntelGpu::read_absolute`. No percentages are computed, so a
+/// single read is meaningful on its own; diff the values externally (e.g.
+/// between two runs of a script) or use [`average_busy_percent`](Self::average_busy_percent).
+///
+/// On Linux the PMU counters start at zero when the GPU is opened, not at
+/// boot. On Windows the D3DKMT counters are cumulative since the adapter
+/// started.
+#[derive(Debug, Clone)]
+pub struct AbsoluteStats {
+    /// The cumulative counter values
+    pub counters: CounterSnapshot,
+    /// How long the counters have been counting, in nanoseconds
+    ///
+    /// Time since the GPU was opened on Linux, including any time the
+    /// counters were disabled. `None` when the counters started at an
+    /// unknown point (Windows).
+    pub counting_ns: Option<u64>,
+}
+
+impl AbsoluteStats {
+    /// The primary engine of a class, if it was opened
+    pub fn engine(&self, class: EngineClass) -> Option<&EngineSnapshot> {
+        self.counters
+            .engines
+            .iter()
+            .find(|engine| engine.class == class && engine.primary)
+    }
+
+    /// Average busy percentage of the primary engine of a class since counting started
+    ///
+    /// `None` if the engine was not opened or, for time-based counters, the
+    /// counting time is unknown.
+    pub fn average_busy_percent(&self, class: EngineClass) -> Option<f64> {
+        let engine = self.engine(class)?;
+        let window = engine.total_ticks.or(self.counting_ns)?;
+        Some(percent(engine.busy, window))
+    }
+}
+
+/// Counter deltas of one engine over a window
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EngineDeltas {
+    pub(crate) busy: u64,
+    /// Wait nanoseconds, if counted
+    pub(crate) wait: Option<u64>,
+    /// Semaphore wait nanoseconds, if counted
+    pub(crate) sema: Option<u64>,
+    /// Total ticks, when `busy` counts ticks rather than nanoseconds
+    pub(crate) total: Option<u64>,
+}
+
+impl EngineDeltas {
+    /// Compute utilization over a window of `elapsed_ns`
+    ///
+    /// The math is done on integers wherever possible, so the result stays
+    /// accurate for any window a `u64` nanosecond count can hold (about 584
+    /// years), e.g. a daemon reading again after days of sleep. Only the
+    /// fractional part of a percentage goes through `f64`.
+    pub(crate) fn utilization(&self, elapsed_ns: u64) -> EngineUtilization {
+        // Tick events measure busy against total engine ticks, otherwise
+        // busy time is measured against wall time
+        let busy_window = self.total.unwrap_or(elapsed_ns);
+        let busy = self.busy.min(busy_window);
+        // Ticks are not time, so scale them back to the sample window
+        let busy_ns = match self.total {
+            Some(total) if total > 0 => (busy as u128 * elapsed_ns as u128 / total as u128) as u64,
+            Some(_) => 0,
+            None => self.busy,
+        };
+
+        let mut utilization =
+            EngineUtilization::busy_only(percent(busy, busy_window)).with_busy_ns(busy_ns);
+        utilization.wait_percent = self.wait.map(|wait| percent(wait, elapsed_ns));
+        utilization.sema_percent = self.sema.map(|sema| percent(sema, elapsed_ns));
+        utilization
+    }
+}
+
+/// `part` as a percentage of `whole`, capped at 100
+///
+/// The integer percent is computed exactly in `u128`, so large counter
+/// values don't lose precision by being converted to `f64` first.
+pub(crate) fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        return 0.0;
+    }
+    let scaled = part.min(whole) as u128 * 100;
+    let whole = whole as u128;
+    (scaled / whole) as f64 + (scaled % whole) as f64 / whole as f64
+}
+
+impl GpuStats {
+    /// Compute stats over the window between two counter snapshots
+    ///
+    /// Engines are matched by class and instance; engines missing from
+    /// `start` are skipped. Counters that went backwards count as zero.
+    pub fn from_snapshots(start: &CounterSnapshot, end: &CounterSnapshot) -> GpuStats {
+        let elapsed_ns = end
+            .timestamp
+            .saturating_duration_since(start.timestamp)
+            .as_nanos() as u64;
+        let mut stats = GpuStats::new(end.timestamp, elapsed_ns);
+
+        for engine in &end.engines {
+            let Some(before) = start
+                .engines
+                .iter()
+                .find(|e| e.class == engine.class && e.instance == engine.instance)
+            else {
+                continue;
+            };
+            let delta = |end: Option<u64>, start: Option<u64>| {
+                end.zip(start).map(|(end, start)| end.saturating_sub(start))
+            };
+            let deltas = EngineDeltas {
+                busy: engine.busy.saturating_sub(before.busy),
+                wait: delta(engine.wait, before.wait),
+                sema: delta(engine.sema, before.sema),
+                total: engine
+                    .total_ticks
+                    .map(|total| total.saturating_sub(before.total_ticks.unwrap_or(0))),
+            };
+            let mut utilization = deltas.utilization(elapsed_ns);
+            utilization.context_switches = engine
+                .context_switches
+                .zip(before.context_switches)
+                .map(|(end, start)| end.wrapping_sub(start) as u64);
+
+            match engine.class {
+                EngineClass::Video => stats.engines.video_instances.push(utilization.clone()),
+                EngineClass::VideoEnhance => stats
+                    .engines
+                    .video_enhance_instances
+                    .push(utilization.clone()),
+                EngineClass::Compute => stats.engines.compute_instances.push(utilization.clone()),
+                _ => {}
+            }
+            if engine.primary {
+                match engine.class {
+                    EngineClass::Render => stats.engines.render = utilization,
+                    EngineClass::Video => stats.engines.video = utilization,
+                    EngineClass::VideoEnhance => stats.engines.video_enhance = utilization,
+                    EngineClass::Copy => stats.engines.blitter = utilization,
+                    EngineClass::Compute => stats.engines.compute = Some(utilization),
+                }
+            }
+        }
+
+        // Frequency counters accumulate MHz × ns
+        let mhz = |end: Option<u64>, start: Option<u64>| {
+            end.zip(start)
+                .and_then(|(end, start)| end.saturating_sub(start).checked_div(elapsed_ns))
+                .map_or(0, |mhz| mhz as u32)
+        };
+        stats.frequency = FrequencyStats::new(
+            mhz(end.actual_frequency, start.actual_frequency),
+            mhz(end.requested_frequency, start.requested_frequency),
+        );
+
+        if elapsed_ns > 0 {
+            let residencies: Vec<f64> = end
+                .rc6
+                .iter()
+                .zip(&start.rc6)
+                .map(|(end, start)| {
+                    let counts = end.count.saturating_sub(start.count);
+                    percent((counts as f64 * end.ns_per_count) as u64, elapsed_ns)
+                })
+                .collect();
+            if !residencies.is_empty() {
+                let mean = residencies.iter().sum::<f64>() / residencies.len() as f64;
+                stats.rc6 = Some(Rc6Stats::new(mean));
+            }
+        }
+
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn engine(class: EngineClass, instance: u16, busy: u64) -> EngineSnapshot {
+        EngineSnapshot {
+            class,
+            instance,
+            primary: instance == 0,
+            busy,
+            wait: Some(0),
+            sema: None,
+            total_ticks: None,
+            context_switches: None,
+        }
+    }
+
+    #[test]
+    fn test_engine_deltas() {
+        let ns = EngineDeltas {
+            busy: 25_000_000,
+            wait: Some(10_000_000),
+            ..Default::default()
+        };
+        let utilization = ns.utilization(100_000_000);
+        assert_eq!(utilization.busy_percent, 25.0);
+        assert_eq!(utilization.wait_percent, Some(10.0));
+        assert_eq!(utilization.sema_percent, None);
+        assert_eq!(utilization.busy_ns, 25_000_000);
+
+        let ticks = EngineDeltas {
+            busy: 300,
+            total: Some(400),
+            ..Default::default()
+        };
+        let utilization = ticks.utilization(100_000_000);
+        assert_eq!(utilization.busy_percent, 75.0);
+        assert_eq!(utilization.busy_ns, 75_000_000);
+    }
+
+    #[test]
+    fn test_engine_deltas_long_window() {
+        // Far beyond 2^53 ns, where f64 no longer holds every integer
+        let elapsed_ns = (1u64 << 62) + 3;
+
+        let ns = EngineDeltas {
+            busy: elapsed_ns / 4,
+            wait: Some(elapsed_ns),
+            sema: Some(u64::MAX),
+            ..Default::default()
+        };
+        let utilization = ns.utilization(elapsed_ns);
+        assert!((utilization.busy_percent - 25.0).abs() < 1e-12);
+        assert_eq!(utilization.busy_ns, elapsed_ns / 4);
+        assert_eq!(utilization.wait_percent, Some(100.0));
+        assert_eq!(utilization.sema_percent, Some(100.0));
+
+        // Scaling ticks back to time stays exact
+        let ticks = EngineDeltas {
+            busy: 7,
+            total: Some(7),
+            ..Default::default()
+        };
+        let utilization = ticks.utilization(elapsed_ns);
+        assert_eq!(utilization.busy_percent, 100.0);
+        assert_eq!(utilization.busy_ns, elapsed_ns);
+
+        assert_eq!(EngineDeltas::default().utilization(0).busy_percent, 0.0);
+    }
+
+    #[test]
+    fn test_absolute_stats() {
+        let absolute = AbsoluteStats {
+            counters: CounterSnapshot {
+                timestamp: Instant::now(),
+                engines: vec![
+                    engine(EngineClass::Video, 0, 30_000_000),
+                    engine(EngineClass::Video, 1, 90_000_000),
+                ],
+                actual_frequency: None,
+                requested_frequency: None,
+                rc6: Vec::new(),
+            },
+            counting_ns: Some(120_000_000),
+        };
+        assert_eq!(absolute.engine(EngineClass::Video).unwrap().instance, 0);
+        assert_eq!(
+            absolute.average_busy_percent(EngineClass::Video),
+            Some(25.0)
+        );
+        assert_eq!(absolute.average_busy_percent(EngineClass::Render), None);
+
+        let unknown_start = AbsoluteStats {
+            counting_ns: None,
+            ..absolute
+        };
+        assert_eq!(unknown_start.average_busy_percent(EngineClass::Video), None);
+    }
+
+    #[test]
+    fn test_from_snapshots() {
+        let t0 = Instant::now();
+        let start = CounterSnapshot {
+            timestamp: t0,
+            engines: vec![
+                engine(EngineClass::Render, 0, 1_000),
+                engine(EngineClass::Video, 0, 5_000),
+                engine(EngineClass::Video, 1, 0),
+            ],
+            actual_frequency: Some(0),
+            requested_frequency: None,
+            // Counted as nanoseconds until the unit was checked
+            rc6: vec![Rc6Snapshot {
+                count: 0,
+                ns_per_count: 1.0,
+            }],
+        };
+        let end = CounterSnapshot {
+            timestamp: t0 + Duration::from_millis(200),
+            engines: vec![
+                engine(EngineClass::Render, 0, 1_000 + 50_000_000),
+                engine(EngineClass::Video, 0, 5_000 + 100_000_000),
+                engine(EngineClass::Video, 1, 20_000_000),
+                // Not in the start snapshot
+                engine(EngineClass::Compute, 0, 1),
+            ],
+            actual_frequency: Some(1200 * 200_000_000),
+            requested_frequency: None,
+            // 40 ms of residency in a millisecond counter
+            rc6: vec![Rc6Snapshot {
+                count: 40,
+                ns_per_count: 1e6,
+            }],
+        };
+
+        let stats = GpuStats::from_snapshots(&start, &end);
+        assert_eq!(stats.sample_duration_ns, 200_000_000);
+        assert_eq!(stats.engines.render.busy_percent, 25.0);
+        assert_eq!(stats.engines.video.busy_percent, 50.0);
+        assert_eq!(stats.engines.video_instances.len(), 2);
+        assert_eq!(stats.engines.video_instances[1].busy_percent, 10.0);
+        assert!(stats.engines.compute.is_none());
+        assert_eq!(stats.frequency.actual_mhz, 1200);
+        assert_eq!(stats.frequency.requested_mhz, 0);
+        assert_eq!(stats.rc6.unwrap().residency_percent, 20.0);
+
+        assert!(end.is_busy_since(&start));
+        assert!(!start.is_busy_since(&start));
+        assert!(!start.is_busy_since(&end));
+    }
+}
@@ -0,0 +1,198 @@
+//! Throttle residency tracking
+//!
+//! Instantaneous throttle flags only say whether the GPU is throttled right
+//! now. [`ThrottleTracker`] integrates throttle samples over time so callers
+//! can ask how long each reason was active within a recent window, e.g.
+//! "thermally throttled for 30% of the last minute".
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::types::{ThrottleInfo, ThrottleReason};
+
+/// Default amount of history kept by a [`ThrottleTracker`]
+pub const DEFAULT_RETENTION: Duration = Duration::from_secs(600);
+
+/// A period of time during which the set of active reasons did not change
+#[derive(Debug, Clone, Copy)]
+struct Span {
+    start: Instant,
+    end: Instant,
+    /// Bitmask of active reasons, indexed by position in [`ThrottleReason::ALL`]
+    active: u8,
+}
+
+/// Accumulates how long each throttle reason was active
+///
+/// Each recorded sample attributes the time since the previous sample to the
+/// reasons active in the new sample. Samples without throttle information
+/// leave a gap, which is excluded from residency calculations.
+#[derive(Debug, Clone)]
+pub struct ThrottleTracker {
+    retention: Duration,
+    last_sample: Option<Instant>,
+    spans: VecDeque<Span>,
+}
+
+impl ThrottleTracker {
+    /// Create a tracker keeping [`DEFAULT_RETENTION`] of history
+    pub fn new() -> Self {
+        Self::with_retention(DEFAULT_RETENTION)
+    }
+
+    /// Create a tracker keeping the given amount of history
+    pub fn with_retention(retention: Duration) -> Self {
+        Self {
+            retention,
+            last_sample: None,
+            spans: VecDeque::new(),
+        }
+    }
+
+    /// Record a throttle sample taken at `timestamp`
+    pub fn record(&mut self, timestamp: Instant, info: Option<&ThrottleInfo>) {
+        let previous = self.last_sample.replace(timestamp);
+
+        if let (Some(start), Some(info)) = (previous, info) {
+            if timestamp > start {
+                self.push_span(start, timestamp, active_mask(info));
+            }
+        }
+
+        if let Some(cutoff) = timestamp.checked_sub(self.retention) {
+            while self.spans.front().is_some_and(|span| span.end <= cutoff) {
+                self.spans.pop_front();
+            }
+        }
+    }
+
+    fn push_span(&mut self, start: Instant, end: Instant, active: u8) {
+        // Extend the previous span when nothing changed to keep history compact
+        if let Some(last) = self.spans.back_mut() {
+            if last.end == start && last.active == active {
+                last.end = end;
+                return;
+            }
+        }
+        self.spans.push_back(Span { start, end, active });
+    }
+
+    /// Percentage of the last `window` each throttle reason was active
+    ///
+    /// The window ends at the most recent sample. Only time covered by
+    /// throttle samples counts, so a tracker that has run for less than
+    /// `window` reports against the time it has observed. Returns an empty
+    /// map when no throttle data has been recorded in the window.
+    pub fn residency(&self, window: Duration) -> HashMap<ThrottleReason, f64> {
+        let mut residency = HashMap::new();
+        let Some(end) = self.last_sample else {
+            return residency;
+        };
+        let window_start = end.checked_sub(window);
+
+        let mut covered = Duration::ZERO;
+        let mut active = [Duration::ZERO; ThrottleReason::ALL.len()];
+        for span in &self.spans {
+            let start = match window_start {
+                Some(window_start) if span.end <= window_start => continue,
+                Some(window_start) => span.start.max(window_start),
+                None => span.start,
+            };
+            let duration = span.end - start;
+            covered += duration;
+            for (index, total) in active.iter_mut().enumerate() {
+                if span.active & (1 << index) != 0 {
+                    *total += duration;
+                }
+            }
+        }
+
+        if covered.is_zero() {
+            return residency;
+        }
+
+        for (reason, total) in ThrottleReason::ALL.iter().zip(active) {
+            residency.insert(*reason, total.as_secs_f64() / covered.as_secs_f64() * 100.0);
+        }
+        residency
+    }
+
+    /// Discard all recorded history
+    pub fn clear(&mut self) {
+        self.last_sample = None;
+        self.spans.clear();
+    }
+}
+
+impl Default for ThrottleTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lock a shared tracker, recovering it if a holder panicked
+pub(crate) fn lock_shared(tracker: &Mutex<ThrottleTracker>) -> MutexGuard<'_, ThrottleTracker> {
+    tracker
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn active_mask(info: &ThrottleInfo) -> u8 {
+    ThrottleReason::ALL
+        .iter()
+        .enumerate()
+        .filter(|(_, reason)| info.is_active(**reason))
+        .fold(0, |mask, (index, _)| mask | (1 << index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thermal(active: bool) -> ThrottleInfo {
+        ThrottleInfo {
+            is_throttled: active,
+            thermal: active,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_residency_fraction() {
+        let mut tracker = ThrottleTracker::new();
+        let t0 = Instant::now();
+        tracker.record(t0, Some(&thermal(false)));
+        tracker.record(t0 + Duration::from_secs(7), Some(&thermal(false)));
+        tracker.record(t0 + Duration::from_secs(10), Some(&thermal(true)));
+
+        let residency = tracker.residency(Duration::from_secs(60));
+        assert!((residency[&ThrottleReason::Thermal] - 30.0).abs() < 1e-9);
+        assert_eq!(residency[&ThrottleReason::PowerLimit], 0.0);
+
+        // Only the last three seconds were throttled
+        let recent = tracker.residency(Duration::from_secs(3));
+        assert!((recent[&ThrottleReason::Thermal] - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gaps_and_retention() {
+        let mut tracker = ThrottleTracker::with_retention(Duration::from_secs(5));
+        assert!(tracker.residency(Duration::from_secs(1)).is_empty());
+
+        let t0 = Instant::now();
+        tracker.record(t0, Some(&thermal(true)));
+        tracker.record(t0 + Duration::from_secs(2), Some(&thermal(true)));
+        // Missing throttle data is not counted either way
+        tracker.record(t0 + Duration::from_secs(4), None);
+        tracker.record(t0 + Duration::from_secs(6), Some(&thermal(false)));
+
+        let residency = tracker.residency(Duration::from_secs(60));
+        assert!((residency[&ThrottleReason::Thermal] - 50.0).abs() < 1e-9);
+
+        // The first span falls out of the retention window
+        tracker.record(t0 + Duration::from_secs(8), Some(&thermal(false)));
+        let residency = tracker.residency(Duration::from_secs(60));
+        assert_eq!(residency[&ThrottleReason::Thermal], 0.0);
+    }
+}
@@ -1,15 +1,46 @@
 //! Data types for Intel GPU statistics
+//!
+//! These types only depend on `core` and `alloc`, so they remain available
+//! when the crate is built without the default `std` feature (e.g. to share
+//! them with a `no_std` consumer). The one std-specific piece, the snapshot
+//! time, is abstracted behind [`Timestamp`].
 
-use std::fmt;
-use std::time::Instant;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::time::Duration;
+
+/// Point in time at which a [`GpuStats`] snapshot was taken
+///
+/// This is `std::time::Instant` with the `std` feature (the default). Without
+/// it, it is a plain `u64` tick count whose unit and epoch are chosen by the
+/// producer of the stats.
+#[cfg(feature = "std")]
+pub type Timestamp = std::time::Instant;
+
+/// Point in time at which a [`GpuStats`] snapshot was taken
+///
+/// This is `std::time::Instant` with the `std` feature (the default). Without
+/// it, it is a plain `u64` tick count whose unit and epoch are chosen by the
+/// producer of the stats.
+#[cfg(not(feature = "std"))]
+pub type Timestamp = u64;
 
 /// Intel GPU kernel driver type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GpuDriver {
     /// Legacy i915 driver (most Intel GPUs before ~2024)
     I915,
     /// New xe driver (Intel Arc, newer integrated GPUs)
     Xe,
+    /// Bound to `vfio-pci` for passthrough to a virtual machine
+    ///
+    /// Such a GPU has no usable PMU on the host and cannot be monitored.
+    Vfio,
+    /// Intel Graphics DCH driver on Windows, monitored through D3DKMT
+    WindowsDch,
 }
 
 impl GpuDriver {
@@ -18,6 +49,8 @@ impl GpuDriver {
         match self {
             GpuDriver::I915 => "i915",
             GpuDriver::Xe => "xe",
+            GpuDriver::Vfio => "vfio-pci",
+            GpuDriver::WindowsDch => "windows-dch",
         }
     }
 }
@@ -28,8 +61,26 @@ impl fmt::Display for GpuDriver {
     }
 }
 
+/// Which GPU to favour when several are present
+///
+/// Used by `IntelGpu::detect_preferring`. A preference only ranks GPUs; if
+/// none matches, the first usable GPU is still opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GpuPreference {
+    /// A discrete GPU (e.g. Intel Arc) over integrated graphics
+    Discrete,
+    /// Integrated graphics over a discrete GPU
+    Integrated,
+    /// The GPU exposing the most engine instances
+    MostEngines,
+    /// A GPU bound to a specific kernel driver
+    Driver(GpuDriver),
+}
+
 /// Information about a detected Intel GPU
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GpuInfo {
     /// Unique identifier for this GPU (e.g., "card0")
     pub id: String,
@@ -45,8 +96,22 @@ pub struct GpuInfo {
     pub render_node: Option<String>,
     /// DRM card node path (e.g., /dev/dri/card0)
     pub card_node: Option<String>,
-    /// Kernel driver in use
+    /// Kernel driver in use, if it is one this crate recognizes
     pub driver: Option<GpuDriver>,
+    /// Name of the kernel driver the device is bound to (e.g. "i915" or
+    /// "vfio-pci"), also set for drivers without a [`GpuDriver`] variant
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub driver_name: Option<String>,
+    /// Current PCIe link speed (e.g. "16.0 GT/s PCIe"), if reported
+    pub pcie_link_speed: Option<String>,
+    /// Current PCIe link width in lanes (e.g. 16 for x16), if reported
+    pub pcie_link_width: Option<u8>,
+    /// Number of media engines (Video + VideoEnhance) found at discovery
+    ///
+    /// Prefer [`media_engine_count`](Self::media_engine_count), which falls
+    /// back to a table of known devices when this is not set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub media_engines: Option<u32>,
 }
 
 impl GpuInfo {
@@ -54,14 +119,115 @@ impl GpuInfo {
     pub fn is_intel(&self) -> bool {
         self.vendor_id == 0x8086
     }
+
+    /// Returns true if this looks like an integrated GPU
+    ///
+    /// Intel integrated graphics always sit at PCI address `00:02.0` on the
+    /// root bus. Returns false when the PCI location is unknown (Windows).
+    pub fn is_integrated(&self) -> bool {
+        self.pci_path
+            .rsplit('/')
+            .next()
+            .is_some_and(|address| address.ends_with(":00:02.0"))
+    }
+
+    /// PCI address of the GPU (e.g. "0000:03:00.0"), the last component of `pci_path`
+    ///
+    /// `None` when the PCI location is unknown, as on Windows or when sysfs
+    /// is unavailable.
+    pub fn pci_address(&self) -> Option<&str> {
+        self.pci_path
+            .rsplit('/')
+            .next()
+            .filter(|address| is_pci_address(address))
+    }
+
+    /// Returns true if the GPU is bound to vfio-pci (passed through to a VM)
+    pub fn is_passthrough(&self) -> bool {
+        self.driver == Some(GpuDriver::Vfio)
+    }
+
+    /// Number of media engines (Video + VideoEnhance instances) of this GPU
+    ///
+    /// Uses the engines found at discovery (PMU engine instances on Linux)
+    /// and otherwise the known maximum for the device ID, as always on
+    /// Windows. Parts of the same device ID may have engines fused off, so
+    /// the fallback is an upper bound. Returns `None` for unknown devices.
+    pub fn media_engine_count(&self) -> Option<u32> {
+        self.media_engines
+            .or_else(|| known_media_engine_count(self.device_id))
+    }
+}
+
+/// Check for a PCI address in `domain:bus:device.function` form ("0000:00:02.0")
+pub(crate) fn is_pci_address(address: &str) -> bool {
+    let hex =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_hexdigit());
+    let mut parts = address.split(':');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(domain), Some(bus), Some(slot), None) => {
+            hex(domain, 4)
+                && hex(bus, 2)
+                && slot
+                    .split_once('.')
+                    .is_some_and(|(device, function)| hex(device, 2) && hex(function, 1))
+        }
+        _ => false,
+    }
+}
+
+/// Maximum media engine count (VCS + VECS) by PCI device ID
+fn known_media_engine_count(device_id: u16) -> Option<u32> {
+    let count = match device_id {
+        // Gen9 GT2 (Kaby Lake, Coffee Lake): 1 VCS + 1 VECS
+        0x5917 | 0x3e90..=0x3e92 | 0x3e98 => 2,
+        // Xe-LP (Tiger Lake, Alder Lake): 2 VCS + 1 VECS
+        0x9a40 | 0x9a49 | 0x9a78 | 0x4680 | 0x46a6 => 3,
+        // Xe-HPG (Arc Alchemist): 2 VCS + 2 VECS
+        0x5690..=0x5698 | 0x56a0..=0x56b3 => 4,
+        _ => return None,
+    };
+    Some(count)
+}
+
+/// Equality considers only the stable identity of the GPU (`id`, `vendor_id`,
+/// `device_id` and `pci_path`), so the same GPU compares equal across
+/// rediscovery even if e.g. its PCIe link speed changed in between.
+impl PartialEq for GpuInfo {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+            && self.vendor_id == other.vendor_id
+            && self.device_id == other.device_id
+            && self.pci_path == other.pci_path
+    }
+}
+
+impl Eq for GpuInfo {}
+
+impl Hash for GpuInfo {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+        self.vendor_id.hash(state);
+        self.device_id.hash(state);
+        self.pci_path.hash(state);
+    }
 }
 
 /// Complete GPU statistics snapshot
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GpuStats {
     /// When this snapshot was taken
-    pub timestamp: Instant,
+    ///
+    /// Not serialized; deserialized stats are stamped with the time of decoding.
+    #[cfg_attr(
+        all(feature = "serde", feature = "std"),
+        serde(skip, default = "std::time::Instant::now")
+    )]
+    pub timestamp: Timestamp,
     /// Time elapsed since the last sample (for rate calculations)
+    ///
+    /// See [`sample_duration`](Self::sample_duration) for it as a `Duration`.
     pub sample_duration_ns: u64,
     /// Engine utilization statistics
     pub engines: EngineStats,
@@ -69,17 +235,32 @@ pub struct GpuStats {
     pub frequency: FrequencyStats,
     /// Power consumption (if available via RAPL)
     pub power: Option<PowerStats>,
-    /// RC6 power-saving state residency
+    /// RC6 power-saving state residency, averaged over GTs on multi-GT parts
     pub rc6: Option<Rc6Stats>,
     /// Temperature information (if available via hwmon)
     pub temperature: Option<TemperatureStats>,
     /// Throttle information (if available)
     pub throttle: Option<ThrottleInfo>,
+    /// Dedicated and shared memory usage (if available)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub memory: Option<MemoryStats>,
+    /// Performance level at the end of the sample (Linux only)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub perf_state: Option<PerfState>,
+    /// System memory (DRAM) bandwidth in GB/s (Linux, integrated GPUs only)
+    ///
+    /// Not a GPU counter: read and write traffic of the whole system,
+    /// counted by the CPU's memory controller. The integrated GPU shares
+    /// that memory, so this is an upper bound for its traffic. Only read
+    /// when enabled with `IntelGpuBuilder::system_memory_bandwidth`; `None`
+    /// otherwise and on discrete GPUs, whose VRAM has no such counter.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub system_memory_bandwidth_gbps: Option<f64>,
 }
 
 impl GpuStats {
     /// Create a new GpuStats with the given timestamp
-    pub fn new(timestamp: Instant, sample_duration_ns: u64) -> Self {
+    pub fn new(timestamp: Timestamp, sample_duration_ns: u64) -> Self {
         Self {
             timestamp,
             sample_duration_ns,
@@ -89,12 +270,265 @@ impl GpuStats {
             rc6: None,
             temperature: None,
             throttle: None,
+            memory: None,
+            perf_state: None,
+            system_memory_bandwidth_gbps: None,
+        }
+    }
+
+    /// Time elapsed since the last sample
+    pub fn sample_duration(&self) -> Duration {
+        Duration::from_nanos(self.sample_duration_ns)
+    }
+
+    /// Overwrite with a copy of `other`, reusing the engine lists' capacity
+    ///
+    /// A non-allocating alternative to `*self = other.clone()` once the
+    /// lists have grown, e.g. to keep the latest sample in a shared buffer.
+    pub fn copy_from(&mut self, other: &GpuStats) {
+        self.timestamp = other.timestamp;
+        self.sample_duration_ns = other.sample_duration_ns;
+        self.engines.copy_from(&other.engines);
+        self.frequency = other.frequency.clone();
+        self.power.clone_from(&other.power);
+        self.rc6.clone_from(&other.rc6);
+        self.temperature.clone_from(&other.temperature);
+        self.throttle.clone_from(&other.throttle);
+        self.memory.clone_from(&other.memory);
+        self.perf_state = other.perf_state;
+        self.system_memory_bandwidth_gbps = other.system_memory_bandwidth_gbps;
+    }
+
+    /// Diagnose a starved GPU frequency
+    ///
+    /// Returns `None` unless [`FrequencyStats::is_frequency_starved`]. A
+    /// starved GPU comes with the active throttle reasons that explain it,
+    /// which are empty if the GPU reports none or throttle reasons are
+    /// unavailable.
+    pub fn frequency_starvation(&self) -> Option<Vec<ThrottleReason>> {
+        if !self.frequency.is_frequency_starved() {
+            return None;
+        }
+        Some(
+            self.throttle
+                .as_ref()
+                .map(ThrottleInfo::active_reasons)
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Returns a flattened summary of the most commonly displayed values
+    ///
+    /// Every optional measurement is resolved to a concrete value; see
+    /// [`GpuSummary`] for the defaults used when data is missing.
+    pub fn summary(&self) -> GpuSummary {
+        GpuSummary {
+            busy_percent: self.engines.max_utilization(),
+            encode_percent: self.engines.video_enhance.busy_percent,
+            decode_percent: self.engines.video.busy_percent,
+            actual_mhz: self.frequency.actual_mhz,
+            gpu_watts: self.power.as_ref().map(|p| p.gpu_watts).unwrap_or(0.0),
+            temperature_celsius: self
+                .temperature
+                .as_ref()
+                .map(|t| t.gpu_celsius)
+                .unwrap_or(0.0),
+            throttled: self
+                .throttle
+                .as_ref()
+                .map(|t| t.any_throttling())
+                .unwrap_or(false),
+        }
+    }
+
+    /// Returns engine utilization per watt of GPU power
+    ///
+    /// Computed as `engines.max_utilization() / power.gpu_watts`, i.e. busy
+    /// percentage points delivered per watt. Higher is better. Returns `None`
+    /// when power data is unavailable or the GPU power reading is zero.
+    pub fn efficiency_metric(&self) -> Option<f64> {
+        let watts = self.power.as_ref()?.gpu_watts;
+        if watts <= 0.0 {
+            return None;
+        }
+        Some(self.engines.max_utilization() / watts)
+    }
+
+    /// Compare per-process engine time with the PMU's global busy time
+    ///
+    /// `client_deltas` must hold each client's engine time accumulated over
+    /// the same window as these stats, e.g. [`DrmClient::since`] applied to
+    /// snapshots taken around the read. Returns one entry per engine class
+    /// measured in these stats. Media classes count all instances, since
+    /// client times do too.
+    pub fn client_discrepancy(&self, client_deltas: &[DrmClient]) -> Vec<EngineDiscrepancy> {
+        let busy_ns =
+            |busy_percent: f64| (busy_percent / 100.0 * self.sample_duration_ns as f64) as u64;
+        let media_busy_ns = |primary: &EngineUtilization, instances: &[EngineUtilization]| {
+            if instances.is_empty() {
+                busy_ns(primary.busy_percent)
+            } else {
+                busy_ns(instances.iter().map(|e| e.busy_percent).sum())
+            }
+        };
+
+        let engines = &self.engines;
+        let mut pmu = Vec::from([
+            (EngineClass::Render, busy_ns(engines.render.busy_percent)),
+            (EngineClass::Copy, busy_ns(engines.blitter.busy_percent)),
+            (
+                EngineClass::Video,
+                media_busy_ns(&engines.video, &engines.video_instances),
+            ),
+            (
+                EngineClass::VideoEnhance,
+                media_busy_ns(&engines.video_enhance, &engines.video_enhance_instances),
+            ),
+        ]);
+        if let Some(ref compute) = engines.compute {
+            pmu.push((EngineClass::Compute, busy_ns(compute.busy_percent)));
+        }
+
+        pmu.into_iter()
+            .map(|(class, pmu_busy_ns)| EngineDiscrepancy {
+                class,
+                pmu_busy_ns,
+                clients_busy_ns: client_deltas.iter().map(|c| c.engine_ns(class)).sum(),
+            })
+            .collect()
+    }
+
+    /// Returns GPU watts spent per percentage point of engine utilization
+    ///
+    /// The inverse of [`efficiency_metric`](Self::efficiency_metric). Lower is
+    /// better. Returns `None` when power data is unavailable or the GPU is idle,
+    /// since the ratio is meaningless without any engine activity.
+    pub fn watts_per_percent(&self) -> Option<f64> {
+        let watts = self.power.as_ref()?.gpu_watts;
+        let utilization = self.engines.max_utilization();
+        if utilization <= 0.0 {
+            return None;
+        }
+        Some(watts / utilization)
+    }
+}
+
+/// Flattened view of a [`GpuStats`] snapshot for overlays and status lines
+///
+/// Missing data is reported as zero (or `false` for `throttled`). Use the
+/// full [`GpuStats`] when you need to distinguish "unavailable" from zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpuSummary {
+    /// Highest busy percentage across all engines (0.0 - 100.0)
+    pub busy_percent: f64,
+    /// Quick Sync encode (video enhance) busy percentage
+    pub encode_percent: f64,
+    /// Quick Sync decode (video) busy percentage
+    pub decode_percent: f64,
+    /// Actual GPU frequency in MHz (0 if unavailable)
+    pub actual_mhz: u32,
+    /// GPU power draw in watts (0.0 if unavailable)
+    pub gpu_watts: f64,
+    /// GPU temperature in Celsius (0.0 if unavailable)
+    pub temperature_celsius: f64,
+    /// Whether any throttle reason is active (false if unavailable)
+    pub throttled: bool,
+}
+
+/// Monitoring capabilities of an opened GPU
+///
+/// A snapshot of which statistics the reader can provide, so they can be
+/// checked and passed around without holding on to the GPU itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    /// Render/3D engine utilization is available
+    pub render: bool,
+    /// Video (decode) engine utilization is available
+    pub video: bool,
+    /// Video enhance (encode) engine utilization is available
+    pub video_enhance: bool,
+    /// Blitter/Copy engine utilization is available
+    pub blitter: bool,
+    /// Compute engine utilization is available
+    pub compute: bool,
+    /// Frequency is available, from PMU counters or sysfs
+    pub frequency: bool,
+    /// RC6 residency counter opened
+    pub rc6: bool,
+    /// Temperature monitoring is available
+    pub temperature: bool,
+    /// Fan speed monitoring is available
+    pub fan: bool,
+    /// Throttle monitoring is available
+    pub throttle: bool,
+    /// Power monitoring is available
+    pub power: bool,
+}
+
+impl Capabilities {
+    /// Check whether utilization is available for an engine class
+    pub fn has_engine(&self, class: EngineClass) -> bool {
+        match class {
+            EngineClass::Render => self.render,
+            EngineClass::Copy => self.blitter,
+            EngineClass::Video => self.video,
+            EngineClass::VideoEnhance => self.video_enhance,
+            EngineClass::Compute => self.compute,
+        }
+    }
+}
+
+/// A non-fatal problem met while opening a GPU
+///
+/// Returned by `IntelGpu::open_warnings`. The GPU still opened, but some
+/// statistics are missing; [`Capabilities`] tells which.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OpenWarning {
+    /// An engine listed by the PMU could not be opened
+    EngineUnavailable {
+        /// Engine class
+        class: EngineClass,
+        /// Engine instance within the class
+        instance: u16,
+        /// Why opening failed
+        reason: String,
+    },
+    /// No usable PMU was found, so only the sensors were opened
+    ///
+    /// Only when opening best effort, see `IntelGpu::detect_best_effort`.
+    PmuUnavailable {
+        /// Why no PMU could be used
+        reason: String,
+    },
+}
+
+impl fmt::Display for OpenWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpenWarning::EngineUnavailable {
+                class,
+                instance,
+                reason,
+            } => write!(
+                f,
+                "{} engine instance {} present in PMU but failed to open: {}",
+                class.name(),
+                instance,
+                reason
+            ),
+            OpenWarning::PmuUnavailable { reason } => {
+                write!(f, "PMU unavailable, only sensors opened: {}", reason)
+            }
         }
     }
 }
 
 /// Statistics for all GPU engines
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EngineStats {
     /// Render/3D engine (OpenGL/Vulkan)
     pub render: EngineUtilization,
@@ -106,9 +540,36 @@ pub struct EngineStats {
     pub blitter: EngineUtilization,
     /// Compute engine (Intel Arc and newer)
     pub compute: Option<EngineUtilization>,
+    /// Per-instance video engine utilization (vcs0, vcs1, ...)
+    ///
+    /// Empty when the platform does not report individual instances.
+    pub video_instances: Vec<EngineUtilization>,
+    /// Per-instance video enhance engine utilization (vecs0, vecs1, ...)
+    ///
+    /// Empty when the platform does not report individual instances.
+    pub video_enhance_instances: Vec<EngineUtilization>,
+    /// Per-instance compute engine utilization (ccs0, ccs1, ...)
+    ///
+    /// `compute` is the primary one of these. Empty when the GPU has no
+    /// compute engine or the platform does not report individual instances.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compute_instances: Vec<EngineUtilization>,
 }
 
 impl EngineStats {
+    /// Overwrite with a copy of `other`, reusing the instance lists' capacity
+    pub fn copy_from(&mut self, other: &EngineStats) {
+        self.render = other.render.clone();
+        self.video = other.video.clone();
+        self.video_enhance = other.video_enhance.clone();
+        self.blitter = other.blitter.clone();
+        self.compute.clone_from(&other.compute);
+        self.video_instances.clone_from(&other.video_instances);
+        self.video_enhance_instances
+            .clone_from(&other.video_enhance_instances);
+        self.compute_instances.clone_from(&other.compute_instances);
+    }
+
     /// Returns the overall maximum utilization across all engines
     pub fn max_utilization(&self) -> f64 {
         let mut max = self
@@ -125,33 +586,355 @@ impl EngineStats {
         max
     }
 
+    /// Returns the maximum utilization scaled by the frequency, as a
+    /// percentage of peak capability (0.0 - 100.0)
+    ///
+    /// [`max_utilization`](Self::max_utilization) times
+    /// `freq.actual_mhz / max_mhz`, so 50% busy at 300 MHz on a 2400 MHz GPU
+    /// gives 6.25. Comparable across power states where busy percentages
+    /// alone are not. `max_mhz` is the hardware maximum (RP0), e.g. from
+    /// `IntelGpu::max_frequency_mhz`; 0 gives 0.0. Media engines on a
+    /// separate media clock are scaled by the GT clock all the same.
+    pub fn normalized_load(&self, freq: &FrequencyStats, max_mhz: u32) -> f64 {
+        if max_mhz == 0 {
+            return 0.0;
+        }
+        let ratio = (freq.actual_mhz as f64 / max_mhz as f64).min(1.0);
+        clamp_percent(self.max_utilization() * ratio)
+    }
+
+    /// Returns the utilization of the primary engine of a class
+    ///
+    /// `None` for the compute class on GPUs without a compute engine.
+    pub fn get(&self, class: EngineClass) -> Option<&EngineUtilization> {
+        match class {
+            EngineClass::Render => Some(&self.render),
+            EngineClass::Copy => Some(&self.blitter),
+            EngineClass::Video => Some(&self.video),
+            EngineClass::VideoEnhance => Some(&self.video_enhance),
+            EngineClass::Compute => self.compute.as_ref(),
+        }
+    }
+
     /// Returns the Quick Sync utilization (video + video_enhance combined)
     pub fn quicksync_utilization(&self) -> f64 {
         self.video.busy_percent.max(self.video_enhance.busy_percent)
     }
+
+    /// Returns the video and video enhance engines combined into one media engine
+    ///
+    /// Combined like [`quicksync_utilization`](Self::quicksync_utilization):
+    /// busy, peak, wait and semaphore percentages and busy nanoseconds are the
+    /// larger of the two engines' values, not their sum. The engines run in
+    /// parallel, so a sum could exceed 100%, and on xe kernels where both
+    /// classes alias one engine it would count the same work twice. Wait and
+    /// semaphore percentages are `None` unless both engines measured them.
+    /// Context switches are summed.
+    pub fn media(&self) -> EngineUtilization {
+        let (video, video_enhance) = (&self.video, &self.video_enhance);
+        let max = |a: Option<f64>, b: Option<f64>| a.zip(b).map(|(a, b)| a.max(b));
+        EngineUtilization {
+            busy_percent: video.busy_percent.max(video_enhance.busy_percent),
+            wait_percent: max(video.wait_percent, video_enhance.wait_percent),
+            sema_percent: max(video.sema_percent, video_enhance.sema_percent),
+            peak_busy_percent: video.peak_busy_percent.max(video_enhance.peak_busy_percent),
+            busy_ns: video.busy_ns.max(video_enhance.busy_ns),
+            context_switches: match (video.context_switches, video_enhance.context_switches) {
+                (Some(a), Some(b)) => Some(a + b),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    /// Fold video enhance into video, leaving a single media engine
+    ///
+    /// `video` becomes [`media`](Self::media), `video_instances` holds the
+    /// instances of both classes, and `video_enhance` is reset to an idle
+    /// engine with no instances. `quicksync_utilization` is unchanged.
+    pub fn merge_media(&mut self) {
+        self.video = self.media();
+        self.video_enhance = EngineUtilization::default();
+        let video_enhance_instances = core::mem::take(&mut self.video_enhance_instances);
+        self.video_instances.extend(video_enhance_instances);
+    }
+
+    /// Fold the compute engine into render, leaving no compute engine
+    ///
+    /// Render's busy, peak, wait and semaphore percentages become the sums
+    /// of both engines', capped at 100, and busy nanoseconds and context
+    /// switches are summed. `compute` becomes `None` and
+    /// `compute_instances` is cleared. Without a compute engine, render is
+    /// left unchanged.
+    pub fn merge_compute(&mut self) {
+        let Some(compute) = self.compute.take() else {
+            return;
+        };
+        self.compute_instances.clear();
+        let render = &mut self.render;
+        let sum = |a: Option<f64>, b: Option<f64>| a.zip(b).map(|(a, b)| clamp_percent(a + b));
+        render.busy_percent = clamp_percent(render.busy_percent + compute.busy_percent);
+        render.peak_busy_percent =
+            clamp_percent(render.peak_busy_percent + compute.peak_busy_percent);
+        render.wait_percent = sum(render.wait_percent, compute.wait_percent);
+        render.sema_percent = sum(render.sema_percent, compute.sema_percent);
+        render.busy_ns = render.busy_ns.saturating_add(compute.busy_ns);
+        render.context_switches = match (render.context_switches, compute.context_switches) {
+            (Some(a), Some(b)) => Some(a + b),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Apply a [`ComputeReporting`] mode to these stats
+    pub fn report_compute(&mut self, mode: ComputeReporting) {
+        match mode {
+            ComputeReporting::Auto => {}
+            ComputeReporting::Separate => {
+                self.compute.get_or_insert_with(EngineUtilization::default);
+            }
+            ComputeReporting::MergeIntoRender => self.merge_compute(),
+        }
+    }
+
+    /// Returns a capacity-weighted Quick Sync utilization
+    ///
+    /// For each media class the per-instance busy percentages are averaged
+    /// using the given weights, so a busy high-throughput instance counts for
+    /// more than a busy low-throughput one. As with
+    /// [`quicksync_utilization`](Self::quicksync_utilization), the larger of
+    /// the video and video enhance figures is returned.
+    ///
+    /// With default (equal) weights and a single instance per class this is
+    /// identical to `quicksync_utilization`. Classes without per-instance data
+    /// fall back to their primary engine figure.
+    pub fn quicksync_utilization_weighted(&self, weights: &MediaEngineWeights) -> f64 {
+        let video = weighted_busy(&self.video, &self.video_instances, &weights.video);
+        let video_enhance = weighted_busy(
+            &self.video_enhance,
+            &self.video_enhance_instances,
+            &weights.video_enhance,
+        );
+        video.max(video_enhance)
+    }
+}
+
+/// Capacity-weighted mean busy percentage over engine instances
+fn weighted_busy(
+    primary: &EngineUtilization,
+    instances: &[EngineUtilization],
+    weights: &[f64],
+) -> f64 {
+    if instances.is_empty() {
+        return primary.busy_percent;
+    }
+
+    let mut weighted_sum = 0.0;
+    let mut total_weight = 0.0;
+    for (i, instance) in instances.iter().enumerate() {
+        let weight = weights.get(i).copied().unwrap_or(1.0).max(0.0);
+        weighted_sum += instance.busy_percent * weight;
+        total_weight += weight;
+    }
+
+    if total_weight > 0.0 {
+        weighted_sum / total_weight
+    } else {
+        primary.busy_percent
+    }
+}
+
+/// Relative capacity weights for individual media engine instances
+///
+/// Entry `i` is the weight of instance `i` in the matching
+/// `EngineStats::*_instances` list, e.g. `video: vec![2.0, 1.0]` when vcs0 has
+/// twice the throughput of vcs1. Missing entries default to `1.0`, so the
+/// default (empty) weights treat every instance as equally capable.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MediaEngineWeights {
+    /// Weights for video (decode) engine instances
+    pub video: Vec<f64>,
+    /// Weights for video enhance (encode) engine instances
+    pub video_enhance: Vec<f64>,
+}
+
+impl MediaEngineWeights {
+    /// Create weights for the video and video enhance instances
+    pub fn new(video: Vec<f64>, video_enhance: Vec<f64>) -> Self {
+        Self {
+            video,
+            video_enhance,
+        }
+    }
+}
+
+/// How compute engine (CCS) utilization is reported
+///
+/// Intel Arc and newer GPUs have dedicated compute engines, while on older
+/// integrated GPUs compute work (OpenCL, oneAPI) runs on the render engine
+/// and is counted in render utilization. The modes present one engine set
+/// across both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComputeReporting {
+    /// `engines.compute` is reported if the GPU has a compute engine and
+    /// `None` otherwise
+    #[default]
+    Auto,
+    /// `engines.compute` is always reported; it is idle on GPUs without a
+    /// compute engine, whose compute work shows up in render
+    Separate,
+    /// Compute utilization is added to `engines.render` and
+    /// `engines.compute` is always `None`, as on GPUs without a compute
+    /// engine (see [`EngineStats::merge_compute`])
+    MergeIntoRender,
+}
+
+/// Stable "Quick Sync is active" flag for indicators such as a tally light
+///
+/// Applies hysteresis to [`EngineStats::quicksync_utilization`]: the flag
+/// turns on when utilization rises above the on threshold and off when it
+/// drops below the off threshold, and keeps its last state in between, so it
+/// does not flicker on samples near a single threshold.
+///
+/// ```
+/// use intel_gpu_stats::QuickSyncIndicator;
+///
+/// let mut indicator = QuickSyncIndicator::new();
+/// assert!(indicator.update_percent(12.0));
+/// assert!(indicator.update_percent(3.0)); // between thresholds: stays on
+/// assert!(!indicator.update_percent(1.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuickSyncIndicator {
+    /// Utilization above which the indicator turns on (percent)
+    pub on_percent: f64,
+    /// Utilization below which the indicator turns off (percent)
+    pub off_percent: f64,
+    active: bool,
+}
+
+impl QuickSyncIndicator {
+    /// Default utilization above which the indicator turns on
+    pub const DEFAULT_ON_PERCENT: f64 = 5.0;
+    /// Default utilization below which the indicator turns off
+    pub const DEFAULT_OFF_PERCENT: f64 = 2.0;
+
+    /// Create an inactive indicator with the default thresholds
+    pub fn new() -> Self {
+        Self::with_thresholds(Self::DEFAULT_ON_PERCENT, Self::DEFAULT_OFF_PERCENT)
+    }
+
+    /// Create an inactive indicator with custom thresholds
+    ///
+    /// `off_percent` should not exceed `on_percent`; if it does, the two are
+    /// swapped.
+    pub fn with_thresholds(on_percent: f64, off_percent: f64) -> Self {
+        Self {
+            on_percent: on_percent.max(off_percent),
+            off_percent: off_percent.min(on_percent),
+            active: false,
+        }
+    }
+
+    /// Update from a sample and return whether Quick Sync is active
+    pub fn update(&mut self, stats: &GpuStats) -> bool {
+        self.update_percent(stats.engines.quicksync_utilization())
+    }
+
+    /// Update from a utilization percentage and return the new state
+    pub fn update_percent(&mut self, percent: f64) -> bool {
+        if percent > self.on_percent {
+            self.active = true;
+        } else if percent < self.off_percent {
+            self.active = false;
+        }
+        self.active
+    }
+
+    /// Whether Quick Sync was active as of the last update
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+impl Default for QuickSyncIndicator {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Utilization statistics for a single GPU engine
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EngineUtilization {
     /// Percentage of time the engine was actively processing (0.0 - 100.0)
     pub busy_percent: f64,
     /// Percentage of time the engine was waiting for memory (0.0 - 100.0)
-    pub wait_percent: f64,
+    ///
+    /// `None` where the platform does not measure it: on Windows, and on
+    /// Linux kernels without wait counters (e.g. xe).
+    pub wait_percent: Option<f64>,
     /// Percentage of time the engine was waiting on semaphores (0.0 - 100.0)
-    pub sema_percent: f64,
+    ///
+    /// `None` where the platform does not measure it, like `wait_percent`.
+    pub sema_percent: Option<f64>,
+    /// Highest busy percentage seen within the sample window (0.0 - 100.0)
+    ///
+    /// Equal to `busy_percent` unless the stats were aggregated from faster
+    /// inner samples (see [`SamplingOptions::with_inner_interval`](crate::SamplingOptions::with_inner_interval)).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub peak_busy_percent: f64,
+    /// Nanoseconds the engine was busy since the previous read
+    ///
+    /// The raw counter delta behind `busy_percent`, for callers that do their
+    /// own windowing. On xe kernels that only expose tick counters it is
+    /// derived from the active/total tick ratio and the sample duration.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub busy_ns: u64,
+    /// Context switches on the engine since the previous read
+    ///
+    /// High rates indicate contention between clients. Only reported on
+    /// Windows (D3DKMT node statistics); the Linux PMUs do not count them.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub context_switches: Option<u64>,
 }
 
 impl EngineUtilization {
     /// Create a new EngineUtilization with the given values
+    ///
+    /// Percentages are clamped to 0.0 - 100.0, so float noise from counter
+    /// arithmetic (e.g. `-1e-12` or `100.00000001`) never reaches callers.
     pub fn new(busy_percent: f64, wait_percent: f64, sema_percent: f64) -> Self {
+        let busy_percent = clamp_percent(busy_percent);
         Self {
             busy_percent,
-            wait_percent,
-            sema_percent,
+            wait_percent: Some(clamp_percent(wait_percent)),
+            sema_percent: Some(clamp_percent(sema_percent)),
+            peak_busy_percent: busy_percent,
+            busy_ns: 0,
+            context_switches: None,
         }
     }
 
+    /// Create an EngineUtilization with only the busy percentage measured
+    ///
+    /// Wait and semaphore percentages are left `None` rather than zero.
+    pub fn busy_only(busy_percent: f64) -> Self {
+        let busy_percent = clamp_percent(busy_percent);
+        Self {
+            busy_percent,
+            peak_busy_percent: busy_percent,
+            ..Default::default()
+        }
+    }
+
+    /// Set the busy nanoseconds since the previous read
+    pub fn with_busy_ns(mut self, busy_ns: u64) -> Self {
+        self.busy_ns = busy_ns;
+        self
+    }
+
     /// Returns true if this engine is idle
     pub fn is_idle(&self) -> bool {
         self.busy_percent < 0.1
@@ -161,26 +944,117 @@ impl EngineUtilization {
     pub fn is_busy(&self) -> bool {
         self.busy_percent > 90.0
     }
+
+    /// Busy percentage rounded to `decimals` decimal places, for display
+    ///
+    /// `busy_rounded(0)` turns e.g. `99.99996` into `100.0`. At most 9
+    /// decimals are kept.
+    pub fn busy_rounded(&self, decimals: u8) -> f64 {
+        round_percent(self.busy_percent, decimals)
+    }
+
+    /// Wait percentage rounded to `decimals` decimal places, for display
+    pub fn wait_rounded(&self, decimals: u8) -> Option<f64> {
+        self.wait_percent
+            .map(|percent| round_percent(percent, decimals))
+    }
+
+    /// Semaphore wait percentage rounded to `decimals` decimal places, for display
+    pub fn sema_rounded(&self, decimals: u8) -> Option<f64> {
+        self.sema_percent
+            .map(|percent| round_percent(percent, decimals))
+    }
+}
+
+/// Clamp a percentage to 0.0 - 100.0, mapping NaN to 0.0
+pub(crate) fn clamp_percent(value: f64) -> f64 {
+    if value.is_nan() {
+        0.0
+    } else {
+        value.clamp(0.0, 100.0)
+    }
+}
+
+/// Round a percentage half away from zero (`f64::round` needs `std`)
+fn round_percent(value: f64, decimals: u8) -> f64 {
+    let scale = (0..decimals.min(9)).fold(1.0, |scale, _| scale * 10.0);
+    let scaled = clamp_percent(value) * scale + 0.5;
+    (scaled as u64) as f64 / scale
 }
 
 /// GPU frequency statistics
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrequencyStats {
     /// Actual current GPU frequency in MHz
     pub actual_mhz: u32,
     /// Requested GPU frequency in MHz
     pub requested_mhz: u32,
+    /// Minimum (RPn) GPU frequency in MHz, if the driver reports it
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_mhz: Option<u32>,
+    /// Actual frequency of the media engines in MHz, if they clock separately
+    ///
+    /// Platforms with a standalone media GT (e.g. Meteor Lake and later on
+    /// xe) run the video engines at their own clock. `None` when the media
+    /// engines share the render clock or the driver doesn't report it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub media_mhz: Option<u32>,
+    /// Ratio of the media clock to the GT clock, if fixed by the firmware
+    ///
+    /// i915 reports it as `media_freq_factor`, e.g. 0.5 when the media
+    /// engines run at half the GT clock. `None` when the ratio is dynamic or
+    /// not reported.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub media_freq_factor: Option<f64>,
 }
 
+/// Fraction of the requested frequency below which the GPU counts as starved
+pub const FREQUENCY_STARVED_RATIO: f64 = 0.8;
+
 impl FrequencyStats {
     /// Create a new FrequencyStats
     pub fn new(actual_mhz: u32, requested_mhz: u32) -> Self {
         Self {
             actual_mhz,
             requested_mhz,
+            min_mhz: None,
+            media_mhz: None,
+            media_freq_factor: None,
         }
     }
 
+    /// Set the minimum frequency
+    pub fn with_min_mhz(mut self, min_mhz: Option<u32>) -> Self {
+        self.min_mhz = min_mhz;
+        self
+    }
+
+    /// Set the media frequency
+    pub fn with_media_mhz(mut self, media_mhz: Option<u32>) -> Self {
+        self.media_mhz = media_mhz;
+        self
+    }
+
+    /// Set the media frequency factor
+    pub fn with_media_freq_factor(mut self, media_freq_factor: Option<f64>) -> Self {
+        self.media_freq_factor = media_freq_factor;
+        self
+    }
+
+    /// Check whether the GPU wants to clock higher but can't
+    ///
+    /// True when the actual frequency is below [`FREQUENCY_STARVED_RATIO`] of
+    /// the requested one while more than the minimum frequency is requested,
+    /// which points at a power or thermal limit. Requests at the minimum are
+    /// ignored since an idle GPU sitting in RC6 reads a low actual frequency.
+    /// Without a known minimum any non-zero request counts.
+    /// [`GpuStats::frequency_starvation`] adds the throttle reasons.
+    pub fn is_frequency_starved(&self) -> bool {
+        self.requested_mhz > self.min_mhz.unwrap_or(0)
+            && (self.actual_mhz as f64) < self.requested_mhz as f64 * FREQUENCY_STARVED_RATIO
+    }
+
     /// Returns the frequency efficiency (actual / requested)
     pub fn efficiency(&self) -> f64 {
         if self.requested_mhz == 0 {
@@ -191,13 +1065,99 @@ impl FrequencyStats {
     }
 }
 
+/// Where a frequency reading came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FrequencySource {
+    /// PMU frequency counter
+    Pmu,
+    /// Driver sysfs file, used when the PMU is missing or implausible
+    Sysfs,
+    /// No source was available; the reported frequency is 0
+    #[default]
+    Unavailable,
+}
+
+/// Performance level of the GPU
+///
+/// Derived from the runtime power management state and the frequency the
+/// driver requests compared with its boost frequency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum PerfState {
+    /// Runtime suspended, or the GT is parked and not clocked
+    Idle = 0,
+    /// Clocked below the boost frequency
+    Normal = 1,
+    /// Requesting the boost frequency or above, e.g. after the driver
+    /// boosted a client waiting on the GPU
+    Boost = 2,
+}
+
+impl PerfState {
+    /// Classify a sample's frequency
+    ///
+    /// `boost_mhz` is the driver's boost frequency (`rps_boost_freq_mhz` on
+    /// i915); without it the GPU is never reported as boosted.
+    pub fn classify(frequency: &FrequencyStats, boost_mhz: Option<u32>, suspended: bool) -> Self {
+        if suspended || frequency.actual_mhz == 0 {
+            PerfState::Idle
+        } else if boost_mhz.is_some_and(|boost| boost > 0 && frequency.requested_mhz >= boost) {
+            PerfState::Boost
+        } else {
+            PerfState::Normal
+        }
+    }
+
+    /// Get a display name for this state
+    pub fn name(&self) -> &'static str {
+        match self {
+            PerfState::Idle => "Idle",
+            PerfState::Normal => "Normal",
+            PerfState::Boost => "Boost",
+        }
+    }
+
+    /// Get the state from its numeric value, as used by the binary format
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(PerfState::Idle),
+            1 => Some(PerfState::Normal),
+            2 => Some(PerfState::Boost),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PerfState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
 /// Power consumption statistics
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PowerStats {
     /// GPU power draw in Watts
     pub gpu_watts: f64,
     /// Package power draw in Watts (if available)
     pub package_watts: Option<f64>,
+    /// Platform power draw in Watts from the RAPL psys domain (if available)
+    ///
+    /// Covers the whole SoC including the GPU, and often more of the
+    /// platform; on some laptops it is the only meaningful reading.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub platform_watts: Option<f64>,
+    /// Power of each individual RAPL domain as (name, Watts), e.g.
+    /// `("uncore", 2.1)` or `("psys", 18.4)`
+    ///
+    /// `gpu_watts` and `package_watts` are sums over the relevant domains;
+    /// this lists all of them so callers can combine them differently.
+    /// Empty when RAPL is unavailable.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub domains: Vec<(String, f64)>,
 }
 
 impl PowerStats {
@@ -206,12 +1166,15 @@ impl PowerStats {
         Self {
             gpu_watts,
             package_watts,
+            platform_watts: None,
+            domains: Vec::new(),
         }
     }
 }
 
 /// RC6 power-saving state statistics
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Rc6Stats {
     /// Percentage of time in RC6 power-saving state (0.0 - 100.0)
     pub residency_percent: f64,
@@ -229,8 +1192,47 @@ impl Rc6Stats {
     }
 }
 
+/// GPU memory usage
+///
+/// Dedicated memory is the GPU's own local memory (VRAM on discrete GPUs);
+/// shared memory is system memory the GPU may use. Integrated GPUs have
+/// little or no dedicated memory.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryStats {
+    /// Total dedicated memory in bytes
+    pub dedicated_total_bytes: u64,
+    /// Dedicated memory in use in bytes
+    pub dedicated_used_bytes: u64,
+    /// Total shared memory the GPU may use, in bytes
+    pub shared_total_bytes: u64,
+    /// Shared memory in use in bytes
+    pub shared_used_bytes: u64,
+}
+
+impl MemoryStats {
+    /// Percentage of dedicated memory in use (0.0 - 100.0)
+    ///
+    /// Returns `None` if the GPU has no dedicated memory.
+    pub fn dedicated_used_percent(&self) -> Option<f64> {
+        used_percent(self.dedicated_used_bytes, self.dedicated_total_bytes)
+    }
+
+    /// Percentage of shared memory in use (0.0 - 100.0)
+    ///
+    /// Returns `None` if no shared memory is reported.
+    pub fn shared_used_percent(&self) -> Option<f64> {
+        used_percent(self.shared_used_bytes, self.shared_total_bytes)
+    }
+}
+
+fn used_percent(used: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| used as f64 / total as f64 * 100.0)
+}
+
 /// Engine class identifiers as defined in i915 driver
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum EngineClass {
     /// Render/3D engine
@@ -268,10 +1270,97 @@ impl EngineClass {
             EngineClass::Compute => "Compute",
         }
     }
+
+    /// Get the i915 engine abbreviation used by `intel_gpu_top`, e.g. "VCS"
+    pub fn abbreviation(&self) -> &'static str {
+        match self {
+            EngineClass::Render => "RCS",
+            EngineClass::Copy => "BCS",
+            EngineClass::Video => "VCS",
+            EngineClass::VideoEnhance => "VECS",
+            EngineClass::Compute => "CCS",
+        }
+    }
+
+    /// Get a name for end users, e.g. "Quick Sync Decode"
+    ///
+    /// Names the media engines after the Quick Sync features they provide,
+    /// where [`name`](Self::name) follows the driver's naming. To use other
+    /// names, e.g. translations, see [`EngineNames`].
+    pub fn friendly_name(&self) -> &'static str {
+        match self {
+            EngineClass::Render => "3D",
+            EngineClass::Copy => "Copy",
+            EngineClass::Video => "Quick Sync Decode",
+            EngineClass::VideoEnhance => "Quick Sync Encode",
+            EngineClass::Compute => "Compute",
+        }
+    }
+}
+
+/// Display names for engine classes, overriding [`EngineClass::name`]
+///
+/// Classes without an override keep their default name, so only the names
+/// that differ need to be set.
+///
+/// ```
+/// use intel_gpu_stats::{EngineClass, EngineNames};
+///
+/// let names = EngineNames::friendly().with(EngineClass::Render, "Rendu 3D");
+/// assert_eq!(names.get(EngineClass::Render), "Rendu 3D");
+/// assert_eq!(names.get(EngineClass::VideoEnhance), "Quick Sync Encode");
+/// assert_eq!(EngineNames::new().get(EngineClass::Copy), "Blitter");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineNames {
+    /// Overrides indexed by the class' numeric value
+    names: [Option<String>; 5],
+}
+
+impl EngineNames {
+    /// Create names without overrides
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create names using [`EngineClass::friendly_name`] for every class
+    pub fn friendly() -> Self {
+        [
+            EngineClass::Render,
+            EngineClass::Copy,
+            EngineClass::Video,
+            EngineClass::VideoEnhance,
+            EngineClass::Compute,
+        ]
+        .into_iter()
+        .fold(Self::new(), |names, class| {
+            names.with(class, class.friendly_name())
+        })
+    }
+
+    /// Override the name of a class
+    pub fn with(mut self, class: EngineClass, name: impl Into<String>) -> Self {
+        self.set(class, name);
+        self
+    }
+
+    /// Override the name of a class in place
+    pub fn set(&mut self, class: EngineClass, name: impl Into<String>) {
+        self.names[class as usize] = Some(name.into());
+    }
+
+    /// Get the name of a class, its override or else [`EngineClass::name`]
+    pub fn get(&self, class: EngineClass) -> &str {
+        self.names[class as usize]
+            .as_deref()
+            .unwrap_or_else(|| class.name())
+    }
 }
 
 /// Sample type identifiers for PMU events
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum SampleType {
     /// Engine busy time
@@ -296,19 +1385,50 @@ impl SampleType {
 
 /// GPU temperature statistics from hwmon
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TemperatureStats {
     /// GPU temperature in degrees Celsius
     pub gpu_celsius: f64,
     /// Fan speed in RPM (if available, typically for discrete GPUs)
     pub fan_rpm: Option<u32>,
+    /// Hardware-reported critical temperature in Celsius (hwmon `temp1_crit`,
+    /// or `temp1_max` if the driver only reports that)
+    pub critical_celsius: Option<f64>,
+    /// Fan control mode (hwmon `pwm1_enable`), if the fan is controllable
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fan_control_mode: Option<FanMode>,
+    /// Every temperature sensor the GPU exposes, ordered by index
+    ///
+    /// `gpu_celsius` is the first sensor; discrete cards may add e.g.
+    /// memory or board sensors.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sensors: Vec<TemperatureSensor>,
+}
+
+/// A single hwmon temperature sensor
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureSensor {
+    /// hwmon channel index (the N in `tempN_input`)
+    pub index: u32,
+    /// Label from `tempN_label`, or "tempN" if the sensor has no label
+    pub label: String,
+    /// Temperature in degrees Celsius
+    pub celsius: f64,
 }
 
 impl TemperatureStats {
+    /// Fallback critical threshold used when the hardware does not report one
+    pub const DEFAULT_CRITICAL_CELSIUS: f64 = 90.0;
+
     /// Create a new TemperatureStats
     pub fn new(gpu_celsius: f64) -> Self {
         Self {
             gpu_celsius,
             fan_rpm: None,
+            critical_celsius: None,
+            fan_control_mode: None,
+            sensors: Vec::new(),
         }
     }
 
@@ -317,12 +1437,31 @@ impl TemperatureStats {
         Self {
             gpu_celsius,
             fan_rpm: Some(fan_rpm),
+            critical_celsius: None,
+            fan_control_mode: None,
+            sensors: Vec::new(),
         }
     }
 
-    /// Check if temperature is critical (>90C)
+    /// Check if temperature is critical
+    ///
+    /// Uses the hardware-reported critical threshold when available,
+    /// otherwise falls back to [`DEFAULT_CRITICAL_CELSIUS`](Self::DEFAULT_CRITICAL_CELSIUS).
     pub fn is_critical(&self) -> bool {
-        self.gpu_celsius > 90.0
+        self.gpu_celsius
+            > self
+                .critical_celsius
+                .unwrap_or(Self::DEFAULT_CRITICAL_CELSIUS)
+    }
+
+    /// Headroom to the hardware-reported critical temperature in Celsius
+    ///
+    /// Negative once the GPU is past the threshold. `None` if the hardware
+    /// does not report one; unlike [`is_critical`](Self::is_critical) this
+    /// does not fall back to a default, so the margin is never a guess.
+    pub fn thermal_margin_celsius(&self) -> Option<f64> {
+        self.critical_celsius
+            .map(|critical| critical - self.gpu_celsius)
     }
 
     /// Check if temperature is high (>80C)
@@ -331,8 +1470,43 @@ impl TemperatureStats {
     }
 }
 
+/// Fan control mode as reported by hwmon `pwm1_enable`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FanMode {
+    /// Fan control disabled, the fan runs at full speed (0)
+    Off,
+    /// Fixed duty cycle set through `pwm1` (1)
+    Manual,
+    /// Controlled automatically by firmware or the driver (2 and above)
+    Auto,
+}
+
+impl FanMode {
+    /// Parse a `pwm1_enable` value
+    ///
+    /// Values above 2 are driver-specific automatic modes.
+    pub fn from_pwm_enable(value: u32) -> Self {
+        match value {
+            0 => FanMode::Off,
+            1 => FanMode::Manual,
+            _ => FanMode::Auto,
+        }
+    }
+
+    /// The `pwm1_enable` value selecting this mode
+    pub fn pwm_enable(self) -> u32 {
+        match self {
+            FanMode::Off => 0,
+            FanMode::Manual => 1,
+            FanMode::Auto => 2,
+        }
+    }
+}
+
 /// GPU throttling information
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThrottleInfo {
     /// Whether the GPU is currently throttled
     pub is_throttled: bool,
@@ -350,6 +1524,13 @@ pub struct ThrottleInfo {
     pub vr_thermal: bool,
     /// Throttled due to VR TDC (Thermal Design Current)
     pub vr_tdc: bool,
+    /// Whether throttling was inferred rather than reported by the driver
+    ///
+    /// Set on kernels without throttle reason files, where the GPU counts
+    /// as thermally throttled while running below its maximum frequency at
+    /// a high temperature. Only `is_throttled` and `thermal` are set then.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub inferred: bool,
 }
 
 impl ThrottleInfo {
@@ -369,10 +1550,111 @@ impl ThrottleInfo {
             || self.vr_thermal
             || self.vr_tdc
     }
+
+    /// Check whether a specific throttle reason is active
+    pub fn is_active(&self, reason: ThrottleReason) -> bool {
+        match reason {
+            ThrottleReason::Status => self.status,
+            ThrottleReason::PowerLimit => self.power_limit,
+            ThrottleReason::Thermal => self.thermal,
+            ThrottleReason::Prochot => self.prochot,
+            ThrottleReason::Ratl => self.ratl,
+            ThrottleReason::VrThermal => self.vr_thermal,
+            ThrottleReason::VrTdc => self.vr_tdc,
+        }
+    }
+
+    /// List the active throttle reasons
+    ///
+    /// The general `is_throttled` flag is not a reason by itself and is not
+    /// included.
+    pub fn active_reasons(&self) -> Vec<ThrottleReason> {
+        ThrottleReason::ALL
+            .into_iter()
+            .filter(|reason| self.is_active(*reason))
+            .collect()
+    }
+
+    /// Returns the reasons that changed state since `previous`
+    ///
+    /// Events are in [`ThrottleReason::ALL`] order.
+    pub fn changes_from(&self, previous: &ThrottleInfo) -> Vec<ThrottleEvent> {
+        ThrottleReason::ALL
+            .into_iter()
+            .filter(|reason| self.is_active(*reason) != previous.is_active(*reason))
+            .map(|reason| ThrottleEvent {
+                reason,
+                active: self.is_active(reason),
+            })
+            .collect()
+    }
+}
+
+/// A single throttle reason becoming active or clearing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThrottleEvent {
+    /// The throttle reason that changed
+    pub reason: ThrottleReason,
+    /// True if the reason became active, false if it cleared
+    pub active: bool,
+}
+
+/// Individual GPU throttle reasons, matching the fields of [`ThrottleInfo`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThrottleReason {
+    /// General throttle status
+    Status,
+    /// Power limit (PL1)
+    PowerLimit,
+    /// Thermal limit
+    Thermal,
+    /// PROCHOT signal
+    Prochot,
+    /// Running Average Thermal Limit
+    Ratl,
+    /// VR thermal alert
+    VrThermal,
+    /// VR Thermal Design Current
+    VrTdc,
+}
+
+impl ThrottleReason {
+    /// All throttle reasons
+    pub const ALL: [ThrottleReason; 7] = [
+        ThrottleReason::Status,
+        ThrottleReason::PowerLimit,
+        ThrottleReason::Thermal,
+        ThrottleReason::Prochot,
+        ThrottleReason::Ratl,
+        ThrottleReason::VrThermal,
+        ThrottleReason::VrTdc,
+    ];
+
+    /// Get a human-readable name for this reason
+    pub fn name(&self) -> &'static str {
+        match self {
+            ThrottleReason::Status => "Status",
+            ThrottleReason::PowerLimit => "Power Limit (PL1)",
+            ThrottleReason::Thermal => "Thermal",
+            ThrottleReason::Prochot => "PROCHOT",
+            ThrottleReason::Ratl => "RATL",
+            ThrottleReason::VrThermal => "VR Thermal",
+            ThrottleReason::VrTdc => "VR TDC",
+        }
+    }
+}
+
+impl fmt::Display for ThrottleReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
 }
 
 /// Per-process (DRM client) GPU usage information
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DrmClient {
     /// Process ID
     pub pid: u32,
@@ -390,6 +1672,32 @@ pub struct DrmClient {
     pub compute_ns: u64,
     /// Total GPU memory used in bytes
     pub memory_bytes: u64,
+    /// GPU resets the driver attributes to this client
+    ///
+    /// Summed over every fdinfo key ending in `-resets`. Zero when the
+    /// driver reports none, and always on Windows.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub resets: u64,
+    /// GPU faults (e.g. page faults) the driver attributes to this client
+    ///
+    /// Summed over every fdinfo key ending in `-faults`; zero when absent.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub faults: u64,
+    /// Number of engines of each class the client's engine time spans
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub engine_capacity: DrmEngineCapacity,
+    /// Engine usage in GPU cycles, reported by xe instead of engine time
+    ///
+    /// The `*_ns` fields stay zero for such clients.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub engine_cycles: DrmEngineCycles,
+    /// Card the usage is attributed to (e.g. "card1"), matching `GpuInfo::id`
+    ///
+    /// Resolved from the DRM node the process has open. `None` if it could
+    /// not be resolved, and always on Windows, where per-process usage is not
+    /// split by adapter.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub card_id: Option<String>,
 }
 
 impl DrmClient {
@@ -404,16 +1712,839 @@ impl DrmClient {
             video_enhance_ns: 0,
             compute_ns: 0,
             memory_bytes: 0,
+            resets: 0,
+            faults: 0,
+            engine_capacity: DrmEngineCapacity::default(),
+            engine_cycles: DrmEngineCycles::default(),
+            card_id: None,
         }
     }
 
     /// Total engine usage across all engines
+    ///
+    /// Zero for clients reporting [`engine_cycles`](Self::engine_cycles).
     pub fn total_usage_ns(&self) -> u64 {
         self.render_ns + self.copy_ns + self.video_ns + self.video_enhance_ns + self.compute_ns
     }
 
     /// Check if this client is using Quick Sync (video or video_enhance)
     pub fn is_using_quicksync(&self) -> bool {
-        self.video_ns > 0 || self.video_enhance_ns > 0
+        self.video_ns > 0
+            || self.video_enhance_ns > 0
+            || self.engine_cycles.get(EngineClass::Video).busy > 0
+            || self.engine_cycles.get(EngineClass::VideoEnhance).busy > 0
+    }
+
+    /// Engine usage of one class in nanoseconds
+    pub fn engine_ns(&self, class: EngineClass) -> u64 {
+        match class {
+            EngineClass::Render => self.render_ns,
+            EngineClass::Copy => self.copy_ns,
+            EngineClass::Video => self.video_ns,
+            EngineClass::VideoEnhance => self.video_enhance_ns,
+            EngineClass::Compute => self.compute_ns,
+        }
+    }
+
+    /// Engine usage, resets and faults accumulated since an earlier snapshot
+    /// of the same client
+    ///
+    /// Counters that went backwards (e.g. the PID was reused) yield zero.
+    /// Memory is taken from `self`, since it is not cumulative.
+    pub fn since(&self, earlier: &DrmClient) -> DrmClient {
+        DrmClient {
+            pid: self.pid,
+            name: self.name.clone(),
+            render_ns: self.render_ns.saturating_sub(earlier.render_ns),
+            copy_ns: self.copy_ns.saturating_sub(earlier.copy_ns),
+            video_ns: self.video_ns.saturating_sub(earlier.video_ns),
+            video_enhance_ns: self
+                .video_enhance_ns
+                .saturating_sub(earlier.video_enhance_ns),
+            compute_ns: self.compute_ns.saturating_sub(earlier.compute_ns),
+            memory_bytes: self.memory_bytes,
+            resets: self.resets.saturating_sub(earlier.resets),
+            faults: self.faults.saturating_sub(earlier.faults),
+            engine_capacity: self.engine_capacity,
+            engine_cycles: self.engine_cycles.since(&earlier.engine_cycles),
+            card_id: self.card_id.clone(),
+        }
+    }
+}
+
+/// Busy and elapsed GPU cycles of one engine class
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrmCycles {
+    /// Cycles the client's work ran on engines of the class
+    pub busy: u64,
+    /// GPU timestamp cycles elapsed over the same time
+    pub total: u64,
+}
+
+impl DrmCycles {
+    /// Busy share of the elapsed cycles, spread over `capacity` engines
+    ///
+    /// `None` if no cycles elapsed. Capped at 100.
+    pub fn percent(&self, capacity: u32) -> Option<f64> {
+        (self.total > 0).then(|| {
+            clamp_percent(self.busy as f64 / (self.total as f64 * capacity.max(1) as f64) * 100.0)
+        })
+    }
+}
+
+/// GPU cycles of each engine class used by a DRM client
+///
+/// xe reports engine usage as `drm-cycles-<class>` and
+/// `drm-total-cycles-<class>` fdinfo lines rather than engine time, so
+/// utilization is the ratio of their deltas. All zero for drivers reporting
+/// engine time (i915, Windows).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrmEngineCycles {
+    /// Render/3D engines
+    pub render: DrmCycles,
+    /// Copy/Blitter engines
+    pub copy: DrmCycles,
+    /// Video engines
+    pub video: DrmCycles,
+    /// Video enhance engines
+    pub video_enhance: DrmCycles,
+    /// Compute engines
+    pub compute: DrmCycles,
+}
+
+impl DrmEngineCycles {
+    /// Cycles of one class
+    pub fn get(&self, class: EngineClass) -> DrmCycles {
+        match class {
+            EngineClass::Render => self.render,
+            EngineClass::Copy => self.copy,
+            EngineClass::Video => self.video,
+            EngineClass::VideoEnhance => self.video_enhance,
+            EngineClass::Compute => self.compute,
+        }
+    }
+
+    /// Mutable cycles of one class
+    pub fn get_mut(&mut self, class: EngineClass) -> &mut DrmCycles {
+        match class {
+            EngineClass::Render => &mut self.render,
+            EngineClass::Copy => &mut self.copy,
+            EngineClass::Video => &mut self.video,
+            EngineClass::VideoEnhance => &mut self.video_enhance,
+            EngineClass::Compute => &mut self.compute,
+        }
+    }
+
+    /// Cycles accumulated since an earlier reading, zero where they went
+    /// backwards
+    pub fn since(&self, earlier: &DrmEngineCycles) -> DrmEngineCycles {
+        let since = |now: DrmCycles, then: DrmCycles| DrmCycles {
+            busy: now.busy.saturating_sub(then.busy),
+            total: now.total.saturating_sub(then.total),
+        };
+        DrmEngineCycles {
+            render: since(self.render, earlier.render),
+            copy: since(self.copy, earlier.copy),
+            video: since(self.video, earlier.video),
+            video_enhance: since(self.video_enhance, earlier.video_enhance),
+            compute: since(self.compute, earlier.compute),
+        }
+    }
+}
+
+/// Number of engines of each class available to a DRM client
+///
+/// Read from the `drm-engine-capacity-<engine>` fdinfo lines. A client's
+/// engine time is summed over all engines of a class, so it can reach the
+/// capacity times the wall time. Classes without such a line have a
+/// capacity of 1, as the DRM fdinfo specification defines; so do all
+/// classes on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrmEngineCapacity {
+    /// Render/3D engines
+    pub render: u32,
+    /// Copy/Blitter engines
+    pub copy: u32,
+    /// Video engines
+    pub video: u32,
+    /// Video enhance engines
+    pub video_enhance: u32,
+    /// Compute engines
+    pub compute: u32,
+}
+
+impl Default for DrmEngineCapacity {
+    fn default() -> Self {
+        Self {
+            render: 1,
+            copy: 1,
+            video: 1,
+            video_enhance: 1,
+            compute: 1,
+        }
+    }
+}
+
+impl DrmEngineCapacity {
+    /// Number of engines of one class
+    pub fn get(&self, class: EngineClass) -> u32 {
+        match class {
+            EngineClass::Render => self.render,
+            EngineClass::Copy => self.copy,
+            EngineClass::Video => self.video,
+            EngineClass::VideoEnhance => self.video_enhance,
+            EngineClass::Compute => self.compute,
+        }
+    }
+
+    /// Set the number of engines of one class
+    pub fn set(&mut self, class: EngineClass, capacity: u32) {
+        let slot = match class {
+            EngineClass::Render => &mut self.render,
+            EngineClass::Copy => &mut self.copy,
+            EngineClass::Video => &mut self.video,
+            EngineClass::VideoEnhance => &mut self.video_enhance,
+            EngineClass::Compute => &mut self.compute,
+        };
+        *slot = capacity;
+    }
+}
+
+/// Per-process (DRM client) GPU utilization over a measurement window
+///
+/// Returned by `IntelGpu::sample_drm_clients`, or computed from two
+/// [`DrmClient`] lists with [`between`](Self::between).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DrmClientUtil {
+    /// Process ID
+    pub pid: u32,
+    /// Process name/command
+    pub name: String,
+    /// Card the usage is attributed to, as in [`DrmClient::card_id`]
+    pub card_id: Option<String>,
+    /// Render/3D engine busy percentage (0.0 - 100.0)
+    pub render_percent: f64,
+    /// Copy/Blitter engine busy percentage (0.0 - 100.0)
+    pub copy_percent: f64,
+    /// Video engine busy percentage (0.0 - 100.0)
+    pub video_percent: f64,
+    /// Video enhance engine busy percentage (0.0 - 100.0)
+    pub video_enhance_percent: f64,
+    /// Compute engine busy percentage (0.0 - 100.0)
+    pub compute_percent: f64,
+    /// GPU memory used at the end of the window in bytes
+    pub memory_bytes: u64,
+    /// Length of the window in nanoseconds
+    pub window_ns: u64,
+}
+
+impl DrmClientUtil {
+    /// Utilization from the engine time a client accumulated over `window_ns`
+    ///
+    /// `delta` is typically [`DrmClient::since`] of two snapshots. Engine
+    /// time is divided by the class' [`DrmClient::engine_capacity`], so a
+    /// client keeping both of two video engines busy is at 100% and one
+    /// keeping one of them busy at 50%. Percentages are capped at 100.
+    ///
+    /// Classes with [`DrmClient::engine_cycles`] use the ratio of busy to
+    /// elapsed cycles instead of engine time over `window_ns`.
+    pub fn from_delta(delta: &DrmClient, window_ns: u64) -> Self {
+        let percent = |ns: u64, class: EngineClass| {
+            let capacity = delta.engine_capacity.get(class).max(1);
+            if let Some(percent) = delta.engine_cycles.get(class).percent(capacity) {
+                percent
+            } else if window_ns == 0 {
+                0.0
+            } else {
+                clamp_percent(ns as f64 / (window_ns as f64 * capacity as f64) * 100.0)
+            }
+        };
+        Self {
+            pid: delta.pid,
+            name: delta.name.clone(),
+            card_id: delta.card_id.clone(),
+            render_percent: percent(delta.render_ns, EngineClass::Render),
+            copy_percent: percent(delta.copy_ns, EngineClass::Copy),
+            video_percent: percent(delta.video_ns, EngineClass::Video),
+            video_enhance_percent: percent(delta.video_enhance_ns, EngineClass::VideoEnhance),
+            compute_percent: percent(delta.compute_ns, EngineClass::Compute),
+            memory_bytes: delta.memory_bytes,
+            window_ns,
+        }
+    }
+
+    /// Utilization of every client in `after` over the window since `before`
+    ///
+    /// Clients are matched by PID and card. Clients missing from `before`
+    /// opened the GPU during the window, so all their engine time counts;
+    /// clients that exited are left out. Sorted by total busy percentage,
+    /// highest first.
+    pub fn between(before: &[DrmClient], after: &[DrmClient], window_ns: u64) -> Vec<Self> {
+        let mut clients: Vec<Self> = after
+            .iter()
+            .map(|client| {
+                let delta = match before
+                    .iter()
+                    .find(|earlier| earlier.pid == client.pid && earlier.card_id == client.card_id)
+                {
+                    Some(earlier) => client.since(earlier),
+                    None => client.clone(),
+                };
+                Self::from_delta(&delta, window_ns)
+            })
+            .collect();
+        clients.sort_by(|a, b| b.total_percent().total_cmp(&a.total_percent()));
+        clients
+    }
+
+    /// Busy percentage of one engine class
+    pub fn engine_percent(&self, class: EngineClass) -> f64 {
+        match class {
+            EngineClass::Render => self.render_percent,
+            EngineClass::Copy => self.copy_percent,
+            EngineClass::Video => self.video_percent,
+            EngineClass::VideoEnhance => self.video_enhance_percent,
+            EngineClass::Compute => self.compute_percent,
+        }
+    }
+
+    /// Sum of the busy percentages of all engine classes
+    ///
+    /// Can exceed 100 when the process keeps several engines busy.
+    pub fn total_percent(&self) -> f64 {
+        self.render_percent
+            + self.copy_percent
+            + self.video_percent
+            + self.video_enhance_percent
+            + self.compute_percent
+    }
+}
+
+/// Per-process engine time compared with the PMU's global busy time
+///
+/// Returned by [`GpuStats::client_discrepancy`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EngineDiscrepancy {
+    /// Engine class compared
+    pub class: EngineClass,
+    /// Busy time reported by the PMU over the sample, in nanoseconds
+    pub pmu_busy_ns: u64,
+    /// Sum of the clients' engine time over the same window, in nanoseconds
+    pub clients_busy_ns: u64,
+}
+
+impl EngineDiscrepancy {
+    /// Ratio of client time to PMU time
+    ///
+    /// 1.0 means the two agree. Well below 1.0 suggests clients that could
+    /// not be read (e.g. other users' processes) or that exited during the
+    /// window; well above 1.0 suggests kernel accounting issues. `None` when
+    /// the PMU saw no activity.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.pmu_busy_ns > 0).then(|| self.clients_busy_ns as f64 / self.pmu_busy_ns as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    fn now() -> Timestamp {
+        Timestamp::now()
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn now() -> Timestamp {
+        0
+    }
+
+    fn gpu_info(id: &str) -> GpuInfo {
+        GpuInfo {
+            id: id.into(),
+            pci_path: "/sys/devices/pci0000:00/0000:00:02.0".into(),
+            device_name: None,
+            vendor_id: 0x8086,
+            device_id: 0x56a0,
+            render_node: None,
+            card_node: None,
+            driver: Some(GpuDriver::I915),
+            driver_name: Some("i915".into()),
+            pcie_link_speed: None,
+            pcie_link_width: None,
+            media_engines: None,
+        }
+    }
+
+    #[test]
+    fn test_perf_state() {
+        let freq = FrequencyStats::new(1200, 2050);
+        assert_eq!(
+            PerfState::classify(&freq, Some(2050), false),
+            PerfState::Boost
+        );
+        assert_eq!(
+            PerfState::classify(&freq, Some(2400), false),
+            PerfState::Normal
+        );
+        assert_eq!(PerfState::classify(&freq, None, false), PerfState::Normal);
+        assert_eq!(
+            PerfState::classify(&freq, Some(2050), true),
+            PerfState::Idle
+        );
+        let parked = FrequencyStats::new(0, 300);
+        assert_eq!(
+            PerfState::classify(&parked, Some(2050), false),
+            PerfState::Idle
+        );
+        assert_eq!(
+            PerfState::from_u8(PerfState::Boost as u8),
+            Some(PerfState::Boost)
+        );
+        assert_eq!(PerfState::from_u8(3), None);
+    }
+
+    #[test]
+    fn test_normalized_load() {
+        let engines = EngineStats {
+            render: EngineUtilization::busy_only(50.0),
+            video: EngineUtilization::busy_only(20.0),
+            ..Default::default()
+        };
+
+        let low = FrequencyStats::new(300, 300);
+        let high = FrequencyStats::new(2400, 2400);
+        assert_eq!(engines.normalized_load(&low, 2400), 6.25);
+        assert_eq!(engines.normalized_load(&high, 2400), 50.0);
+        // Overshoot above the maximum is capped
+        assert_eq!(
+            engines.normalized_load(&FrequencyStats::new(2500, 2400), 2400),
+            50.0
+        );
+        assert_eq!(engines.normalized_load(&high, 0), 0.0);
+    }
+
+    #[test]
+    fn test_quicksync_indicator() {
+        let mut indicator = QuickSyncIndicator::new();
+        assert!(!indicator.update_percent(4.0));
+        assert!(indicator.update_percent(5.5));
+        assert!(indicator.update_percent(2.0));
+        assert!(!indicator.update_percent(1.9));
+        assert!(!indicator.update_percent(4.9));
+
+        let mut stats = GpuStats::new(now(), 1_000_000);
+        stats.engines.video_enhance = EngineUtilization::new(30.0, 0.0, 0.0);
+        assert!(indicator.update(&stats));
+        assert!(indicator.is_active());
+
+        let swapped = QuickSyncIndicator::with_thresholds(2.0, 10.0);
+        assert_eq!((swapped.on_percent, swapped.off_percent), (10.0, 2.0));
+    }
+
+    #[test]
+    fn test_engine_rounding() {
+        let engine = EngineUtilization::new(99.99996, -1e-12, f64::NAN);
+        assert_eq!(engine.busy_rounded(0), 100.0);
+        assert_eq!(engine.busy_rounded(2), 100.0);
+        assert_eq!(engine.wait_percent, Some(0.0));
+        assert_eq!(engine.sema_percent, Some(0.0));
+
+        let engine = EngineUtilization::new(42.345, 100.0000001, 0.0);
+        assert_eq!(engine.busy_rounded(0), 42.0);
+        assert_eq!(engine.busy_rounded(1), 42.3);
+        assert_eq!(engine.wait_percent, Some(100.0));
+        assert_eq!(engine.wait_rounded(1), Some(100.0));
+
+        let engine = EngineUtilization::busy_only(100.5);
+        assert_eq!(engine.busy_percent, 100.0);
+        assert_eq!(engine.peak_busy_percent, 100.0);
+        assert_eq!(engine.wait_rounded(1), None);
+        assert_eq!(engine.sema_percent, None);
+    }
+
+    #[test]
+    fn test_fan_mode() {
+        assert_eq!(FanMode::from_pwm_enable(0), FanMode::Off);
+        assert_eq!(FanMode::from_pwm_enable(1), FanMode::Manual);
+        assert_eq!(FanMode::from_pwm_enable(5), FanMode::Auto);
+        assert_eq!(
+            FanMode::from_pwm_enable(FanMode::Auto.pwm_enable()),
+            FanMode::Auto
+        );
+    }
+
+    #[test]
+    fn test_open_warning_display() {
+        let warning = OpenWarning::EngineUnavailable {
+            class: EngineClass::Compute,
+            instance: 0,
+            reason: "Permission denied".into(),
+        };
+        assert_eq!(
+            warning.to_string(),
+            "Compute engine instance 0 present in PMU but failed to open: Permission denied"
+        );
+    }
+
+    #[test]
+    fn test_is_integrated() {
+        let mut gpu = gpu_info("card0");
+        assert!(gpu.is_integrated());
+        gpu.pci_path = "../../../0000:00:01.0/0000:01:00.0/0000:03:00.0".into();
+        assert!(!gpu.is_integrated());
+        gpu.pci_path = "LUID:0000000000012345".into();
+        assert!(!gpu.is_integrated());
+    }
+
+    #[test]
+    fn test_pci_address() {
+        let mut gpu = gpu_info("card0");
+        assert_eq!(gpu.pci_address(), Some("0000:00:02.0"));
+        gpu.pci_path = "../../../0000:00:01.0/0000:01:00.0/0000:03:00.0".into();
+        assert_eq!(gpu.pci_address(), Some("0000:03:00.0"));
+        gpu.pci_path = "LUID:0000000000012345".into();
+        assert_eq!(gpu.pci_address(), None);
+        gpu.pci_path = String::new();
+        assert_eq!(gpu.pci_address(), None);
+    }
+
+    #[test]
+    fn test_memory_used_percent() {
+        let memory = MemoryStats {
+            dedicated_total_bytes: 8 << 30,
+            dedicated_used_bytes: 2 << 30,
+            ..Default::default()
+        };
+        assert_eq!(memory.dedicated_used_percent(), Some(25.0));
+        assert_eq!(memory.shared_used_percent(), None);
+    }
+
+    #[test]
+    fn test_media_engine_count() {
+        let mut gpu = gpu_info("card0");
+        assert_eq!(gpu.media_engine_count(), Some(4));
+        gpu.media_engines = Some(3);
+        assert_eq!(gpu.media_engine_count(), Some(3));
+        gpu.media_engines = None;
+        gpu.device_id = 0x1234;
+        assert_eq!(gpu.media_engine_count(), None);
+    }
+
+    #[test]
+    fn test_gpu_info_identity() {
+        let gpu = gpu_info("card0");
+        let mut rediscovered = gpu.clone();
+        rediscovered.pcie_link_speed = Some("2.5 GT/s PCIe".into());
+        rediscovered.pcie_link_width = Some(1);
+        assert_eq!(gpu, rediscovered);
+        assert_ne!(gpu, gpu_info("card1"));
+
+        let mut states = std::collections::HashMap::new();
+        states.insert(gpu, 1);
+        assert_eq!(states.get(&rediscovered), Some(&1));
+        assert_eq!(states.get(&gpu_info("card1")), None);
+    }
+
+    #[test]
+    fn test_efficiency_metric() {
+        let mut stats = GpuStats::new(now(), 1_000_000);
+        stats.engines.video.busy_percent = 60.0;
+        assert!(stats.efficiency_metric().is_none());
+        assert!(stats.watts_per_percent().is_none());
+
+        stats.power = Some(PowerStats::new(12.0, None));
+        assert!((stats.efficiency_metric().unwrap() - 5.0).abs() < 1e-9);
+        assert!((stats.watts_per_percent().unwrap() - 0.2).abs() < 1e-9);
+
+        stats.engines.video.busy_percent = 0.0;
+        assert!(stats.watts_per_percent().is_none());
+    }
+
+    #[test]
+    fn test_frequency_starvation() {
+        let mut stats = GpuStats::new(now(), 1_000_000);
+        stats.frequency = FrequencyStats::new(900, 1300).with_min_mhz(Some(300));
+        assert!(stats.frequency.is_frequency_starved());
+        assert_eq!(stats.frequency_starvation(), Some(Vec::new()));
+
+        stats.throttle = Some(ThrottleInfo {
+            is_throttled: true,
+            power_limit: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            stats.frequency_starvation(),
+            Some(vec![ThrottleReason::PowerLimit])
+        );
+
+        // Idle at the minimum frequency, or keeping up with the request
+        stats.frequency = FrequencyStats::new(0, 300).with_min_mhz(Some(300));
+        assert_eq!(stats.frequency_starvation(), None);
+        stats.frequency = FrequencyStats::new(1250, 1300);
+        assert!(!stats.frequency.is_frequency_starved());
+    }
+
+    #[test]
+    fn test_summary_defaults() {
+        let mut stats = GpuStats::new(now(), 1_000_000);
+        stats.engines.video.busy_percent = 30.0;
+        stats.engines.video_enhance.busy_percent = 70.0;
+        stats.frequency = FrequencyStats::new(1200, 1300);
+
+        let summary = stats.summary();
+        assert_eq!(summary.busy_percent, 70.0);
+        assert_eq!(summary.encode_percent, 70.0);
+        assert_eq!(summary.decode_percent, 30.0);
+        assert_eq!(summary.actual_mhz, 1200);
+        assert_eq!(summary.gpu_watts, 0.0);
+        assert_eq!(summary.temperature_celsius, 0.0);
+        assert!(!summary.throttled);
+
+        stats.throttle = Some(ThrottleInfo {
+            thermal: true,
+            ..Default::default()
+        });
+        assert!(stats.summary().throttled);
+    }
+
+    #[test]
+    fn test_quicksync_utilization_weighted() {
+        let mut engines = EngineStats {
+            video: EngineUtilization::new(50.0, 0.0, 0.0),
+            video_instances: vec![
+                EngineUtilization::new(50.0, 0.0, 0.0),
+                EngineUtilization::new(50.0, 0.0, 0.0),
+            ],
+            ..Default::default()
+        };
+
+        // Equal weights: same figure regardless of instance count
+        let equal = MediaEngineWeights::default();
+        assert!((engines.quicksync_utilization_weighted(&equal) - 50.0).abs() < 1e-9);
+
+        // vcs0 is twice as capable and idle, vcs1 is saturated
+        engines.video_instances[0].busy_percent = 0.0;
+        engines.video_instances[1].busy_percent = 100.0;
+        let weights = MediaEngineWeights::new(vec![2.0, 1.0], Vec::new());
+        let weighted = engines.quicksync_utilization_weighted(&weights);
+        assert!((weighted - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_merge_media() {
+        let mut engines = EngineStats {
+            video: EngineUtilization::new(20.0, 1.0, 0.0),
+            video_enhance: EngineUtilization::busy_only(70.0).with_busy_ns(7_000),
+            video_instances: vec![EngineUtilization::new(20.0, 1.0, 0.0)],
+            video_enhance_instances: vec![EngineUtilization::busy_only(70.0)],
+            ..Default::default()
+        };
+        let media = engines.media();
+        assert_eq!(media.busy_percent, 70.0);
+        assert_eq!(media.busy_ns, 7_000);
+        assert_eq!(media.wait_percent, None);
+        assert_eq!(media.busy_percent, engines.quicksync_utilization());
+
+        engines.merge_media();
+        assert_eq!(engines.video.busy_percent, 70.0);
+        assert!(engines.video_enhance.is_idle());
+        assert_eq!(engines.video_instances.len(), 2);
+        assert!(engines.video_enhance_instances.is_empty());
+        assert_eq!(engines.quicksync_utilization(), 70.0);
+    }
+
+    #[test]
+    fn test_report_compute() {
+        let arc = EngineStats {
+            render: EngineUtilization::new(60.0, 1.0, 0.0).with_busy_ns(6_000),
+            compute: Some(EngineUtilization::new(50.0, 2.0, 0.0).with_busy_ns(5_000)),
+            compute_instances: vec![EngineUtilization::busy_only(50.0)],
+            ..Default::default()
+        };
+
+        let mut auto = arc.clone();
+        auto.report_compute(ComputeReporting::Auto);
+        assert_eq!(auto.compute.unwrap().busy_percent, 50.0);
+        assert_eq!(auto.render.busy_percent, 60.0);
+
+        let mut merged = arc.clone();
+        merged.report_compute(ComputeReporting::MergeIntoRender);
+        assert!(merged.compute.is_none());
+        assert!(merged.compute_instances.is_empty());
+        assert_eq!(merged.render.busy_percent, 100.0);
+        assert_eq!(merged.render.wait_percent, Some(3.0));
+        assert_eq!(merged.render.busy_ns, 11_000);
+
+        // Integrated GPUs without a compute engine
+        let mut separate = EngineStats::default();
+        separate.report_compute(ComputeReporting::Separate);
+        assert!(separate.compute.unwrap().is_idle());
+        let mut merged = EngineStats::default();
+        merged.report_compute(ComputeReporting::MergeIntoRender);
+        assert!(merged.compute.is_none());
+        assert!(merged.render.is_idle());
+    }
+
+    #[test]
+    fn test_copy_from() {
+        let mut source = GpuStats::new(now(), 100_000_000);
+        source.engines.video_instances = vec![EngineUtilization::busy_only(40.0); 2];
+        source.frequency = FrequencyStats::new(900, 1000);
+        source.rc6 = Some(Rc6Stats::new(50.0));
+
+        let mut out = GpuStats::new(now(), 0);
+        out.engines.compute = Some(EngineUtilization::busy_only(10.0));
+        out.engines.video_instances = Vec::with_capacity(8);
+        let buffer = out.engines.video_instances.as_ptr();
+
+        out.copy_from(&source);
+        assert_eq!(out.sample_duration_ns, 100_000_000);
+        assert_eq!(out.sample_duration(), Duration::from_millis(100));
+        assert_eq!(out.engines.video_instances.len(), 2);
+        assert_eq!(out.engines.video_instances.as_ptr(), buffer);
+        assert!(out.engines.compute.is_none());
+        assert_eq!(out.frequency.actual_mhz, 900);
+        assert_eq!(out.rc6.unwrap().residency_percent, 50.0);
+    }
+
+    #[test]
+    fn test_client_discrepancy() {
+        let mut stats = GpuStats::new(now(), 100_000_000);
+        stats.engines.render = EngineUtilization::new(50.0, 0.0, 0.0);
+        stats.engines.video_instances = vec![
+            EngineUtilization::new(20.0, 0.0, 0.0),
+            EngineUtilization::new(10.0, 0.0, 0.0),
+        ];
+
+        let mut before = DrmClient::new(42, "ffmpeg".into());
+        before.render_ns = 1_000_000;
+        let mut after = before.clone();
+        after.render_ns += 25_000_000;
+        after.video_ns = 30_000_000;
+
+        let report = stats.client_discrepancy(&[after.since(&before)]);
+        assert_eq!(report.len(), 4);
+        assert_eq!(report[0].class, EngineClass::Render);
+        assert_eq!(report[0].pmu_busy_ns, 50_000_000);
+        assert_eq!(report[0].ratio(), Some(0.5));
+        // Video counts both instances
+        assert_eq!(report[2].pmu_busy_ns, 30_000_000);
+        assert_eq!(report[2].ratio(), Some(1.0));
+        // Idle blitter has no ratio
+        assert_eq!(report[1].ratio(), None);
+    }
+
+    #[test]
+    fn test_drm_client_util_between() {
+        let mut before = DrmClient::new(42, "ffmpeg".into());
+        before.video_ns = 5_000_000;
+        let mut after = before.clone();
+        after.video_ns += 40_000_000;
+        after.render_ns = 150_000_000;
+        after.memory_bytes = 4096;
+        let mut started = DrmClient::new(7, "vainfo".into());
+        started.render_ns = 10_000_000;
+        let exited = DrmClient::new(9, "old".into());
+
+        let util =
+            DrmClientUtil::between(&[before, exited], &[started.clone(), after], 100_000_000);
+        assert_eq!(util.len(), 2);
+        assert_eq!(util[0].pid, 42);
+        assert_eq!(util[0].video_percent, 40.0);
+        assert_eq!(util[0].engine_percent(EngineClass::Video), 40.0);
+        // Several contexts can exceed the window
+        assert_eq!(util[0].render_percent, 100.0);
+        assert_eq!(util[0].memory_bytes, 4096);
+        assert_eq!(util[1].pid, 7);
+        assert_eq!(util[1].render_percent, 10.0);
+        assert_eq!(util[1].total_percent(), 10.0);
+
+        assert_eq!(DrmClientUtil::from_delta(&started, 0).render_percent, 0.0);
+
+        // Two video engines: 100ms of engine time in 100ms is half the capacity
+        let mut both = DrmClient::new(5, "ffmpeg".into());
+        both.video_ns = 100_000_000;
+        both.engine_capacity.set(EngineClass::Video, 2);
+        assert_eq!(both.engine_capacity.get(EngineClass::Render), 1);
+        assert_eq!(
+            DrmClientUtil::from_delta(&both, 100_000_000).video_percent,
+            50.0
+        );
+        both.video_ns = 200_000_000;
+        assert_eq!(
+            DrmClientUtil::from_delta(&both, 100_000_000).video_percent,
+            100.0
+        );
+
+        // xe: a quarter of the cycles on one of two video engines
+        let mut xe = DrmClient::new(6, "ffmpeg".into());
+        xe.engine_capacity.set(EngineClass::Video, 2);
+        xe.engine_cycles.video = DrmCycles {
+            busy: 1_000,
+            total: 10_000,
+        };
+        let mut later = xe.clone();
+        later.engine_cycles.video = DrmCycles {
+            busy: 3_000,
+            total: 14_000,
+        };
+        assert!(later.is_using_quicksync());
+        let util = DrmClientUtil::between(&[xe], &[later], 100_000_000);
+        assert_eq!(util[0].video_percent, 25.0);
+        assert_eq!(util[0].render_percent, 0.0);
+    }
+
+    #[test]
+    fn test_throttle_changes() {
+        let previous = ThrottleInfo {
+            is_throttled: true,
+            power_limit: true,
+            ..Default::default()
+        };
+        let current = ThrottleInfo {
+            is_throttled: true,
+            thermal: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            current.changes_from(&previous),
+            vec![
+                ThrottleEvent {
+                    reason: ThrottleReason::PowerLimit,
+                    active: false,
+                },
+                ThrottleEvent {
+                    reason: ThrottleReason::Thermal,
+                    active: true,
+                },
+            ]
+        );
+        assert!(current.changes_from(&current).is_empty());
+    }
+
+    #[test]
+    fn test_active_reasons() {
+        let info = ThrottleInfo {
+            is_throttled: true,
+            thermal: true,
+            vr_tdc: true,
+            ..Default::default()
+        };
+        let reasons = info.active_reasons();
+        assert_eq!(
+            reasons,
+            vec![ThrottleReason::Thermal, ThrottleReason::VrTdc]
+        );
+
+        let names: Vec<String> = reasons.iter().map(|r| r.to_string()).collect();
+        assert_eq!(names.join(", "), "Thermal, VR TDC");
+        assert!(ThrottleInfo::new().active_reasons().is_empty());
     }
 }
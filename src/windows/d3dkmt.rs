@@ -5,13 +5,15 @@
 
 use std::collections::HashMap;
 use std::ffi::c_void;
-use std::mem::{size_of, zeroed};
+use std::mem::{offset_of, size_of, zeroed};
 use std::ptr::null_mut;
+use std::sync::OnceLock;
 
 use windows::Win32::Foundation::{CloseHandle, HANDLE, LUID, NTSTATUS};
 use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory1, IDXGIFactory1};
 use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ};
 
+use super::dxgi::luid_path;
 use crate::error::{Error, Result};
 use crate::types::*;
 
@@ -20,9 +22,21 @@ const STATUS_SUCCESS: i32 = 0;
 
 // D3DKMT statistics types
 const D3DKMT_QUERYSTATISTICS_ADAPTER: u32 = 0;
+const D3DKMT_QUERYSTATISTICS_SEGMENT: u32 = 3;
 const D3DKMT_QUERYSTATISTICS_NODE: u32 = 4;
 
-// Engine type mappings for Intel GPUs
+// D3DKMTQueryAdapterInfo types
+const KMTQAITYPE_NODEMETADATA: u32 = 25;
+
+// DXGK_ENGINE_TYPE values reported in node metadata
+const DXGK_ENGINE_TYPE_OTHER: u32 = 0;
+const DXGK_ENGINE_TYPE_3D: u32 = 1;
+const DXGK_ENGINE_TYPE_VIDEO_DECODE: u32 = 2;
+const DXGK_ENGINE_TYPE_VIDEO_ENCODE: u32 = 3;
+const DXGK_ENGINE_TYPE_VIDEO_PROCESSING: u32 = 4;
+const DXGK_ENGINE_TYPE_COPY: u32 = 6;
+
+// Fallback node ordinals for drivers that don't report node metadata
 // These are typical node ordinals for Intel GPU engines
 const ENGINE_NODE_3D: u32 = 0; // Render/3D
 const ENGINE_NODE_COPY: u32 = 1; // Blitter/Copy
@@ -53,6 +67,26 @@ struct D3DKMT_QUERYADAPTERINFO {
     private_driver_data_size: u32,
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct D3DKMT_NODEMETADATA {
+    /// Node ordinal in the low word, physical adapter index in the high word
+    node_ordinal_and_adapter_index: u32,
+    node_data: DXGK_NODEMETADATA,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct DXGK_NODEMETADATA {
+    engine_type: u32,
+    friendly_name: [u16; 32],
+    _flags: u32,
+    _gpu_mmu_supported: u8,
+    _io_mmu_supported: u8,
+}
+
+// Layout of the Windows SDK's D3DKMT_QUERYSTATISTICS: the query input
+// (segment or node id) follows the result union, not the common fields.
 #[repr(C)]
 #[derive(Clone, Copy)]
 struct D3DKMT_QUERYSTATISTICS {
@@ -60,6 +94,7 @@ struct D3DKMT_QUERYSTATISTICS {
     adapter_luid: LUID,
     h_process: HANDLE,
     query_result: D3DKMT_QUERYSTATISTICS_RESULT,
+    query: D3DKMT_QUERYSTATISTICS_QUERY,
 }
 
 #[repr(C)]
@@ -67,10 +102,30 @@ struct D3DKMT_QUERYSTATISTICS {
 union D3DKMT_QUERYSTATISTICS_RESULT {
     adapter_info: D3DKMT_QUERYSTATISTICS_ADAPTER_INFORMATION,
     node_info: D3DKMT_QUERYSTATISTICS_NODE_INFORMATION,
+    segment_info: D3DKMT_QUERYSTATISTICS_SEGMENT_INFORMATION,
     process_info: D3DKMT_QUERYSTATISTICS_PROCESS_INFORMATION,
-    _padding: [u8; 512], // Ensure union is large enough
+    // Size of the SDK union, whose largest members are not declared here
+    _padding: [u8; 776],
+}
+
+// Segment, node and physical adapter queries all take a single id
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct D3DKMT_QUERYSTATISTICS_QUERY {
+    id: u32,
 }
 
+// Guard the layout against the SDK's (sizeof == 0x328 on 64-bit)
+const _: () = assert!(size_of::<D3DKMT_QUERYSTATISTICS_RESULT>() == 776);
+const _: () = assert!(
+    offset_of!(D3DKMT_QUERYSTATISTICS, query)
+        == offset_of!(D3DKMT_QUERYSTATISTICS, query_result) + 776
+);
+#[cfg(target_pointer_width = "64")]
+const _: () = assert!(offset_of!(D3DKMT_QUERYSTATISTICS, query_result) == 24);
+#[cfg(target_pointer_width = "64")]
+const _: () = assert!(size_of::<D3DKMT_QUERYSTATISTICS>() == 0x328);
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 struct D3DKMT_QUERYSTATISTICS_ADAPTER_INFORMATION {
@@ -94,6 +149,25 @@ struct D3DKMT_QUERYSTATISTICS_NODE_GLOBAL_INFO {
     _reserved: [u32; 3],
 }
 
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct D3DKMT_QUERYSTATISTICS_SEGMENT_INFORMATION {
+    commit_limit: u64,    // Size of the segment in bytes
+    bytes_committed: u64, // Bytes committed to the segment
+    bytes_resident: u64,  // Bytes resident in the segment
+    memory: D3DKMT_QUERYSTATISTICS_MEMORY,
+    aperture: u32, // Non-zero for aperture (system memory) segments
+    _reserved: [u64; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct D3DKMT_QUERYSTATISTICS_MEMORY {
+    total_bytes_evicted: u64,
+    allocs_committed: u32,
+    allocs_resident: u32,
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Default)]
 struct D3DKMT_QUERYSTATISTICS_PROCESS_INFORMATION {
@@ -114,7 +188,6 @@ struct D3dkmtFunctions {
     open_adapter: FnD3DKMTOpenAdapterFromLuid,
     close_adapter: FnD3DKMTCloseAdapter,
     query_statistics: FnD3DKMTQueryStatistics,
-    #[allow(dead_code)]
     query_adapter_info: FnD3DKMTQueryAdapterInfo,
 }
 
@@ -173,30 +246,42 @@ impl D3dkmtFunctions {
     }
 }
 
-// Thread-local D3DKMT functions
-thread_local! {
-    static D3DKMT: std::cell::RefCell<Option<D3dkmtFunctions>> = const { std::cell::RefCell::new(None) };
-}
+// Process-wide D3DKMT functions
+//
+// The entry points are plain function pointers into gdi32.dll, which stays
+// loaded for the lifetime of the process, so a single table can be shared
+// across threads. An adapter opened on one thread can be queried from another.
+static D3DKMT: OnceLock<D3dkmtFunctions> = OnceLock::new();
 
 fn with_d3dkmt<T, F: FnOnce(&D3dkmtFunctions) -> T>(f: F) -> Result<T> {
-    D3DKMT.with(|cell| {
-        let mut opt = cell.borrow_mut();
-        if opt.is_none() {
-            *opt = Some(D3dkmtFunctions::load()?);
+    let funcs = match D3DKMT.get() {
+        Some(funcs) => funcs,
+        None => {
+            // Two threads may race to load; LoadLibrary is reference counted
+            // and both tables are identical, so whichever wins is fine.
+            let loaded = D3dkmtFunctions::load()?;
+            D3DKMT.get_or_init(|| loaded)
         }
-        Ok(f(opt.as_ref().unwrap()))
-    })
+    };
+    Ok(f(funcs))
 }
 
 /// D3DKMT adapter handle wrapper
+///
+/// Holds only the kernel adapter handle and LUID, both plain integers, so it
+/// is `Send` and may be closed from a different thread than it was opened on.
 pub struct D3dkmtAdapter {
     h_adapter: u32,
     adapter_luid: LUID,
     node_count: u32,
+    segment_count: u32,
 }
 
 impl D3dkmtAdapter {
     /// Open a D3DKMT adapter from GpuInfo
+    ///
+    /// The adapter is found by the LUID in `gpu_info.pci_path` when present,
+    /// else by its `adapterN` index.
     pub fn open(gpu_info: &GpuInfo) -> Result<Self> {
         // Get the LUID from DXGI
         let factory: IDXGIFactory1 = unsafe { CreateDXGIFactory1() }.map_err(|e| Error::Io {
@@ -205,6 +290,7 @@ impl D3dkmtAdapter {
         })?;
 
         // Find the adapter matching our GPU
+        let by_luid = gpu_info.pci_path.starts_with("LUID:");
         let mut adapter_index = 0u32;
         let mut found_luid: Option<LUID> = None;
 
@@ -215,8 +301,12 @@ impl D3dkmtAdapter {
             };
 
             if let Ok(desc) = unsafe { adapter.GetDesc1() } {
-                let id = format!("adapter{}", adapter_index);
-                if id == gpu_info.id {
+                let matches = if by_luid {
+                    luid_path(&desc.AdapterLuid) == gpu_info.pci_path
+                } else {
+                    format!("adapter{}", adapter_index) == gpu_info.id
+                };
+                if matches {
                     found_luid = Some(desc.AdapterLuid);
                     break;
                 }
@@ -245,18 +335,21 @@ impl D3dkmtAdapter {
             Ok(())
         })??;
 
-        // Query adapter info to get node count
-        let node_count = Self::query_node_count(adapter_luid)?;
+        // Query adapter info to get node and segment counts
+        let adapter_info = Self::query_adapter_info(adapter_luid)?;
 
         Ok(Self {
             h_adapter: open_adapter.h_adapter,
             adapter_luid,
-            node_count,
+            node_count: adapter_info.node_count,
+            segment_count: adapter_info.segment_count,
         })
     }
 
-    /// Query the number of GPU nodes
-    fn query_node_count(adapter_luid: LUID) -> Result<u32> {
+    /// Query the number of GPU nodes and memory segments
+    fn query_adapter_info(
+        adapter_luid: LUID,
+    ) -> Result<D3DKMT_QUERYSTATISTICS_ADAPTER_INFORMATION> {
         let mut query: D3DKMT_QUERYSTATISTICS = unsafe { zeroed() };
         query.query_type = D3DKMT_QUERYSTATISTICS_ADAPTER;
         query.adapter_luid = adapter_luid;
@@ -270,12 +363,60 @@ impl D3dkmtAdapter {
                     source: std::io::Error::new(std::io::ErrorKind::Other, "D3DKMT error"),
                 });
             }
-            Ok(unsafe { query.query_result.adapter_info.node_count })
+            Ok(unsafe { query.query_result.adapter_info })
         })?
     }
 
     /// Query the mapping of engine classes to node ordinals
+    ///
+    /// Built from the engine type the driver reports for each node. Drivers
+    /// that don't report node metadata (before WDDM 2.0) get the typical
+    /// Intel layout instead.
     pub fn query_node_mapping(&self) -> Result<HashMap<EngineClass, u32>> {
+        let nodes: Option<Vec<NodeDescription>> = (0..self.node_count)
+            .map(|ordinal| self.query_node_metadata(ordinal).ok())
+            .collect();
+        match nodes {
+            Some(nodes) if !nodes.is_empty() => Ok(node_mapping_from_descriptions(&nodes)),
+            _ => Ok(self.default_node_mapping()),
+        }
+    }
+
+    /// Query the engine type and name of a node
+    fn query_node_metadata(&self, ordinal: u32) -> Result<NodeDescription> {
+        let mut metadata: D3DKMT_NODEMETADATA = unsafe { zeroed() };
+        metadata.node_ordinal_and_adapter_index = ordinal;
+
+        let mut query = D3DKMT_QUERYADAPTERINFO {
+            h_adapter: self.h_adapter,
+            info_type: KMTQAITYPE_NODEMETADATA,
+            private_driver_data: &mut metadata as *mut _ as *mut c_void,
+            private_driver_data_size: size_of::<D3DKMT_NODEMETADATA>() as u32,
+        };
+
+        with_d3dkmt(|funcs| {
+            let status = unsafe { (funcs.query_adapter_info)(&mut query) };
+            if status.0 != STATUS_SUCCESS {
+                return Err(Error::Io {
+                    context: format!(
+                        "D3DKMTQueryAdapterInfo (node {} metadata) failed: 0x{:08x}",
+                        ordinal, status.0
+                    ),
+                    source: std::io::Error::new(std::io::ErrorKind::Other, "D3DKMT error"),
+                });
+            }
+            let name = &metadata.node_data.friendly_name;
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            Ok(NodeDescription {
+                ordinal,
+                engine_type: metadata.node_data.engine_type,
+                name: String::from_utf16_lossy(&name[..len]),
+            })
+        })?
+    }
+
+    /// Typical Intel node layout, used when node metadata is unavailable
+    fn default_node_mapping(&self) -> HashMap<EngineClass, u32> {
         let mut mapping = HashMap::new();
 
         // Intel GPUs typically have a fixed node layout
@@ -301,7 +442,7 @@ impl D3dkmtAdapter {
             mapping.insert(EngineClass::Compute, ENGINE_NODE_COMPUTE);
         }
 
-        Ok(mapping)
+        mapping
     }
 
     /// Get the adapter LUID
@@ -329,6 +470,97 @@ impl Drop for D3dkmtAdapter {
     }
 }
 
+/// Engine type and name the driver reports for a GPU node
+#[derive(Debug, Clone)]
+pub struct NodeDescription {
+    /// Node ordinal
+    pub ordinal: u32,
+    /// `DXGK_ENGINE_TYPE` of the node
+    pub engine_type: u32,
+    /// Driver-provided name, e.g. "Compute"
+    pub name: String,
+}
+
+/// Engine class of a node, if it is one we track
+///
+/// Intel drivers report compute nodes as `DXGK_ENGINE_TYPE_OTHER`, so those
+/// are recognized by name.
+fn engine_class_of(node: &NodeDescription) -> Option<EngineClass> {
+    match node.engine_type {
+        DXGK_ENGINE_TYPE_3D => Some(EngineClass::Render),
+        DXGK_ENGINE_TYPE_COPY => Some(EngineClass::Copy),
+        DXGK_ENGINE_TYPE_VIDEO_DECODE => Some(EngineClass::Video),
+        DXGK_ENGINE_TYPE_VIDEO_ENCODE | DXGK_ENGINE_TYPE_VIDEO_PROCESSING => {
+            Some(EngineClass::VideoEnhance)
+        }
+        DXGK_ENGINE_TYPE_OTHER if node.name.to_ascii_lowercase().contains("compute") => {
+            Some(EngineClass::Compute)
+        }
+        _ => None,
+    }
+}
+
+/// Map each engine class to the first node of that class
+///
+/// A video encode node takes precedence over a video processing node for
+/// [`EngineClass::VideoEnhance`], as encode load is what it reports.
+pub fn node_mapping_from_descriptions(nodes: &[NodeDescription]) -> HashMap<EngineClass, u32> {
+    let mut mapping = HashMap::new();
+    for node in nodes {
+        if let Some(class) = engine_class_of(node) {
+            mapping.entry(class).or_insert(node.ordinal);
+        }
+    }
+    if let Some(encode) = nodes
+        .iter()
+        .find(|node| node.engine_type == DXGK_ENGINE_TYPE_VIDEO_ENCODE)
+    {
+        mapping.insert(EngineClass::VideoEnhance, encode.ordinal);
+    }
+    mapping
+}
+
+/// Cumulative counters of a GPU node (engine)
+#[derive(Debug, Clone, Copy)]
+pub struct NodeCounters {
+    /// Total running time in nanoseconds
+    pub running_time_ns: u64,
+    /// Total context switches, wrapping at `u32::MAX`
+    pub context_switches: u32,
+}
+
+/// Size and usage of a GPU memory segment
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentCounters {
+    /// Segment size in bytes
+    pub commit_limit: u64,
+    /// Bytes committed to the segment
+    pub bytes_committed: u64,
+    /// True for aperture segments, which map shared system memory
+    pub aperture: bool,
+}
+
+/// Sum segments into dedicated (local) and shared (aperture) memory
+pub fn memory_from_segments(segments: &[SegmentCounters]) -> MemoryStats {
+    let mut memory = MemoryStats::default();
+    for segment in segments {
+        let (total, used) = if segment.aperture {
+            (
+                &mut memory.shared_total_bytes,
+                &mut memory.shared_used_bytes,
+            )
+        } else {
+            (
+                &mut memory.dedicated_total_bytes,
+                &mut memory.dedicated_used_bytes,
+            )
+        };
+        *total += segment.commit_limit;
+        *used += segment.bytes_committed;
+    }
+    memory
+}
+
 /// Statistics query helper
 pub struct D3dkmtQueryStatistics<'a> {
     adapter: &'a D3dkmtAdapter,
@@ -340,61 +572,79 @@ impl<'a> D3dkmtQueryStatistics<'a> {
         Self { adapter }
     }
 
-    /// Query running time for a specific node (in nanoseconds)
-    pub fn query_node_running_time(&self, node_id: u32) -> Result<u64> {
-        // Use the query_node structure with proper node_id
-        #[repr(C)]
-        #[derive(Clone, Copy)]
-        struct QueryNodeInput {
-            query_type: u32,
-            adapter_luid: LUID,
-            h_process: HANDLE,
-            node_id: u32,
-        }
+    /// Query the cumulative counters of a specific node
+    pub fn query_node(&self, node_id: u32) -> Result<NodeCounters> {
+        let description = format!("node {}", node_id);
+        let result = self.query(D3DKMT_QUERYSTATISTICS_NODE, node_id, &description)?;
+        // Every bit pattern is a valid node information struct
+        let node_info = unsafe { result.node_info };
 
-        let mut query_bytes = [0u8; size_of::<D3DKMT_QUERYSTATISTICS>()];
+        Ok(NodeCounters {
+            // Convert from 100ns units to nanoseconds
+            running_time_ns: node_info.global_info.running_time * 100,
+            context_switches: node_info.global_info.context_switch,
+        })
+    }
+
+    /// Query the size and usage of a specific memory segment
+    pub fn query_segment(&self, segment_id: u32) -> Result<SegmentCounters> {
+        let description = format!("segment {}", segment_id);
+        let result = self.query(D3DKMT_QUERYSTATISTICS_SEGMENT, segment_id, &description)?;
+        // Every bit pattern is a valid segment information struct
+        let segment_info = unsafe { result.segment_info };
+
+        Ok(SegmentCounters {
+            commit_limit: segment_info.commit_limit,
+            bytes_committed: segment_info.bytes_committed,
+            aperture: segment_info.aperture != 0,
+        })
+    }
+
+    /// Query dedicated and shared memory usage over all segments
+    ///
+    /// Returns `None` if no segment could be queried.
+    pub fn query_memory(&self) -> Option<MemoryStats> {
+        let segments: Vec<SegmentCounters> = (0..self.adapter.segment_count)
+            .filter_map(|segment_id| self.query_segment(segment_id).ok())
+            .collect();
+        if segments.is_empty() {
+            return None;
+        }
+        Some(memory_from_segments(&segments))
+    }
 
-        // Set up the input part
-        let input = QueryNodeInput {
-            query_type: D3DKMT_QUERYSTATISTICS_NODE,
+    /// Run a per-node or per-segment statistics query
+    ///
+    /// `id` is the node or segment ordinal. Returns the result union, whose
+    /// member matching `query_type` is filled in.
+    fn query(
+        &self,
+        query_type: u32,
+        id: u32,
+        description: &str,
+    ) -> Result<D3DKMT_QUERYSTATISTICS_RESULT> {
+        let mut query = D3DKMT_QUERYSTATISTICS {
+            query_type,
             adapter_luid: self.adapter.adapter_luid,
             h_process: HANDLE(null_mut()),
-            node_id,
+            query_result: D3DKMT_QUERYSTATISTICS_RESULT { _padding: [0; 776] },
+            query: D3DKMT_QUERYSTATISTICS_QUERY { id },
         };
 
-        // Copy input to query bytes
-        unsafe {
-            std::ptr::copy_nonoverlapping(
-                &input as *const _ as *const u8,
-                query_bytes.as_mut_ptr(),
-                size_of::<QueryNodeInput>(),
-            );
-        }
-
         with_d3dkmt(|funcs| {
-            let status = unsafe {
-                (funcs.query_statistics)(query_bytes.as_mut_ptr() as *mut D3DKMT_QUERYSTATISTICS)
-            };
+            let status = unsafe { (funcs.query_statistics)(&mut query) };
 
             if status.0 != STATUS_SUCCESS {
                 return Err(Error::Io {
                     context: format!(
-                        "D3DKMTQueryStatistics (node {}) failed: 0x{:08x}",
-                        node_id, status.0
+                        "D3DKMTQueryStatistics ({}) failed: 0x{:08x}",
+                        description, status.0
                     ),
                     source: std::io::Error::new(std::io::ErrorKind::Other, "D3DKMT error"),
                 });
             }
 
-            // Read the result - running_time is at a known offset in the result
-            // The result structure starts after the input fields
-            let result_offset = size_of::<QueryNodeInput>();
-            let result_ptr = query_bytes.as_ptr().wrapping_add(result_offset)
-                as *const D3DKMT_QUERYSTATISTICS_NODE_INFORMATION;
-            let node_info = unsafe { *result_ptr };
-
-            // Convert from 100ns units to nanoseconds
-            Ok(node_info.global_info.running_time * 100)
+            Ok(query.query_result)
         })?
     }
 
@@ -526,4 +776,47 @@ mod tests {
     fn test_status_success() {
         assert_eq!(STATUS_SUCCESS, 0);
     }
+
+    #[test]
+    fn test_node_mapping_from_descriptions() {
+        let node = |ordinal, engine_type, name: &str| NodeDescription {
+            ordinal,
+            engine_type,
+            name: name.into(),
+        };
+        // Arc layout: encode is not on node 3
+        let mapping = node_mapping_from_descriptions(&[
+            node(0, DXGK_ENGINE_TYPE_3D, "3D"),
+            node(1, DXGK_ENGINE_TYPE_OTHER, "Compute"),
+            node(2, DXGK_ENGINE_TYPE_COPY, "Copy"),
+            node(3, DXGK_ENGINE_TYPE_VIDEO_DECODE, "VideoDecode"),
+            node(4, DXGK_ENGINE_TYPE_VIDEO_PROCESSING, "VideoProcessing"),
+            node(5, DXGK_ENGINE_TYPE_VIDEO_ENCODE, "VideoEncode"),
+            node(6, DXGK_ENGINE_TYPE_VIDEO_DECODE, "VideoDecode"),
+        ]);
+        assert_eq!(mapping[&EngineClass::Render], 0);
+        assert_eq!(mapping[&EngineClass::Compute], 1);
+        assert_eq!(mapping[&EngineClass::Copy], 2);
+        assert_eq!(mapping[&EngineClass::Video], 3);
+        assert_eq!(mapping[&EngineClass::VideoEnhance], 5);
+        assert_eq!(mapping.len(), 5);
+    }
+
+    #[test]
+    fn test_memory_from_segments() {
+        let segment = |commit_limit, bytes_committed, aperture| SegmentCounters {
+            commit_limit,
+            bytes_committed,
+            aperture,
+        };
+        let memory = memory_from_segments(&[
+            segment(8 << 30, 1 << 30, false),
+            segment(256 << 20, 0, false),
+            segment(16 << 30, 512 << 20, true),
+        ]);
+        assert_eq!(memory.dedicated_total_bytes, (8 << 30) + (256 << 20));
+        assert_eq!(memory.dedicated_used_bytes, 1 << 30);
+        assert_eq!(memory.shared_total_bytes, 16 << 30);
+        assert_eq!(memory.shared_used_bytes, 512 << 20);
+    }
 }
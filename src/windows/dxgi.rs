@@ -1,15 +1,27 @@
 //! DXGI adapter enumeration for finding Intel GPUs
 
+use windows::Win32::Foundation::LUID;
 use windows::Win32::Graphics::Dxgi::{
-    CreateDXGIFactory1, IDXGIAdapter1, IDXGIFactory1, DXGI_ADAPTER_DESC1,
+    CreateDXGIFactory1, IDXGIAdapter1, IDXGIFactory1, DXGI_ADAPTER_DESC1, DXGI_ADAPTER_FLAG_REMOTE,
+    DXGI_ADAPTER_FLAG_SOFTWARE,
 };
 
 use crate::error::{Error, Result};
-use crate::types::GpuInfo;
+use crate::types::{GpuDriver, GpuInfo};
 
 /// Intel vendor ID
 const INTEL_VENDOR_ID: u32 = 0x8086;
 
+/// `GpuInfo::pci_path` stand-in identifying an adapter by its LUID
+///
+/// Unlike the `adapterN` index, the LUID stays the same while the system
+/// runs, so several adapters can be opened side by side without mixing
+/// them up if the enumeration order changes.
+pub fn luid_path(luid: &LUID) -> String {
+    let luid = ((luid.HighPart as i64) << 32) | (luid.LowPart as i64);
+    format!("LUID:{:016x}", luid)
+}
+
 /// DXGI factory wrapper for GPU enumeration
 pub struct DxgiEnumerator {
     factory: IDXGIFactory1,
@@ -27,6 +39,10 @@ impl DxgiEnumerator {
     }
 
     /// Enumerate all Intel GPUs
+    ///
+    /// Software and remote adapters are skipped even if they report Intel's
+    /// vendor ID (see `is_hardware_adapter`), since D3DKMT can't query
+    /// them.
     pub fn enumerate_intel_gpus(&self) -> Result<Vec<GpuInfo>> {
         let mut gpus = Vec::new();
         let mut adapter_index = 0u32;
@@ -40,7 +56,7 @@ impl DxgiEnumerator {
 
             if let Ok(desc) = unsafe { adapter.GetDesc1() } {
                 // Check if this is an Intel GPU
-                if desc.VendorId == INTEL_VENDOR_ID {
+                if desc.VendorId == INTEL_VENDOR_ID && is_hardware_adapter(&desc) {
                     let gpu_info = adapter_desc_to_gpu_info(&desc, adapter_index);
                     gpus.push(gpu_info);
                 }
@@ -113,17 +129,29 @@ impl DxgiEnumerator {
     }
 }
 
+/// Check if an adapter is a physical GPU
+///
+/// Excludes adapters flagged as software (e.g. the "Microsoft Basic Render
+/// Driver" or WARP) or remote (e.g. in an RDP session), and adapters
+/// without any memory, which no physical GPU has.
+fn is_hardware_adapter(desc: &DXGI_ADAPTER_DESC1) -> bool {
+    let excluded = (DXGI_ADAPTER_FLAG_SOFTWARE.0 | DXGI_ADAPTER_FLAG_REMOTE.0) as u32;
+    let memory = desc
+        .DedicatedVideoMemory
+        .saturating_add(desc.DedicatedSystemMemory)
+        .saturating_add(desc.SharedSystemMemory);
+    desc.Flags & excluded == 0 && memory > 0
+}
+
 /// Convert DXGI adapter description to GpuInfo
 fn adapter_desc_to_gpu_info(desc: &DXGI_ADAPTER_DESC1, adapter_index: u32) -> GpuInfo {
     // Convert wide string description to Rust string
     let device_name = wchar_to_string(&desc.Description);
 
-    // Create a unique ID from the LUID
-    let luid = ((desc.AdapterLuid.HighPart as i64) << 32) | (desc.AdapterLuid.LowPart as i64);
     let id = format!("adapter{}", adapter_index);
 
     // Create PCI-style path from LUID
-    let pci_path = format!("LUID:{:016x}", luid);
+    let pci_path = luid_path(&desc.AdapterLuid);
 
     GpuInfo {
         id,
@@ -133,7 +161,11 @@ fn adapter_desc_to_gpu_info(desc: &DXGI_ADAPTER_DESC1, adapter_index: u32) -> Gp
         device_id: desc.DeviceId as u16,
         render_node: None, // Not applicable on Windows
         card_node: None,   // Not applicable on Windows
-        driver: None,      // Windows uses unified driver
+        driver: Some(GpuDriver::WindowsDch),
+        driver_name: None,
+        pcie_link_speed: None,
+        pcie_link_width: None,
+        media_engines: None, // Not known from D3DKMT nodes
     }
 }
 
@@ -155,6 +187,35 @@ mod tests {
         assert_eq!(wchar_to_string(&wchar), "Hello");
     }
 
+    #[test]
+    fn test_is_hardware_adapter() {
+        let gpu = DXGI_ADAPTER_DESC1 {
+            VendorId: INTEL_VENDOR_ID,
+            DedicatedVideoMemory: 128 << 20,
+            SharedSystemMemory: 8 << 30,
+            ..Default::default()
+        };
+        assert!(is_hardware_adapter(&gpu));
+
+        let software = DXGI_ADAPTER_DESC1 {
+            Flags: DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32,
+            ..gpu
+        };
+        assert!(!is_hardware_adapter(&software));
+
+        let remote = DXGI_ADAPTER_DESC1 {
+            Flags: DXGI_ADAPTER_FLAG_REMOTE.0 as u32,
+            ..gpu
+        };
+        assert!(!is_hardware_adapter(&remote));
+
+        let no_memory = DXGI_ADAPTER_DESC1 {
+            VendorId: INTEL_VENDOR_ID,
+            ..Default::default()
+        };
+        assert!(!is_hardware_adapter(&no_memory));
+    }
+
     #[test]
     fn test_wchar_to_string_no_null() {
         let wchar: [u16; 5] = ['H' as u16, 'e' as u16, 'l' as u16, 'l' as u16, 'o' as u16];
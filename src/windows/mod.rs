@@ -8,74 +8,70 @@ mod dxgi;
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use std::thread::{self, JoinHandle};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::builder::OpenOptions;
 use crate::error::{Error, Result};
+use crate::logging;
+use crate::sampling::{read_window, SamplingOptions, SamplingTimer, Subscribers};
+use crate::snapshot::{percent, AbsoluteStats, CounterSnapshot, EngineSnapshot, IN_USE_WINDOW};
+use crate::throttle_history::{lock_shared, ThrottleTracker};
 use crate::types::*;
 
+pub use crate::sampling::SamplingHandle;
+
 use d3dkmt::{D3dkmtAdapter, D3dkmtQueryStatistics};
 use dxgi::DxgiEnumerator;
 
-/// Handle for controlling background sampling
-pub struct SamplingHandle {
-    stop_flag: Arc<AtomicBool>,
-    thread: Option<JoinHandle<()>>,
-}
-
-impl SamplingHandle {
-    /// Stop the background sampling
-    pub fn stop(mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        if let Some(thread) = self.thread.take() {
-            let _ = thread.join();
-        }
-    }
-
-    /// Check if sampling is still running
-    pub fn is_running(&self) -> bool {
-        !self.stop_flag.load(Ordering::SeqCst)
-    }
-}
-
-impl Drop for SamplingHandle {
-    fn drop(&mut self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
-        if let Some(thread) = self.thread.take() {
-            let _ = thread.join();
-        }
-    }
-}
-
 /// Tracks engine usage over time for utilization calculation
 struct EngineTracker {
-    last_running_time: u64,
+    /// Running time at the last update, `None` until a baseline is known
+    last_running_time: Option<u64>,
     last_timestamp: Instant,
+    /// Context switch count at the last update, `None` until a baseline is known
+    last_context_switches: Option<u32>,
 }
 
 impl EngineTracker {
-    fn new() -> Self {
+    /// Create a tracker with the baseline taken at `now`
+    ///
+    /// Without a baseline (the node could not be queried), the first update
+    /// only records one and reports an idle engine.
+    fn new(running_time: Option<u64>, now: Instant) -> Self {
         Self {
-            last_running_time: 0,
-            last_timestamp: Instant::now(),
+            last_running_time: running_time,
+            last_timestamp: now,
+            last_context_switches: None,
         }
     }
 
-    fn update(&mut self, current_running_time: u64, now: Instant) -> f64 {
+    /// Returns the context switches since the last update
+    ///
+    /// `None` on the first call, which only records the baseline. The
+    /// 32-bit counter may wrap between updates.
+    fn update_context_switches(&mut self, current: u32) -> Option<u64> {
+        let delta = self
+            .last_context_switches
+            .map(|last| current.wrapping_sub(last) as u64);
+        self.last_context_switches = Some(current);
+        delta
+    }
+
+    /// Returns the busy percentage and busy nanoseconds since the last update
+    fn update(&mut self, current_running_time: u64, now: Instant) -> (f64, u64) {
         let elapsed = now.duration_since(self.last_timestamp);
         let elapsed_ns = elapsed.as_nanos() as u64;
 
-        let delta = current_running_time.saturating_sub(self.last_running_time);
+        let delta = self
+            .last_running_time
+            .map_or(0, |last| current_running_time.saturating_sub(last));
 
-        self.last_running_time = current_running_time;
+        self.last_running_time = Some(current_running_time);
         self.last_timestamp = now;
 
-        if elapsed_ns > 0 {
-            (delta as f64 / elapsed_ns as f64 * 100.0).min(100.0)
-        } else {
-            0.0
-        }
+        (percent(delta, elapsed_ns), delta)
     }
 }
 
@@ -83,6 +79,10 @@ impl EngineTracker {
 ///
 /// This struct provides access to Intel GPU statistics on Windows through
 /// the D3DKMT API for performance queries.
+///
+/// `IntelGpu` is `Send`: it can be moved into another thread or a blocking
+/// task (e.g. `tokio::task::spawn_blocking`). Reads take `&mut self`, so wrap
+/// it in a `Mutex` if several threads need to read from the same instance.
 pub struct IntelGpu {
     /// GPU information
     gpu_info: GpuInfo,
@@ -92,10 +92,16 @@ pub struct IntelGpu {
     engine_trackers: HashMap<EngineClass, EngineTracker>,
     /// Last read timestamp
     last_timestamp: Instant,
+    /// Stats returned by the most recent read
+    last_stats: Option<GpuStats>,
+    /// Options set through the builder
+    options: OpenOptions,
     /// Whether compute engine is available
     has_compute: bool,
     /// Available node ordinals for each engine type
     node_mapping: HashMap<EngineClass, u32>,
+    /// Throttle residency history, shared with the sampling handle
+    throttle_history: Arc<Mutex<ThrottleTracker>>,
 }
 
 impl IntelGpu {
@@ -104,7 +110,7 @@ impl IntelGpu {
         let gpus = Self::list_gpus()?;
         let gpu = gpus.into_iter().next().ok_or(Error::NoGpuFound)?;
 
-        Self::open_gpu(gpu)
+        Self::open_info(gpu)
     }
 
     /// Open a specific GPU by card ID (e.g., "adapter0" or the LUID string)
@@ -117,17 +123,36 @@ impl IntelGpu {
                     path: card_id.into(),
                 })?;
 
-        Self::open_gpu(gpu)
+        Self::open_info(gpu)
     }
 
     /// List all available Intel GPUs
+    ///
+    /// `GpuInfo::media_engines` is left unset: D3DKMT nodes don't map one to
+    /// one onto media engines, so `GpuInfo::media_engine_count` uses the
+    /// table of known devices.
     pub fn list_gpus() -> Result<Vec<GpuInfo>> {
         let enumerator = DxgiEnumerator::new()?;
         enumerator.enumerate_intel_gpus()
     }
 
-    /// Internal: open GPU with the given info
-    fn open_gpu(gpu_info: GpuInfo) -> Result<Self> {
+    /// Number of engine instances of each GPU
+    ///
+    /// Not known before an adapter is opened, so every GPU counts as having
+    /// none and ranking by engine count keeps the discovery order.
+    pub(crate) fn engine_counts(gpus: &[GpuInfo]) -> Vec<usize> {
+        vec![0; gpus.len()]
+    }
+
+    /// Open a GPU previously returned by [`list_gpus`](Self::list_gpus)
+    ///
+    /// Skips re-enumerating adapters through DXGI before opening.
+    pub fn open_info(gpu_info: GpuInfo) -> Result<Self> {
+        Self::open_with_options(gpu_info, OpenOptions::default())
+    }
+
+    /// Internal: open GPU with builder options
+    pub(crate) fn open_with_options(gpu_info: GpuInfo, options: OpenOptions) -> Result<Self> {
         // Open D3DKMT adapter
         let adapter = D3dkmtAdapter::open(&gpu_info)?;
 
@@ -135,34 +160,143 @@ impl IntelGpu {
         let node_mapping = adapter.query_node_mapping()?;
         let has_compute = node_mapping.contains_key(&EngineClass::Compute);
 
-        // Initialize engine trackers
-        let mut engine_trackers = HashMap::new();
-        for engine_class in node_mapping.keys() {
-            engine_trackers.insert(*engine_class, EngineTracker::new());
-        }
-
         let mut gpu = Self {
             gpu_info,
             adapter,
-            engine_trackers,
+            engine_trackers: HashMap::new(),
             last_timestamp: Instant::now(),
+            last_stats: None,
+            options,
             has_compute,
             node_mapping,
+            throttle_history: Arc::new(Mutex::new(ThrottleTracker::new())),
         };
-
-        // Prime the trackers with initial values
-        let _ = gpu.read_stats();
+        gpu.prime_trackers();
 
         Ok(gpu)
     }
 
+    /// Record the baseline running time of every engine
+    ///
+    /// All trackers and the read timestamp share one instant, so the first
+    /// [`read_stats`](Self::read_stats) measures exactly the window since
+    /// opening.
+    fn prime_trackers(&mut self) {
+        let query = D3dkmtQueryStatistics::new(&self.adapter);
+        let now = Instant::now();
+        self.engine_trackers = self
+            .node_mapping
+            .iter()
+            .map(|(engine_class, node_ordinal)| {
+                let node = query.query_node(*node_ordinal).ok();
+                let mut tracker = EngineTracker::new(node.map(|n| n.running_time_ns), now);
+                if let Some(node) = node {
+                    tracker.update_context_switches(node.context_switches);
+                }
+                (*engine_class, tracker)
+            })
+            .collect();
+        self.last_timestamp = now;
+        self.last_stats = None;
+    }
+
+    /// Restart the measurement window
+    ///
+    /// D3DKMT statistics are queried on demand and cannot be stopped, so
+    /// this only makes the next `read_stats` cover the time since this call,
+    /// matching the Linux behavior.
+    pub fn enable_counters(&mut self) -> Result<()> {
+        self.prime_trackers();
+        Ok(())
+    }
+
+    /// Stop counting while idle
+    ///
+    /// D3DKMT has no counters to stop, so this does nothing on Windows.
+    pub fn disable_counters(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Read the raw cumulative counters, for one-shot tools
+    ///
+    /// Unlike `read_stats`, a single call is meaningful: it reports the
+    /// counters' totals without computing percentages. The D3DKMT running
+    /// times are cumulative since the adapter started, an unknown point, so
+    /// `counting_ns` is `None`.
+    pub fn read_absolute(&mut self) -> Result<AbsoluteStats> {
+        Ok(AbsoluteStats {
+            counters: self.snapshot_counters()?,
+            counting_ns: None,
+        })
+    }
+
+    /// Check whether any engine is doing work right now
+    ///
+    /// Watches the D3DKMT engine running times for a short window (50 ms)
+    /// instead of enumerating processes like
+    /// [`list_drm_clients`](Self::list_drm_clients), so it blocks for that
+    /// long. Returns `false` if the counters can't be read.
+    pub fn is_in_use(&mut self) -> bool {
+        let Ok(start) = self.snapshot_counters() else {
+            return false;
+        };
+        thread::sleep(IN_USE_WINDOW);
+        self.snapshot_counters()
+            .is_ok_and(|end| end.is_busy_since(&start))
+    }
+
+    /// Capture the cumulative counters for measuring a custom window
+    ///
+    /// Unlike `read_stats`, this does not advance the reader's own baseline,
+    /// so `read_stats` calls in between are unaffected and vice versa. Pass
+    /// two snapshots to [`GpuStats::from_snapshots`] to get the stats over
+    /// the window between them. D3DKMT only provides engine running time and
+    /// context switches; frequency and RC6 are not included.
+    pub fn snapshot_counters(&mut self) -> Result<CounterSnapshot> {
+        let query = D3dkmtQueryStatistics::new(&self.adapter);
+        let timestamp = Instant::now();
+
+        let mut engines = Vec::with_capacity(self.node_mapping.len());
+        for (engine_class, node_ordinal) in &self.node_mapping {
+            let node = query.query_node(*node_ordinal)?;
+            engines.push(EngineSnapshot {
+                class: *engine_class,
+                instance: 0,
+                primary: true,
+                busy: node.running_time_ns,
+                wait: None,
+                sema: None,
+                total_ticks: None,
+                context_switches: Some(node.context_switches),
+            });
+        }
+        engines.sort_by_key(|e| e.class as u16);
+
+        Ok(CounterSnapshot {
+            timestamp,
+            engines,
+            actual_frequency: None,
+            requested_frequency: None,
+            rc6: Vec::new(),
+        })
+    }
+
     /// Read current GPU statistics
     ///
     /// Returns a snapshot of the current GPU state. The utilization percentages
     /// are calculated based on the time elapsed since the last read.
+    ///
+    /// If called again within the minimum sample interval (1ms by default,
+    /// see [`IntelGpuBuilder::min_sample_interval`](crate::IntelGpuBuilder::min_sample_interval)),
+    /// the previous stats are returned unchanged.
     pub fn read_stats(&mut self) -> Result<GpuStats> {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_timestamp);
+        if elapsed < self.options.min_sample_interval {
+            if let Some(ref previous) = self.last_stats {
+                return Ok(previous.clone());
+            }
+        }
         let elapsed_ns = elapsed.as_nanos() as u64;
 
         let mut stats = GpuStats::new(now, elapsed_ns);
@@ -172,10 +306,13 @@ impl IntelGpu {
 
         // Query each engine type
         for (engine_class, node_ordinal) in &self.node_mapping {
-            if let Ok(running_time) = query.query_node_running_time(*node_ordinal) {
+            if let Ok(node) = query.query_node(*node_ordinal) {
                 if let Some(tracker) = self.engine_trackers.get_mut(engine_class) {
-                    let busy_percent = tracker.update(running_time, now);
-                    let utilization = EngineUtilization::new(busy_percent, 0.0, 0.0);
+                    let (busy_percent, busy_ns) = tracker.update(node.running_time_ns, now);
+                    let mut utilization =
+                        EngineUtilization::busy_only(busy_percent).with_busy_ns(busy_ns);
+                    utilization.context_switches =
+                        tracker.update_context_switches(node.context_switches);
 
                     match engine_class {
                         EngineClass::Render => stats.engines.render = utilization,
@@ -199,66 +336,232 @@ impl IntelGpu {
         // Query power if available
         stats.power = query.query_power();
 
+        // Dedicated and shared memory from the adapter's segments
+        stats.memory = query.query_memory();
+
         // Note: RC6 and detailed throttle info are not available through D3DKMT
         // These are Linux-specific concepts
+        lock_shared(&self.throttle_history).record(now, stats.throttle.as_ref());
+
+        stats.engines.report_compute(self.options.compute_reporting);
+        if self.options.merge_media {
+            stats.engines.merge_media();
+        }
 
         self.last_timestamp = now;
+        self.last_stats = Some(stats.clone());
 
         Ok(stats)
     }
 
+    /// Read current GPU statistics into an existing `GpuStats`
+    ///
+    /// Same API as on Linux. D3DKMT reports no per-instance engine lists, so
+    /// there is nothing to reuse and this simply copies a
+    /// [`read_stats`](Self::read_stats) result into `out`.
+    pub fn read_stats_into(&mut self, out: &mut GpuStats) -> Result<()> {
+        let stats = self.read_stats()?;
+        out.copy_from(&stats);
+        Ok(())
+    }
+
     /// Start continuous sampling with a callback
     ///
     /// The callback will be called with GPU statistics at the specified interval.
     /// Returns a handle that can be used to stop sampling.
-    pub fn start_sampling<F>(
+    pub fn start_sampling<F>(self, interval: Duration, callback: F) -> Result<SamplingHandle>
+    where
+        F: FnMut(GpuStats) + Send + 'static,
+    {
+        self.start_sampling_with(SamplingOptions::new(interval), callback)
+    }
+
+    /// Start continuous sampling with custom options
+    ///
+    /// Like [`start_sampling`](Self::start_sampling), but allows polling at a
+    /// faster inner rate to track peak utilization within each interval. See
+    /// [`SamplingOptions`].
+    pub fn start_sampling_with<F>(
         mut self,
-        interval: Duration,
+        options: SamplingOptions,
         mut callback: F,
     ) -> Result<SamplingHandle>
     where
         F: FnMut(GpuStats) + Send + 'static,
     {
+        options.validate()?;
+
         let stop_flag = Arc::new(AtomicBool::new(false));
         let stop_flag_clone = stop_flag.clone();
+        let throttle_history = self.throttle_history.clone();
+        let timer = SamplingTimer::new(options.interval);
+        let thread_timer = timer.clone();
+        let subscribers = Subscribers::new();
+        let thread_subscribers = subscribers.clone();
 
         let thread = thread::spawn(move || {
             while !stop_flag_clone.load(Ordering::SeqCst) {
-                thread::sleep(interval);
+                let result = match options.inner_interval {
+                    Some(inner) => read_window(options.interval, inner, || self.read_stats()),
+                    None => {
+                        thread::sleep(options.interval);
+                        self.read_stats()
+                    }
+                };
 
-                match self.read_stats() {
-                    Ok(stats) => callback(stats),
+                match result {
+                    Ok(stats) => {
+                        thread_timer.record(Instant::now());
+                        thread_subscribers.publish(&stats);
+                        callback(stats)
+                    }
                     Err(e) => {
-                        eprintln!("Error reading GPU stats: {}", e);
+                        logging::warn(format_args!("Error reading GPU stats: {}", e));
                         break;
                     }
                 }
             }
+            thread_subscribers.close();
         });
 
-        Ok(SamplingHandle {
-            stop_flag,
-            thread: Some(thread),
+        Ok(
+            SamplingHandle::new(stop_flag, thread, throttle_history, timer)
+                .with_subscribers(subscribers),
+        )
+    }
+
+    /// Watch throttle reasons on a dedicated thread
+    ///
+    /// Throttle reasons are not available through D3DKMT, so this always
+    /// returns [`Error::FeatureNotAvailable`].
+    pub fn watch_throttle<F>(&self, _interval: Duration, _callback: F) -> Result<SamplingHandle>
+    where
+        F: FnMut(ThrottleEvent) + Send + 'static,
+    {
+        Err(Error::FeatureNotAvailable {
+            feature: "Throttle monitoring".to_string(),
         })
     }
 
+    /// Percentage of the last `window` each throttle reason was active
+    ///
+    /// Throttle reasons are not available through D3DKMT, so this is
+    /// currently always empty on Windows.
+    pub fn throttle_residency(&self, window: Duration) -> HashMap<ThrottleReason, f64> {
+        lock_shared(&self.throttle_history).residency(window)
+    }
+
+    /// Get all monitoring capabilities in one snapshot
+    pub fn capabilities(&self) -> Capabilities {
+        let query = D3dkmtQueryStatistics::new(&self.adapter);
+        Capabilities {
+            render: self.node_mapping.contains_key(&EngineClass::Render),
+            video: self.node_mapping.contains_key(&EngineClass::Video),
+            video_enhance: self.node_mapping.contains_key(&EngineClass::VideoEnhance),
+            blitter: self.node_mapping.contains_key(&EngineClass::Copy),
+            compute: self.node_mapping.contains_key(&EngineClass::Compute),
+            frequency: query.query_frequency().is_ok(),
+            // RC6 is not available through D3DKMT
+            rc6: false,
+            temperature: self.has_temperature(),
+            fan: self.has_fan(),
+            throttle: self.has_throttle(),
+            power: self.has_power(),
+        }
+    }
+
+    /// Non-fatal problems met while opening the GPU
+    ///
+    /// Always empty on Windows; see [`capabilities`](Self::capabilities) for
+    /// what is available.
+    pub fn open_warnings(&self) -> &[OpenWarning] {
+        &[]
+    }
+
     /// Get information about this GPU
     pub fn gpu_info(&self) -> &GpuInfo {
         &self.gpu_info
     }
 
-    /// Check if compute engine is available (Intel Arc GPUs)
+    /// Check if dedicated compute engines (CCS) are available (Intel Arc GPUs)
+    ///
+    /// Without them compute work (OpenCL, oneAPI, XMX) runs on the render
+    /// engine and is counted in render utilization.
     pub fn has_compute_engine(&self) -> bool {
         self.has_compute
     }
 
+    /// Number of compute engine (CCS) instances
+    ///
+    /// D3DKMT exposes at most one compute node per adapter, so this is 1 if
+    /// [`has_compute_engine`](Self::has_compute_engine) and 0 otherwise.
+    pub fn compute_instance_count(&self) -> u32 {
+        self.has_compute as u32
+    }
+
     /// Get the driver type in use
     ///
-    /// On Windows, we report as I915 for compatibility, though the actual
-    /// driver is the Intel Graphics Driver for Windows.
+    /// Always [`GpuDriver::WindowsDch`], the Intel Graphics Driver for Windows.
     pub fn driver(&self) -> GpuDriver {
-        // Windows uses a unified driver, report as I915 for API compatibility
-        GpuDriver::I915
+        GpuDriver::WindowsDch
+    }
+
+    /// Check if the GPU has a counter for its actual frequency
+    ///
+    /// Always `false`: D3DKMT does not report GPU frequencies.
+    pub fn has_frequency_event(&self) -> bool {
+        false
+    }
+
+    /// Check if a reported frequency of 0 MHz can be trusted
+    ///
+    /// Always `false`: the frequency is always reported as 0 MHz on Windows.
+    pub fn has_working_frequency(&self) -> bool {
+        false
+    }
+
+    /// Source of the actual frequency reported by the most recent read
+    ///
+    /// Always [`FrequencySource::Unavailable`]: D3DKMT does not report GPU
+    /// frequencies.
+    pub fn actual_frequency_source(&self) -> FrequencySource {
+        FrequencySource::Unavailable
+    }
+
+    /// Source of the requested frequency reported by the most recent read
+    ///
+    /// Always [`FrequencySource::Unavailable`]: D3DKMT does not report GPU
+    /// frequencies.
+    pub fn requested_frequency_source(&self) -> FrequencySource {
+        FrequencySource::Unavailable
+    }
+
+    /// Hardware maximum frequency in MHz
+    ///
+    /// Always `None`: D3DKMT does not report GPU frequencies.
+    pub fn max_frequency_mhz(&self) -> Option<u32> {
+        None
+    }
+
+    /// List the GT (tile) ids
+    ///
+    /// Always `[0]`: D3DKMT reports each adapter as a single GT.
+    pub fn tiles(&self) -> Vec<u16> {
+        vec![0]
+    }
+
+    /// Get the frequency of a specific GT (tile)
+    ///
+    /// Always `None`: D3DKMT does not report GPU frequencies.
+    pub fn frequency_for_tile(&self, _tile: u16) -> Option<FrequencyStats> {
+        None
+    }
+
+    /// Get the RC6 residency of a specific GT (tile)
+    ///
+    /// Always `None`: D3DKMT does not report RC6 residency.
+    pub fn rc6_for_tile(&self, _tile: u16) -> Option<Rc6Stats> {
+        None
     }
 
     /// Check if temperature monitoring is available
@@ -288,6 +591,58 @@ impl IntelGpu {
             .is_some()
     }
 
+    /// Check if system memory bandwidth is reported
+    ///
+    /// Always `false`: no bandwidth counters are read on Windows.
+    pub fn has_system_memory_bandwidth(&self) -> bool {
+        false
+    }
+
+    /// Switch the fan to manual control with a fixed PWM duty cycle (0-255)
+    ///
+    /// Fan control is not available on Windows, so this always returns
+    /// [`Error::FeatureNotAvailable`].
+    pub fn set_fan_pwm(&self, _pwm: u8) -> Result<()> {
+        Err(Error::FeatureNotAvailable {
+            feature: "Fan control".to_string(),
+        })
+    }
+
+    /// Read the automatic fan curve
+    ///
+    /// Fan curves are not exposed on Windows, so this is always empty.
+    pub fn fan_curve(&self) -> Vec<(f64, u8)> {
+        Vec::new()
+    }
+
+    /// Return the fan to automatic control
+    ///
+    /// Fan control is not available on Windows, so this always returns
+    /// [`Error::FeatureNotAvailable`].
+    pub fn set_fan_auto(&self) -> Result<()> {
+        Err(Error::FeatureNotAvailable {
+            feature: "Fan control".to_string(),
+        })
+    }
+
+    /// Current GPU power limit in watts
+    ///
+    /// Power limits are not exposed through D3DKMT, so this is always `None`
+    /// on Windows.
+    pub fn power_limit_watts(&self) -> Option<f64> {
+        None
+    }
+
+    /// Set the GPU power limit in watts
+    ///
+    /// Power limits are not exposed through D3DKMT, so this always returns
+    /// [`Error::FeatureNotAvailable`].
+    pub fn set_power_limit_watts(&self, _watts: f64) -> Result<()> {
+        Err(Error::FeatureNotAvailable {
+            feature: "Power limit control".to_string(),
+        })
+    }
+
     /// List all processes using the GPU
     ///
     /// Returns a list of processes that are using GPU resources.
@@ -296,6 +651,46 @@ impl IntelGpu {
         d3dkmt::list_gpu_processes().unwrap_or_default()
     }
 
+    /// List GPU processes among the given PIDs only
+    ///
+    /// Processes are still enumerated as in
+    /// [`list_drm_clients`](Self::list_drm_clients); the result is then
+    /// limited to `pids`.
+    pub fn list_drm_clients_for_pids(pids: &[u32]) -> Vec<DrmClient> {
+        Self::list_drm_clients()
+            .into_iter()
+            .filter(|c| pids.contains(&c.pid))
+            .collect()
+    }
+
+    /// Measure per-process utilization over `window`
+    ///
+    /// Lists the GPU processes twice, `window` apart, and turns the engine
+    /// time each process accumulated in between into percentages of the
+    /// measured window (see [`DrmClientUtil::between`]). Blocks for
+    /// `window`.
+    pub fn sample_drm_clients(window: Duration) -> Vec<DrmClientUtil> {
+        let before = Self::list_drm_clients();
+        let start = Instant::now();
+        thread::sleep(window);
+        let after = Self::list_drm_clients();
+        let window_ns = start.elapsed().as_nanos() as u64;
+        DrmClientUtil::between(&before, &after, window_ns)
+    }
+
+    /// Count the processes using the GPU
+    pub fn drm_client_count() -> usize {
+        Self::list_drm_clients().len()
+    }
+
+    /// Count the GPU contexts of all processes
+    ///
+    /// D3DKMT has no cheap per-context view, so this is the process count,
+    /// the same as [`drm_client_count`](Self::drm_client_count).
+    pub fn drm_context_count() -> usize {
+        Self::drm_client_count()
+    }
+
     /// Find processes using Quick Sync (video encode/decode)
     ///
     /// Returns only processes that are actively using the video
@@ -306,6 +701,21 @@ impl IntelGpu {
             .filter(|c| c.is_using_quicksync())
             .collect()
     }
+
+    /// List processes using a specific GPU (e.g. "adapter0")
+    ///
+    /// Returns [`Error::DeviceNotFound`] if no such GPU exists. D3DKMT
+    /// process usage is not split by adapter, so on multi-GPU systems this
+    /// is the same list as [`list_drm_clients`](Self::list_drm_clients),
+    /// including processes using other GPUs.
+    pub fn list_drm_clients_for(card_id: &str) -> Result<Vec<DrmClient>> {
+        if !Self::list_gpus()?.iter().any(|g| g.id == card_id) {
+            return Err(Error::DeviceNotFound {
+                path: card_id.into(),
+            });
+        }
+        Ok(Self::list_drm_clients())
+    }
 }
 
 #[cfg(test)]
@@ -314,14 +724,44 @@ mod tests {
 
     #[test]
     fn test_engine_tracker() {
-        let mut tracker = EngineTracker::new();
-        let now = Instant::now();
+        let mut tracker = EngineTracker::new(Some(0), Instant::now());
 
         // Simulate 50% utilization over 100ms
         std::thread::sleep(Duration::from_millis(100));
-        let percent = tracker.update(50_000_000, Instant::now()); // 50ms of running time
+        let (percent, _) = tracker.update(50_000_000, Instant::now()); // 50ms of running time
 
         // Should be roughly 50% (with some tolerance for timing)
         assert!(percent >= 40.0 && percent <= 60.0);
     }
+
+    #[test]
+    fn test_context_switches() {
+        let mut tracker = EngineTracker::new(Some(0), Instant::now());
+        assert_eq!(tracker.update_context_switches(100), None);
+        assert_eq!(tracker.update_context_switches(250), Some(150));
+        // The counter wrapped
+        assert_eq!(
+            tracker.update_context_switches(5),
+            Some(u32::MAX as u64 - 244)
+        );
+    }
+
+    #[test]
+    fn test_engine_tracker_first_interval() {
+        // The first window runs exactly from the baseline instant
+        let start = Instant::now();
+        let mut tracker = EngineTracker::new(Some(7_000_000_000), start);
+        let (percent, busy_ns) = tracker.update(7_025_000_000, start + Duration::from_millis(100));
+        assert_eq!(busy_ns, 25_000_000);
+        assert!((percent - 25.0).abs() < 1e-9);
+
+        // Without a baseline the lifetime running time is not reported as busy
+        let mut tracker = EngineTracker::new(None, start);
+        assert_eq!(
+            tracker.update(7_000_000_000, start + Duration::from_millis(100)),
+            (0.0, 0)
+        );
+        let (percent, _) = tracker.update(7_050_000_000, start + Duration::from_millis(200));
+        assert!((percent - 50.0).abs() < 1e-9);
+    }
 }